@@ -0,0 +1,37 @@
+//! Times compressing a large texture into DXT1 blocks, which goes through the image one 4x4 block
+//! at a time, so any per-block work (like heap allocations) adds up quickly.
+//!
+//! Run with `cargo bench --bench dxt1`.
+
+use gvrtex::formats::DataFormat;
+use image::{Rgba, RgbaImage};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let opaque = RgbaImage::from_fn(1024, 1024, |x, y| {
+        Rgba([x as u8, y as u8, (x ^ y) as u8, 0xFF])
+    });
+    // Every other block holds transparent pixels, which takes the 3 color path
+    let transparent = RgbaImage::from_fn(1024, 1024, |x, y| {
+        let alpha = if (x / 4 + y / 4) % 2 == 0 { 0xFF } else { 0 };
+        Rgba([x as u8, y as u8, (x ^ y) as u8, alpha])
+    });
+    let encoder = DataFormat::Dxt1.encoder().unwrap();
+
+    for (name, image) in [("opaque", &opaque), ("transparent", &transparent)] {
+        // Warming up the caches first
+        black_box(encoder.encode(image));
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(encoder.encode(black_box(image)));
+        }
+        println!(
+            "{name:<12} {:>10.3?} per encode",
+            start.elapsed() / ITERATIONS
+        );
+    }
+}
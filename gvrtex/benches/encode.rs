@@ -0,0 +1,87 @@
+//! Compares encoding an RGB5A3 texture with the block size known at compile time, like the
+//! built-in encoders and decoders do, against the block size only being known at runtime.
+//!
+//! Going through the image a pixel at a time is where the block size matters the most, while
+//! going through it a block at a time is barely affected by it.
+//!
+//! Run with `cargo bench --bench encode`.
+
+use gvrtex::formats::DataFormat;
+use gvrtex::iter::{BlockSize, ConstBlockSize, PixelBlockIterator, PixelBlockViewIterator};
+use gvrtex::pixel;
+use image::{Rgba, RgbaImage};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 50;
+
+fn encode_rgb5a3_pixels<B: BlockSize>(image: &RgbaImage, block_size: B) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let mut dest = Vec::with_capacity((width * height * 2) as usize);
+
+    for (x, y) in PixelBlockIterator::new(width, height, block_size) {
+        let p = image.get_pixel(x, y);
+        dest.extend_from_slice(&pixel::encode_rgb5a3(p.0).to_be_bytes());
+    }
+
+    dest
+}
+
+fn encode_rgb5a3_blocks<B: BlockSize>(image: &RgbaImage, block_size: B) -> Vec<u8> {
+    let mut dest = Vec::with_capacity((image.width() * image.height() * 2) as usize);
+
+    for block in PixelBlockViewIterator::new(image, block_size) {
+        for &p in block.pixels() {
+            dest.extend_from_slice(&pixel::encode_rgb5a3(p).to_be_bytes());
+        }
+    }
+
+    dest
+}
+
+fn bench(name: &str, mut f: impl FnMut() -> Vec<u8>) {
+    // Warming up the caches first
+    black_box(f());
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    println!(
+        "{name:<28} {:>10.3?} per encode",
+        start.elapsed() / ITERATIONS
+    );
+}
+
+fn main() {
+    let image = RgbaImage::from_fn(1024, 1024, |x, y| {
+        Rgba([x as u8, y as u8, (x ^ y) as u8, (x + y) as u8])
+    });
+    let encoder = DataFormat::Rgb5a3.encoder().unwrap();
+
+    let expected = encoder.encode(&image);
+    assert_eq!(encode_rgb5a3_pixels(&image, black_box((4, 4))), expected);
+    assert_eq!(
+        encode_rgb5a3_pixels(&image, ConstBlockSize::<4, 4>),
+        expected
+    );
+    assert_eq!(encode_rgb5a3_blocks(&image, black_box((4, 4))), expected);
+    assert_eq!(
+        encode_rgb5a3_blocks(&image, ConstBlockSize::<4, 4>),
+        expected
+    );
+
+    bench("pixels, runtime block size", || {
+        encode_rgb5a3_pixels(&image, black_box((4, 4)))
+    });
+    bench("pixels, const block size", || {
+        encode_rgb5a3_pixels(&image, ConstBlockSize::<4, 4>)
+    });
+    bench("blocks, runtime block size", || {
+        encode_rgb5a3_blocks(&image, black_box((4, 4)))
+    });
+    bench("blocks, const block size", || {
+        encode_rgb5a3_blocks(&image, ConstBlockSize::<4, 4>)
+    });
+    bench("built-in encoder", || encoder.encode(&image));
+}
@@ -0,0 +1,165 @@
+#ifndef GVRTEX_H
+#define GVRTEX_H
+
+/* Generated with cbindgen from src/capi.rs, don't edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The texture starts with a GCIX header, see [`TextureType::Gcix`].
+ */
+#define GVRTEX_TEXTURE_GCIX 0
+
+/**
+ * The texture starts with a GBIX header, see [`TextureType::Gbix`].
+ */
+#define GVRTEX_TEXTURE_GBIX 1
+
+/**
+ * The texture starts directly with the GVRT header, see [`TextureType::None`].
+ */
+#define GVRTEX_TEXTURE_NONE 2
+
+/**
+ * The status code returned by the functions of the C API.
+ */
+enum GvrStatus {
+  /**
+   * The call succeeded.
+   */
+  GVR_STATUS_OK = 0,
+  /**
+   * A pointer that has to be valid was null.
+   */
+  GVR_STATUS_NULL_POINTER = -1,
+  /**
+   * One of the arguments was out of range.
+   */
+  GVR_STATUS_INVALID_ARGUMENT = -2,
+  /**
+   * The texture couldn't be decoded.
+   */
+  GVR_STATUS_DECODE_ERROR = -3,
+  /**
+   * The image couldn't be encoded.
+   */
+  GVR_STATUS_ENCODE_ERROR = -4,
+  /**
+   * The library panicked. This is a bug in gvrtex.
+   */
+  GVR_STATUS_PANIC = -5,
+};
+typedef int32_t GvrStatus;
+
+/**
+ * A decoded texture, filled in by [`gvrtex_decode()`].
+ */
+typedef struct GvrDecoded {
+  /**
+   * The pixels of the texture in row-major order, 4 bytes each in RGBA order.
+   */
+  uint8_t *pixels;
+  /**
+   * The length of `pixels` in bytes.
+   */
+  size_t len;
+  /**
+   * The width of the texture in pixels.
+   */
+  uint32_t width;
+  /**
+   * The height of the texture in pixels.
+   */
+  uint32_t height;
+} GvrDecoded;
+
+/**
+ * An encoded texture, filled in by [`gvrtex_encode()`].
+ */
+typedef struct GvrEncoded {
+  /**
+   * The bytes of the GVR texture file.
+   */
+  uint8_t *data;
+  /**
+   * The length of `data` in bytes.
+   */
+  size_t len;
+} GvrEncoded;
+
+/**
+ * Decodes the GVR texture in the `len` bytes at `data` into RGBA pixels, writing them to `out`.
+ *
+ * The pixels written to `out` have to be released with [`gvrtex_free()`]. On failure, `out` is
+ * left untouched.
+ *
+ * # Safety
+ *
+ * `data` has to point to `len` readable bytes, and `out` has to point to a writable
+ * [`GvrDecoded`]. Any pixels `out` already holds aren't released.
+ */
+GvrStatus gvrtex_decode(const uint8_t *data, size_t len, struct GvrDecoded *out);
+
+/**
+ * Encodes `width` x `height` RGBA pixels into a GVR texture, writing it to `out`.
+ *
+ * `texture_type` is one of the `GVRTEX_TEXTURE_*` constants, and `data_format` is the value of
+ * the data format as it's stored in the GVRT header. `pixel_format` is the value of the pixel
+ * format of the color palette in the same way, and is only used if `data_format` is one of the
+ * palettized formats.
+ *
+ * The texture written to `out` has to be released with [`gvrtex_free_encoded()`]. On failure,
+ * `out` is left untouched.
+ *
+ * # Safety
+ *
+ * `pixels` has to point to `len` readable bytes, and `out` has to point to a writable
+ * [`GvrEncoded`]. Any texture `out` already holds isn't released.
+ */
+GvrStatus gvrtex_encode(const uint8_t *pixels,
+                        size_t len,
+                        uint32_t width,
+                        uint32_t height,
+                        uint8_t texture_type,
+                        uint8_t data_format,
+                        uint8_t pixel_format,
+                        struct GvrEncoded *out);
+
+/**
+ * Releases the pixels of a texture decoded by [`gvrtex_decode()`], resetting `decoded`.
+ *
+ * Passing a null pointer or an already released texture does nothing.
+ *
+ * # Safety
+ *
+ * `decoded` has to be null or point to a [`GvrDecoded`] that was filled in by
+ * [`gvrtex_decode()`] or reset by this function.
+ */
+void gvrtex_free(struct GvrDecoded *decoded);
+
+/**
+ * Releases the data of a texture encoded by [`gvrtex_encode()`], resetting `encoded`.
+ *
+ * Passing a null pointer or an already released texture does nothing.
+ *
+ * # Safety
+ *
+ * `encoded` has to be null or point to a [`GvrEncoded`] that was filled in by
+ * [`gvrtex_encode()`] or reset by this function.
+ */
+void gvrtex_free_encoded(struct GvrEncoded *encoded);
+
+/**
+ * Returns a description of the error of the last call to [`gvrtex_decode()`] or
+ * [`gvrtex_encode()`] on this thread, or null if it succeeded.
+ *
+ * The string is nul-terminated UTF-8, and stays valid until the next call to either of them on
+ * the same thread. It's owned by the library, so it mustn't be freed.
+ */
+const char *gvrtex_last_error_message(void);
+
+#endif  /* GVRTEX_H */
@@ -0,0 +1,68 @@
+/*
+ * A small harness for the C API, checking the ownership rules documented in src/capi.rs.
+ *
+ *     cargo rustc --features capi --crate-type cdylib
+ *     cc capi/harness.c -I capi -L ../target/debug -lgvrtex -o harness
+ *     LD_LIBRARY_PATH=../target/debug ./harness
+ */
+
+#include "gvrtex.h"
+
+#include <stdio.h>
+#include <string.h>
+
+#define CHECK(cond)                                                        \
+  do {                                                                     \
+    if (!(cond)) {                                                         \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+      return 1;                                                            \
+    }                                                                      \
+  } while (0)
+
+int main(void) {
+  uint8_t pixels[16 * 8 * 4];
+  for (size_t i = 0; i < sizeof(pixels); i++) {
+    pixels[i] = (i % 4 == 3) ? 0xFF : (uint8_t)(i * 8);
+  }
+
+  GvrEncoded encoded = {0};
+  GvrStatus status = gvrtex_encode(pixels, sizeof(pixels), 16, 8, GVRTEX_TEXTURE_GBIX, 0x06,
+                                   0, &encoded);
+  CHECK(status == GVR_STATUS_OK);
+  CHECK(gvrtex_last_error_message() == NULL);
+  CHECK(memcmp(encoded.data, "GBIX", 4) == 0);
+
+  GvrDecoded decoded = {0};
+  status = gvrtex_decode(encoded.data, encoded.len, &decoded);
+  CHECK(status == GVR_STATUS_OK);
+  CHECK(decoded.width == 16 && decoded.height == 8);
+  CHECK(decoded.len == sizeof(pixels));
+  CHECK(memcmp(decoded.pixels, pixels, sizeof(pixels)) == 0);
+
+  /* Releasing resets the structs, so releasing them again is harmless */
+  gvrtex_free_encoded(&encoded);
+  gvrtex_free_encoded(&encoded);
+  CHECK(encoded.data == NULL && encoded.len == 0);
+  gvrtex_free(&decoded);
+  gvrtex_free(&decoded);
+  CHECK(decoded.pixels == NULL && decoded.len == 0);
+  gvrtex_free(NULL);
+
+  /* Failures leave the output untouched and set the error message */
+  status = gvrtex_decode((const uint8_t *)"XXXX", 4, &decoded);
+  CHECK(status == GVR_STATUS_DECODE_ERROR);
+  CHECK(decoded.pixels == NULL);
+  CHECK(gvrtex_last_error_message() != NULL);
+  printf("decode error: %s\n", gvrtex_last_error_message());
+
+  status = gvrtex_encode(pixels, sizeof(pixels) - 1, 16, 8, GVRTEX_TEXTURE_GCIX, 0x06, 0,
+                         &encoded);
+  CHECK(status == GVR_STATUS_INVALID_ARGUMENT);
+  status = gvrtex_encode(pixels, sizeof(pixels), 16, 8, GVRTEX_TEXTURE_GCIX, 0x07, 0, &encoded);
+  CHECK(status == GVR_STATUS_INVALID_ARGUMENT);
+  status = gvrtex_decode(NULL, 0, &decoded);
+  CHECK(status == GVR_STATUS_NULL_POINTER);
+
+  puts("ok");
+  return 0;
+}
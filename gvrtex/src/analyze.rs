@@ -0,0 +1,225 @@
+use crate::formats::DataFormat;
+use image::RgbaImage;
+use std::collections::HashSet;
+
+/// The properties of an image that matter when choosing a data format for it, as returned by
+/// [`analyze_image()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageStats {
+    /// The amount of distinct RGBA colors in the image. Colors that only differ in alpha count
+    /// as separate colors.
+    pub distinct_colors: usize,
+    /// Whether any pixel of the image isn't fully opaque.
+    pub has_alpha: bool,
+    /// Whether any pixel of the image is partially transparent, meaning its alpha is neither 0
+    /// nor 255. Images with only fully transparent and fully opaque pixels don't need more than
+    /// 1 bit of alpha.
+    pub has_translucency: bool,
+    /// Whether every pixel of the image has equal red, green and blue values, so that the image
+    /// can be stored in an intensity format without losing color.
+    pub is_grayscale: bool,
+}
+
+impl ImageStats {
+    /// Returns the data format to use instead of `data_format` for an image with these stats, so
+    /// that no space is spent on data the image doesn't need.
+    ///
+    /// For an image without any alpha, [`DataFormat::IntensityA4`] and
+    /// [`DataFormat::IntensityA8`] would store the same alpha value for every pixel, so
+    /// [`DataFormat::Intensity4`] and [`DataFormat::Intensity8`] are suggested instead. They
+    /// decode to the same image at half the size. Every other data format is returned as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::analyze_image;
+    /// use gvrtex::formats::DataFormat;
+    /// use image::{Rgba, RgbaImage};
+    ///
+    /// let opaque = RgbaImage::from_fn(8, 8, |x, _| Rgba([x as u8 * 32, x as u8 * 32, x as u8 * 32, 0xFF]));
+    /// let stats = analyze_image(&opaque);
+    /// assert_eq!(stats.suggest_format(DataFormat::IntensityA8), DataFormat::Intensity8);
+    /// assert_eq!(stats.suggest_format(DataFormat::IntensityA4), DataFormat::Intensity4);
+    /// assert_eq!(stats.suggest_format(DataFormat::Rgb5a3), DataFormat::Rgb5a3);
+    ///
+    /// let mut translucent = opaque.clone();
+    /// translucent.put_pixel(0, 0, Rgba([0, 0, 0, 0x80]));
+    /// let stats = analyze_image(&translucent);
+    /// assert_eq!(stats.suggest_format(DataFormat::IntensityA8), DataFormat::IntensityA8);
+    /// ```
+    pub fn suggest_format(&self, data_format: DataFormat) -> DataFormat {
+        match data_format {
+            DataFormat::IntensityA4 if !self.has_alpha => DataFormat::Intensity4,
+            DataFormat::IntensityA8 if !self.has_alpha => DataFormat::Intensity8,
+            _ => data_format,
+        }
+    }
+}
+
+/// Goes through every pixel of the given image, collecting the [`ImageStats`] of it.
+///
+/// This is useful for choosing a data format before encoding. For example, an image with at most
+/// 16 or 256 distinct colors fits in the color palette of [`crate::formats::DataFormat::Index4`]
+/// or [`crate::formats::DataFormat::Index8`], and a grayscale image can be stored as
+/// [`crate::formats::DataFormat::Intensity8`], or [`crate::formats::DataFormat::IntensityA8`] if
+/// it has alpha. To have the encoder pick a lossless format by itself, see
+/// [`crate::TextureEncoder::new_auto_lossless()`].
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::{analyze_image, ImageStats};
+/// use image::{Rgba, RgbaImage};
+///
+/// let gradient = RgbaImage::from_fn(16, 16, |x, y| {
+///     let value = (y * 16 + x) as u8;
+///     Rgba([value, value, value, 0xFF])
+/// });
+/// assert_eq!(
+///     analyze_image(&gradient),
+///     ImageStats {
+///         distinct_colors: 256,
+///         has_alpha: false,
+///         has_translucency: false,
+///         is_grayscale: true,
+///     }
+/// );
+///
+/// // A color image with a fully transparent background
+/// let sprite = RgbaImage::from_fn(8, 8, |x, _| {
+///     if x < 4 {
+///         Rgba([0, 0, 0, 0])
+///     } else {
+///         Rgba([0xFF, 0x80, 0, 0xFF])
+///     }
+/// });
+/// let stats = analyze_image(&sprite);
+/// assert_eq!(stats.distinct_colors, 2);
+/// assert!(stats.has_alpha);
+/// assert!(!stats.has_translucency);
+/// assert!(!stats.is_grayscale);
+///
+/// // Colors that only differ in alpha are distinct
+/// let fade = RgbaImage::from_fn(4, 1, |x, _| Rgba([0xFF, 0xFF, 0xFF, x as u8 * 0x55]));
+/// let stats = analyze_image(&fade);
+/// assert_eq!(stats.distinct_colors, 4);
+/// assert!(stats.has_alpha);
+/// assert!(stats.has_translucency);
+/// assert!(stats.is_grayscale);
+/// ```
+///
+/// An empty image has no colors, and counts as grayscale:
+///
+/// ```
+/// use gvrtex::analyze_image;
+/// use image::RgbaImage;
+///
+/// let stats = analyze_image(&RgbaImage::new(0, 0));
+/// assert_eq!(stats.distinct_colors, 0);
+/// assert!(!stats.has_alpha);
+/// assert!(stats.is_grayscale);
+/// ```
+pub fn analyze_image(img: &RgbaImage) -> ImageStats {
+    let mut colors = HashSet::new();
+    let mut has_alpha = false;
+    let mut has_translucency = false;
+    let mut is_grayscale = true;
+
+    for p in img.pixels() {
+        let [r, g, b, a] = p.0;
+        colors.insert(p.0);
+        has_alpha |= a != 0xFF;
+        has_translucency |= a != 0 && a != 0xFF;
+        is_grayscale &= r == g && g == b;
+    }
+
+    ImageStats {
+        distinct_colors: colors.len(),
+        has_alpha,
+        has_translucency,
+        is_grayscale,
+    }
+}
+
+/// The differences between two images of the same dimensions, as returned by
+/// [`compare_images()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    /// The biggest absolute difference between any channel of any two pixels at the same
+    /// position.
+    pub max_channel_delta: u8,
+    /// The mean of the squared differences of every channel (RGBA) of every pixel.
+    pub mean_squared_error: f64,
+    /// The amount of pixels that differ in at least one channel.
+    pub differing_pixels: u32,
+}
+
+/// Compares two images pixel by pixel, returning the [`ImageDiff`] between them, or [`None`] if
+/// their dimensions differ.
+///
+/// This is useful for checking that decoding an encoded image with a lossy data format, such as
+/// [`crate::formats::DataFormat::Dxt1`], gives back an image that's close enough to the original.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::{compare_images, ImageDiff};
+/// use image::{Rgba, RgbaImage};
+///
+/// let image = RgbaImage::from_fn(4, 4, |x, y| Rgba([x as u8 * 64, y as u8 * 64, 0x80, 0xFF]));
+/// let diff = compare_images(&image, &image).unwrap();
+/// assert_eq!(
+///     diff,
+///     ImageDiff {
+///         max_channel_delta: 0,
+///         mean_squared_error: 0.0,
+///         differing_pixels: 0,
+///     }
+/// );
+///
+/// let mut perturbed = image.clone();
+/// perturbed.put_pixel(1, 2, Rgba([64, 128 + 4, 0x80 - 2, 0xFF]));
+/// perturbed.put_pixel(3, 0, Rgba([192 - 1, 0, 0x80, 0xFF]));
+/// let diff = compare_images(&image, &perturbed).unwrap();
+/// assert_eq!(diff.max_channel_delta, 4);
+/// assert_eq!(diff.differing_pixels, 2);
+/// // (4² + 2² + 1²) / (4 * 4 pixels * 4 channels)
+/// assert_eq!(diff.mean_squared_error, 21.0 / 64.0);
+///
+/// // Images of different dimensions can't be compared
+/// assert_eq!(compare_images(&image, &RgbaImage::new(4, 2)), None);
+/// ```
+pub fn compare_images(a: &RgbaImage, b: &RgbaImage) -> Option<ImageDiff> {
+    if a.dimensions() != b.dimensions() {
+        return None;
+    }
+
+    let mut max_channel_delta = 0;
+    let mut squared_error_sum = 0u64;
+    let mut differing_pixels = 0;
+
+    for (pa, pb) in a.pixels().zip(b.pixels()) {
+        if pa == pb {
+            continue;
+        }
+        differing_pixels += 1;
+        for (ca, cb) in pa.0.into_iter().zip(pb.0) {
+            let delta = ca.abs_diff(cb);
+            max_channel_delta = max_channel_delta.max(delta);
+            squared_error_sum += u64::from(delta) * u64::from(delta);
+        }
+    }
+
+    let channel_count = a.as_raw().len();
+    let mean_squared_error = if channel_count == 0 {
+        0.0
+    } else {
+        squared_error_sum as f64 / channel_count as f64
+    };
+
+    Some(ImageDiff {
+        max_channel_delta,
+        mean_squared_error,
+        differing_pixels,
+    })
+}
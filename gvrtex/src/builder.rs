@@ -0,0 +1,176 @@
+//! A type-checked way of setting up a [`TextureEncoder`], which rejects invalid combinations of
+//! formats and flags at compile time.
+//!
+//! The constructors of [`TextureEncoder`] take the data format as a value, so combinations such as
+//! [`DataFormat::Index8`] without a color palette, or mipmaps on [`DataFormat::IntensityA4`], are
+//! only rejected when the program runs. [`GvrEncoderBuilder`] instead takes the data format as a
+//! marker type, and only has the methods that make sense for that data format:
+//!
+//! * [`GvrEncoderBuilder::palette_format()`] and [`GvrEncoderBuilder::external_palette()`] only
+//!   exist for the palettized data formats ([`Index4`] and [`Index8`]).
+//! * [`GvrEncoderBuilder::mipmaps()`] only exists for the data formats that support mipmaps
+//!   ([`Rgb565`], [`Rgb5a3`] and [`Dxt1`]).
+//!
+//! The builder results in a regular [`TextureEncoder`], so every other setting of the encoder is
+//! set on that.
+//!
+//! # Examples
+//!
+//! ```
+//! use gvrtex::builder::{Dxt1, GvrEncoderBuilder, Index8};
+//! use gvrtex::formats::{PixelFormat, TextureType};
+//! use image::{DynamicImage, RgbaImage};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut encoder = GvrEncoderBuilder::<Dxt1>::new(TextureType::Gcix)
+//!     .mipmaps()
+//!     .build()
+//!     .with_global_index(42);
+//! let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(16, 16)))?;
+//!
+//! let mut encoder = GvrEncoderBuilder::<Index8>::new(TextureType::Gbix)
+//!     .palette_format(PixelFormat::RGB565)
+//!     .build();
+//! let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(16, 16)))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Invalid combinations don't compile:
+//!
+//! ```compile_fail
+//! use gvrtex::builder::{GvrEncoderBuilder, IntensityA4};
+//! use gvrtex::formats::TextureType;
+//!
+//! let encoder = GvrEncoderBuilder::<IntensityA4>::new(TextureType::Gcix).mipmaps();
+//! ```
+//!
+//! ```compile_fail
+//! use gvrtex::builder::{GvrEncoderBuilder, Rgb565};
+//! use gvrtex::formats::{PixelFormat, TextureType};
+//!
+//! let encoder =
+//!     GvrEncoderBuilder::<Rgb565>::new(TextureType::Gcix).palette_format(PixelFormat::RGB5A3);
+//! ```
+
+use crate::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+use crate::TextureEncoder;
+use std::marker::PhantomData;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A marker type for one of the data formats of [`DataFormat`], used with
+/// [`GvrEncoderBuilder`].
+///
+/// This trait is sealed, it's only implemented by the marker types in this module.
+pub trait FormatMarker: sealed::Sealed {
+    /// The data format this marker type stands for.
+    const DATA_FORMAT: DataFormat;
+}
+
+/// Marks the data formats that don't use a color palette.
+pub trait Direct: FormatMarker {}
+
+/// Marks the data formats that use a color palette.
+pub trait Palettized: FormatMarker {}
+
+/// Marks the data formats that support mipmaps.
+pub trait SupportsMipmaps: FormatMarker {}
+
+macro_rules! format_marker {
+    ($name:ident, $kind:ident $(, $extra:ident)*) => {
+        #[doc = concat!("Marks [`DataFormat::", stringify!($name), "`].")]
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl sealed::Sealed for $name {}
+
+        impl FormatMarker for $name {
+            const DATA_FORMAT: DataFormat = DataFormat::$name;
+        }
+
+        impl $kind for $name {}
+        $(impl $extra for $name {})*
+    };
+}
+
+format_marker!(Intensity4, Direct);
+format_marker!(Intensity8, Direct);
+format_marker!(IntensityA4, Direct);
+format_marker!(IntensityA8, Direct);
+format_marker!(Rgb565, Direct, SupportsMipmaps);
+format_marker!(Rgb5a3, Direct, SupportsMipmaps);
+format_marker!(Argb8888, Direct);
+format_marker!(Index4, Palettized);
+format_marker!(Index8, Palettized);
+format_marker!(Dxt1, Direct, SupportsMipmaps);
+
+/// Sets up a [`TextureEncoder`] for the data format `F`, which is one of the marker types in
+/// this module.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone, Copy)]
+pub struct GvrEncoderBuilder<F: FormatMarker> {
+    texture_type: TextureType,
+    pixel_format: PixelFormat,
+    data_flags: DataFlags,
+    _format: PhantomData<F>,
+}
+
+impl<F: FormatMarker> GvrEncoderBuilder<F> {
+    /// Creates a new builder, for an encoder that starts its textures with the header given in
+    /// `texture_type`.
+    ///
+    /// Palettized data formats store their color palette in the texture, in
+    /// [`PixelFormat::RGB5A3`] unless set otherwise with [`Self::palette_format()`].
+    pub fn new(texture_type: TextureType) -> Self {
+        let data_flags = if F::DATA_FORMAT.requires_palette() {
+            DataFlags::InternalPalette
+        } else {
+            DataFlags::empty()
+        };
+
+        Self {
+            texture_type,
+            pixel_format: PixelFormat::RGB5A3,
+            data_flags,
+            _format: PhantomData,
+        }
+    }
+
+    /// Creates the encoder.
+    pub fn build(self) -> TextureEncoder {
+        TextureEncoder {
+            texture_type: self.texture_type,
+            pixel_format: self.pixel_format,
+            data_format: F::DATA_FORMAT,
+            data_flags: self.data_flags,
+            ..Default::default()
+        }
+    }
+}
+
+impl<F: Palettized> GvrEncoderBuilder<F> {
+    /// Sets the format the color palette is stored in.
+    pub fn palette_format(mut self, pixel_format: PixelFormat) -> Self {
+        self.pixel_format = pixel_format;
+        self
+    }
+
+    /// Leaves the color palette out of the encoded textures, so that they only hold the palette
+    /// indices. See [`DataFlags::ExternalPalette`].
+    pub fn external_palette(mut self) -> Self {
+        self.data_flags = DataFlags::ExternalPalette;
+        self
+    }
+}
+
+impl<F: SupportsMipmaps> GvrEncoderBuilder<F> {
+    /// Makes the encoder generate mipmaps alongside the original texture.
+    pub fn mipmaps(mut self) -> Self {
+        self.data_flags.insert(DataFlags::Mipmaps);
+        self
+    }
+}
@@ -0,0 +1,272 @@
+use crate::codec::expand_indexed;
+use crate::error::{TextureDecodeError, TextureEncodeError};
+use crate::formats::{DataFlags, DataFormat, DxtLayout, PixelFormat};
+use crate::header::GvrHeader;
+use crate::pixel_codecs::{
+    create_new_decoder, create_new_decoder_with_palette, create_new_encoder,
+    create_new_encoder_with_palette, decode_indexed_short, palette_size,
+};
+use crate::TextureEncoder;
+use image::RgbaImage;
+
+/// Reads the headers of the GVR texture at the start of `gvr_bytes`, returning them alongside
+/// the texture data following them.
+///
+/// Any data after the end of the texture is left out of the returned texture data.
+pub(crate) fn split_texture(gvr_bytes: &[u8]) -> Result<(GvrHeader, &[u8]), TextureDecodeError> {
+    let header = GvrHeader::read(&mut &gvr_bytes[..])?;
+
+    let data_start = header.size();
+    header.check_data_len(gvr_bytes.len().saturating_sub(data_start) as u64)?;
+
+    let data = &gvr_bytes[data_start..data_start + header.data_len as usize];
+    Ok((header, data))
+}
+
+/// Decodes the GVR texture held in `gvr_bytes` straight into an image.
+///
+/// This is a stateless alternative to [`crate::TextureDecoder`], which reads the pixel data
+/// directly out of the given slice. As there's no decoder to set up, it's the fastest way to decode
+/// many small textures that are already in memory.
+///
+/// # Notes
+///
+/// The start of `gvr_bytes` should point to the start of the texture. Any data after the end of
+/// the texture is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::error::TextureDecodeError;
+/// use gvrtex::formats::DataFormat;
+/// use gvrtex::TextureEncoder;
+/// use image::{DynamicImage, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+/// let mut encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(8, 8)))?;
+/// assert_eq!(gvrtex::decode_bytes(&encoded)?.dimensions(), (8, 8));
+///
+/// // Cutting off the end of the texture data
+/// let err = gvrtex::decode_bytes(&encoded[..0x40]).unwrap_err();
+/// assert!(matches!(
+///     err,
+///     TextureDecodeError::SizeMismatch { expected: 0x80, actual: 0x20 }
+/// ));
+///
+/// // Corrupting the data format of the texture
+/// encoded[0x1B] = 0x42;
+/// let err = gvrtex::decode_bytes(&encoded).unwrap_err();
+/// assert!(matches!(
+///     err,
+///     TextureDecodeError::UnknownDataFormat { offset: 0x1B, value: 0x42 }
+/// ));
+/// assert_eq!(err.to_string(), "Unknown data format 0x42 at offset 0x1B.");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Every data format is decoded to the same image as with [`crate::TextureDecoder`]:
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+/// use gvrtex::{TextureDecoder, TextureEncoder};
+/// use image::{DynamicImage, Rgba, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = RgbaImage::from_fn(32, 16, |x, y| {
+///     Rgba([x as u8 * 8, y as u8 * 16, (x ^ y) as u8 * 8, (x + y) as u8 * 5])
+/// });
+///
+/// for data_format in DataFormat::ALL {
+///     let mut encoder = if data_format.requires_palette() {
+///         TextureEncoder::new_gcix_palettized(Default::default(), data_format)?
+///     } else {
+///         TextureEncoder::new_gcix(data_format)?
+///     };
+///     let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+///
+///     let mut decoder = TextureDecoder::new_from_buffer(encoded.clone());
+///     decoder.decode()?;
+///     assert_eq!(gvrtex::decode_bytes(&encoded)?, decoder.into_decoded()?, "{data_format:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If `gvr_bytes` doesn't hold a valid GVR texture, an error describing the problem is returned,
+/// such as a [`TextureDecodeError::BadMagic`] or a [`TextureDecodeError::SizeMismatch`].
+/// Textures with the [`DataFlags::ExternalPalette`] flag can't be decoded without their color
+/// palette, so a [`TextureDecodeError::MissingPalette`] is returned for those. Use
+/// [`crate::TextureDecoder::with_external_palette()`] to decode them instead.
+pub fn decode_bytes(gvr_bytes: &[u8]) -> Result<RgbaImage, TextureDecodeError> {
+    let (header, mut data) = split_texture(gvr_bytes)?;
+    let width = header.width.into();
+    let height = header.height.into();
+
+    if header.flags.intersects(DataFlags::ExternalPalette) {
+        return Err(TextureDecodeError::MissingPalette);
+    }
+
+    let image = if header.flags.intersects(DataFlags::InternalPalette) {
+        let decoder = create_new_decoder_with_palette(header.data_format);
+        let (palette, indices) = match header.palette_colors() {
+            Some(colors) if colors < palette_size(header.data_format) as usize => {
+                decode_indexed_short(
+                    &mut data,
+                    header.data_format,
+                    width,
+                    height,
+                    header.pixel_format,
+                    colors,
+                )?
+            }
+            _ => decoder.decode_indexed(&mut data, width, height, header.pixel_format)?,
+        };
+        expand_indexed(&palette, &indices, width, height)
+    } else {
+        let decoder = create_new_decoder(header.data_format, DxtLayout::default());
+        decoder.decode(&mut data, width, height)?
+    };
+
+    Ok(image)
+}
+
+/// Encodes the given `image` into raw GX image data of the given `format`, without any GVR
+/// headers.
+///
+/// This is the same data [`crate::TextureEncoder`] stores after the headers of a texture without
+/// mipmaps, which is useful for textures stored in other containers, such as TPL or BTI files.
+/// The palettized formats ([`DataFormat::Index4`] and [`DataFormat::Index8`]) need the
+/// `palette_format` to store their color palette in, which comes before the indices in the
+/// returned data. It's ignored for every other format.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::formats::{DataFormat, PixelFormat};
+/// use image::{Rgba, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = RgbaImage::from_fn(8, 8, |x, _| match x % 2 {
+///     0 => Rgba([0xFF, 0x00, 0x00, 0xFF]),
+///     _ => Rgba([0x00, 0x00, 0xFF, 0xFF]),
+/// });
+///
+/// let data = gvrtex::encode_raw(&image, DataFormat::Rgb565, None)?;
+/// assert_eq!(data.len(), 0x80);
+/// assert_eq!(gvrtex::decode_raw(&data, 8, 8, DataFormat::Rgb565, None)?, image);
+///
+/// let palette_format = Some(PixelFormat::RGB5A3);
+/// let data = gvrtex::encode_raw(&image, DataFormat::Index4, palette_format)?;
+/// assert_eq!(data.len(), 16 * 2 + 0x20);
+/// assert_eq!(gvrtex::decode_raw(&data, 8, 8, DataFormat::Index4, palette_format)?, image);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If the dimensions of `image` can't be encoded in `format`, a
+/// [`TextureEncodeError::SmallDimensions`] or [`TextureEncodeError::InvalidDimensions`] is
+/// returned. If `format` is palettized and no `palette_format` is given, a
+/// [`TextureEncodeError::Format`] is returned.
+pub fn encode_raw(
+    image: &RgbaImage,
+    format: DataFormat,
+    palette_format: Option<PixelFormat>,
+) -> Result<Vec<u8>, TextureEncodeError> {
+    if format.requires_palette() {
+        let palette_format = palette_format.ok_or(TextureEncodeError::Format)?;
+        let encoder = create_new_encoder_with_palette(format);
+        encoder.validate_input(image)?;
+        Ok(encoder.encode(image, palette_format)?)
+    } else {
+        let encoder = create_new_encoder(
+            format,
+            TextureEncoder::DEFAULT_ALPHA_THRESHOLD,
+            0,
+            DxtLayout::default(),
+        );
+        encoder.validate_input(image)?;
+        Ok(encoder.encode(image))
+    }
+}
+
+/// Decodes raw GX image data of the given `format` and dimensions, without any GVR headers, such
+/// as the data returned by [`encode_raw()`].
+///
+/// For the palettized formats ([`DataFormat::Index4`] and [`DataFormat::Index8`]), the data starts
+/// with the color palette in the given `palette_format`. It's ignored for every other format. Any
+/// data after the end of the image is ignored.
+///
+/// # Errors
+///
+/// If `data` is too short for an image of the given dimensions, a
+/// [`TextureDecodeError::SizeMismatch`] is returned. If `format` is palettized and no
+/// `palette_format` is given, a [`TextureDecodeError::MissingPalette`] is returned.
+pub fn decode_raw(
+    mut data: &[u8],
+    width: u32,
+    height: u32,
+    format: DataFormat,
+    palette_format: Option<PixelFormat>,
+) -> Result<RgbaImage, TextureDecodeError> {
+    let expected = format.data_size(width, height, 0);
+    if data.len() < expected {
+        return Err(TextureDecodeError::SizeMismatch {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    let image = if format.requires_palette() {
+        let palette_format = palette_format.ok_or(TextureDecodeError::MissingPalette)?;
+        let decoder = create_new_decoder_with_palette(format);
+        let (palette, indices) =
+            decoder.decode_indexed(&mut data, width, height, palette_format)?;
+        expand_indexed(&palette, &indices, width, height)
+    } else {
+        create_new_decoder(format, DxtLayout::default()).decode(&mut data, width, height)?
+    };
+
+    Ok(image)
+}
+
+/// Returns a hash of the given encoded texture, such as one returned by
+/// [`crate::TextureEncoder::encode()`].
+///
+/// The hash is the 64-bit FNV-1a hash of `bytes`, so it's the same across runs, platforms and
+/// versions of this crate. This makes it suitable as a cache key, such as for skipping textures
+/// that haven't changed since the last build. It's not a cryptographic hash.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+/// use gvrtex::TextureEncoder;
+/// use image::{DynamicImage, Rgba, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8 * 32, y as u8 * 32, 0, 0xFF]));
+/// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+///
+/// let first = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+/// let second = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+/// assert_eq!(gvrtex::hash_encoded(&first), gvrtex::hash_encoded(&second));
+///
+/// // The hash doesn't depend on anything besides the bytes
+/// assert_eq!(gvrtex::hash_encoded(b""), 0xCBF2_9CE4_8422_2325);
+/// # Ok(())
+/// # }
+/// ```
+pub fn hash_encoded(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01B3;
+
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
@@ -0,0 +1,338 @@
+//! A C ABI for decoding and encoding GVR textures from languages that can't use the Rust API
+//! directly, enabled with the `capi` feature.
+//!
+//! The crate is built as a shared library exporting the `gvrtex_*` functions in this module with
+//! `cargo rustc --release --features capi --crate-type cdylib`. Building it as a `cdylib` isn't
+//! the default, as that doesn't work without the `std` feature. A C header for them is kept in
+//! `capi/gvrtex.h`, which can be regenerated with
+//! `cbindgen --config cbindgen.toml --output capi/gvrtex.h` from the crate directory.
+//!
+//! # Errors
+//!
+//! Every function that can fail returns a [`GvrStatus`], which is [`GvrStatus::Ok`] on success.
+//! On failure, a description of the error can be read with [`gvrtex_last_error_message()`] on the
+//! same thread.
+//!
+//! # Ownership
+//!
+//! The input buffers are only borrowed for the duration of the call. The buffers written into a
+//! [`GvrDecoded`] or [`GvrEncoded`] are owned by the caller, and have to be released with
+//! [`gvrtex_free()`] or [`gvrtex_free_encoded()`] respectively, not with `free()`. Releasing a
+//! buffer resets the struct, so releasing it twice is harmless.
+//!
+//! # Examples
+//!
+//! ```
+//! use gvrtex::capi::*;
+//!
+//! let pixels = [0xFFu8; 8 * 8 * 4];
+//! let mut encoded = GvrEncoded::default();
+//! let mut decoded = GvrDecoded::default();
+//! unsafe {
+//!     let status = gvrtex_encode(
+//!         pixels.as_ptr(),
+//!         pixels.len(),
+//!         8,
+//!         8,
+//!         GVRTEX_TEXTURE_GCIX,
+//!         0x05, // Rgb5a3
+//!         0,
+//!         &mut encoded,
+//!     );
+//!     assert_eq!(status, GvrStatus::Ok);
+//!
+//!     let status = gvrtex_decode(encoded.data, encoded.len, &mut decoded);
+//!     assert_eq!(status, GvrStatus::Ok);
+//!     assert_eq!((decoded.width, decoded.height), (8, 8));
+//!     assert_eq!(std::slice::from_raw_parts(decoded.pixels, decoded.len), pixels);
+//!
+//!     gvrtex_free_encoded(&mut encoded);
+//!     gvrtex_free(&mut decoded);
+//!     assert!(decoded.pixels.is_null());
+//!
+//!     let status = gvrtex_decode(b"XXXX".as_ptr(), 4, &mut decoded);
+//!     assert_eq!(status, GvrStatus::DecodeError);
+//!     assert!(!gvrtex_last_error_message().is_null());
+//! }
+//! ```
+
+use crate::error::TextureEncodeError;
+use crate::formats::{DataFormat, PixelFormat, TextureType};
+use crate::{TextureDecoder, TextureEncoder};
+use image::{DynamicImage, RgbaImage};
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::io::Cursor;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// The texture starts with a GCIX header, see [`TextureType::Gcix`].
+pub const GVRTEX_TEXTURE_GCIX: u8 = 0;
+/// The texture starts with a GBIX header, see [`TextureType::Gbix`].
+pub const GVRTEX_TEXTURE_GBIX: u8 = 1;
+/// The texture starts directly with the GVRT header, see [`TextureType::None`].
+pub const GVRTEX_TEXTURE_NONE: u8 = 2;
+
+/// The status code returned by the functions of the C API.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GvrStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer that has to be valid was null.
+    NullPointer = -1,
+    /// One of the arguments was out of range.
+    InvalidArgument = -2,
+    /// The texture couldn't be decoded.
+    DecodeError = -3,
+    /// The image couldn't be encoded.
+    EncodeError = -4,
+    /// The library panicked. This is a bug in gvrtex.
+    Panic = -5,
+}
+
+/// A decoded texture, filled in by [`gvrtex_decode()`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct GvrDecoded {
+    /// The pixels of the texture in row-major order, 4 bytes each in RGBA order.
+    pub pixels: *mut u8,
+    /// The length of `pixels` in bytes.
+    pub len: usize,
+    /// The width of the texture in pixels.
+    pub width: u32,
+    /// The height of the texture in pixels.
+    pub height: u32,
+}
+
+impl Default for GvrDecoded {
+    fn default() -> Self {
+        Self {
+            pixels: ptr::null_mut(),
+            len: 0,
+            width: 0,
+            height: 0,
+        }
+    }
+}
+
+/// An encoded texture, filled in by [`gvrtex_encode()`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct GvrEncoded {
+    /// The bytes of the GVR texture file.
+    pub data: *mut u8,
+    /// The length of `data` in bytes.
+    pub len: usize,
+}
+
+impl Default for GvrEncoded {
+    fn default() -> Self {
+        Self {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior nul bytes can't be represented, so the message is cut off at the first one
+    let message = message.split('\0').next().unwrap_or_default().to_owned();
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, turning its error or panic into a [`GvrStatus`] and the last error message.
+fn guard(f: impl FnOnce() -> Result<(), (GvrStatus, String)>) -> GvrStatus {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => GvrStatus::Ok,
+        Ok(Err((status, message))) => {
+            set_last_error(message);
+            status
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_owned());
+            set_last_error(format!("gvrtex panicked: {message}"));
+            GvrStatus::Panic
+        }
+    }
+}
+
+/// Hands the ownership of `bytes` over to the caller as a raw pointer and a length.
+fn into_raw_parts(bytes: Vec<u8>) -> (*mut u8, usize) {
+    let len = bytes.len();
+    (Box::into_raw(bytes.into_boxed_slice()) as *mut u8, len)
+}
+
+/// Takes back the ownership of a buffer given out by [`into_raw_parts()`] and drops it.
+///
+/// # Safety
+///
+/// `data` and `len` have to come from [`into_raw_parts()`], and the buffer can't be freed yet.
+unsafe fn free_raw_parts(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+/// Decodes the GVR texture in the `len` bytes at `data` into RGBA pixels, writing them to `out`.
+///
+/// The pixels written to `out` have to be released with [`gvrtex_free()`]. On failure, `out` is
+/// left untouched.
+///
+/// # Safety
+///
+/// `data` has to point to `len` readable bytes, and `out` has to point to a writable
+/// [`GvrDecoded`]. Any pixels `out` already holds aren't released.
+#[no_mangle]
+pub unsafe extern "C" fn gvrtex_decode(
+    data: *const u8,
+    len: usize,
+    out: *mut GvrDecoded,
+) -> GvrStatus {
+    guard(|| {
+        if data.is_null() || out.is_null() {
+            return Err((GvrStatus::NullPointer, "data or out is null".to_owned()));
+        }
+
+        let bytes = std::slice::from_raw_parts(data, len);
+        let mut decoder = TextureDecoder::new_from_reader(Cursor::new(bytes));
+        let image = decoder
+            .decode()
+            .and_then(|_| decoder.into_decoded())
+            .map_err(|e| (GvrStatus::DecodeError, e.to_string()))?;
+
+        let (width, height) = image.dimensions();
+        let (pixels, len) = into_raw_parts(image.into_raw());
+        out.write(GvrDecoded {
+            pixels,
+            len,
+            width,
+            height,
+        });
+        Ok(())
+    })
+}
+
+/// Encodes `width` x `height` RGBA pixels into a GVR texture, writing it to `out`.
+///
+/// `texture_type` is one of the `GVRTEX_TEXTURE_*` constants, and `data_format` is the value of
+/// the data format as it's stored in the GVRT header. `pixel_format` is the value of the pixel
+/// format of the color palette in the same way, and is only used if `data_format` is one of the
+/// palettized formats.
+///
+/// The texture written to `out` has to be released with [`gvrtex_free_encoded()`]. On failure,
+/// `out` is left untouched.
+///
+/// # Safety
+///
+/// `pixels` has to point to `len` readable bytes, and `out` has to point to a writable
+/// [`GvrEncoded`]. Any texture `out` already holds isn't released.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn gvrtex_encode(
+    pixels: *const u8,
+    len: usize,
+    width: u32,
+    height: u32,
+    texture_type: u8,
+    data_format: u8,
+    pixel_format: u8,
+    out: *mut GvrEncoded,
+) -> GvrStatus {
+    guard(|| {
+        if pixels.is_null() || out.is_null() {
+            return Err((GvrStatus::NullPointer, "pixels or out is null".to_owned()));
+        }
+
+        let invalid = |message: String| (GvrStatus::InvalidArgument, message);
+        let texture_type = match texture_type {
+            GVRTEX_TEXTURE_GCIX => TextureType::Gcix,
+            GVRTEX_TEXTURE_GBIX => TextureType::Gbix,
+            GVRTEX_TEXTURE_NONE => TextureType::None,
+            _ => return Err(invalid(format!("unknown texture type {texture_type}"))),
+        };
+        let data_format = DataFormat::try_from(data_format)
+            .map_err(|_| invalid(format!("unknown data format {data_format:#04X}")))?;
+
+        let bytes = std::slice::from_raw_parts(pixels, len).to_vec();
+        let image = RgbaImage::from_raw(width, height, bytes).ok_or_else(|| {
+            invalid(format!(
+                "{len} bytes of pixels don't make up a {width}x{height} image"
+            ))
+        })?;
+
+        let encode_error = |e: TextureEncodeError| (GvrStatus::EncodeError, e.to_string());
+        let mut encoder = if matches!(data_format, DataFormat::Index4 | DataFormat::Index8) {
+            let pixel_format = PixelFormat::try_from(pixel_format)
+                .map_err(|_| invalid(format!("unknown pixel format {pixel_format:#04X}")))?;
+            TextureEncoder::new_palettized(texture_type, pixel_format, data_format)
+        } else {
+            TextureEncoder::new(texture_type, data_format)
+        }
+        .map_err(encode_error)?;
+        let encoded = encoder
+            .encode_image(DynamicImage::ImageRgba8(image))
+            .map_err(encode_error)?;
+
+        let (data, len) = into_raw_parts(encoded);
+        out.write(GvrEncoded { data, len });
+        Ok(())
+    })
+}
+
+/// Releases the pixels of a texture decoded by [`gvrtex_decode()`], resetting `decoded`.
+///
+/// Passing a null pointer or an already released texture does nothing.
+///
+/// # Safety
+///
+/// `decoded` has to be null or point to a [`GvrDecoded`] that was filled in by
+/// [`gvrtex_decode()`] or reset by this function.
+#[no_mangle]
+pub unsafe extern "C" fn gvrtex_free(decoded: *mut GvrDecoded) {
+    if let Some(decoded) = decoded.as_mut() {
+        free_raw_parts(decoded.pixels, decoded.len);
+        *decoded = GvrDecoded::default();
+    }
+}
+
+/// Releases the data of a texture encoded by [`gvrtex_encode()`], resetting `encoded`.
+///
+/// Passing a null pointer or an already released texture does nothing.
+///
+/// # Safety
+///
+/// `encoded` has to be null or point to a [`GvrEncoded`] that was filled in by
+/// [`gvrtex_encode()`] or reset by this function.
+#[no_mangle]
+pub unsafe extern "C" fn gvrtex_free_encoded(encoded: *mut GvrEncoded) {
+    if let Some(encoded) = encoded.as_mut() {
+        free_raw_parts(encoded.data, encoded.len);
+        *encoded = GvrEncoded::default();
+    }
+}
+
+/// Returns a description of the error of the last call to [`gvrtex_decode()`] or
+/// [`gvrtex_encode()`] on this thread, or null if it succeeded.
+///
+/// The string is nul-terminated UTF-8, and stays valid until the next call to either of them on
+/// the same thread. It's owned by the library, so it mustn't be freed.
+#[no_mangle]
+pub extern "C" fn gvrtex_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -1,19 +1,144 @@
+//! The traits the encoders and decoders of each data format implement.
+//!
+//! These can be implemented to support data formats this crate doesn't know about, such as a
+//! format specific to a single game. The custom encoders and decoders are registered with
+//! [`crate::TextureEncoder::with_custom_encoder()`] and
+//! [`crate::TextureDecoder::with_custom_decoder()`], under the value the data format has in the
+//! GVRT header.
+//!
+//! The encoders and decoders of the data formats this crate supports are available through
+//! [`crate::formats::DataFormat::encoder()`], [`crate::formats::DataFormat::decoder()`] and their
+//! palette counterparts. These work with the raw GX image data only, without any GVR header, which
+//! is useful for textures stored in other containers.
+//!
+//! # Examples
+//!
+//! A data format storing the pixels as is, row by row, under the data format value 0x42:
+//!
+//! ```
+//! use gvrtex::codec::{GvrBase, GvrDecoder, GvrEncoder, GvrEncoderBase};
+//! use gvrtex::formats::TextureType;
+//! use gvrtex::{TextureDecoder, TextureEncoder};
+//! use image::{DynamicImage, Rgba, RgbaImage};
+//! use std::io::Read;
+//!
+//! struct RawRgba;
+//!
+//! impl GvrBase for RawRgba {
+//!     fn get_block_size(&self) -> (u32, u32) {
+//!         (1, 1)
+//!     }
+//! }
+//!
+//! impl GvrEncoderBase for RawRgba {}
+//!
+//! impl GvrEncoder for RawRgba {
+//!     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
+//!         image.as_raw().clone()
+//!     }
+//! }
+//!
+//! impl GvrDecoder for RawRgba {
+//!     fn decode(
+//!         &self,
+//!         reader: &mut dyn Read,
+//!         width: u32,
+//!         height: u32,
+//!     ) -> Result<RgbaImage, std::io::Error> {
+//!         let mut pixels = vec![0; (width * height * 4) as usize];
+//!         reader.read_exact(&mut pixels)?;
+//!         Ok(RgbaImage::from_raw(width, height, pixels).unwrap())
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let image = RgbaImage::from_fn(3, 5, |x, y| Rgba([x as u8, y as u8, 0x80, 0xFF]));
+//! let mut encoder = TextureEncoder::new(TextureType::Gcix, Default::default())?
+//!     .with_custom_encoder(0x42, Box::new(RawRgba));
+//! let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+//! assert_eq!(encoded[0x1B], 0x42);
+//!
+//! let mut decoder =
+//!     TextureDecoder::new_from_buffer(encoded).with_custom_decoder(0x42, Box::new(RawRgba));
+//! decoder.decode()?;
+//! assert_eq!(decoder.into_decoded()?, image);
+//! # Ok(())
+//! # }
+//! ```
+
 use crate::formats::PixelFormat;
 use crate::TextureEncodeError;
-use image::RgbaImage;
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+use std::io::Read;
 
+/// The base of every encoder and decoder.
 pub trait GvrBase {
+    /// Returns the width and height of the blocks the data format stores its pixels in.
     fn get_block_size(&self) -> (u32, u32);
 }
 
+/// Checks that images can be encoded by an encoder, which is done before
+/// [`GvrEncoder::encode()`] is called.
+///
+/// The default implementation requires the width and height of the image to be at least the size
+/// of a block, a multiple of the bigger side of a block, and small enough to be stored in the
+/// header of a texture.
 pub trait GvrEncoderBase: GvrBase {
+    /// Checks that the given `image` can be encoded.
     fn validate_input(&self, image: &RgbaImage) -> Result<(), TextureEncodeError> {
+        self.validate_dimensions(image.width(), image.height())
+    }
+
+    /// Checks that an image of the given dimensions can be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::codec::GvrEncoderBase;
+    /// use gvrtex::error::TextureEncodeError;
+    /// use gvrtex::formats::DataFormat;
+    ///
+    /// let encoder = DataFormat::Argb8888.encoder().unwrap();
+    /// assert!(encoder.validate_dimensions(1024, 512).is_ok());
+    ///
+    /// // 65536 * 65536 pixels at 4 bytes each overflows a u32, and the header can't store either
+    /// // dimension
+    /// assert!(matches!(
+    ///     encoder.validate_dimensions(65536, 65536),
+    ///     Err(TextureEncodeError::DimensionsTooLarge(65536, 65536))
+    /// ));
+    /// assert!(matches!(
+    ///     encoder.validate_dimensions(1 << 20, 4),
+    ///     Err(TextureEncodeError::DimensionsTooLarge(..))
+    /// ));
+    ///
+    /// // The widest image that's accepted can still be encoded, and decoded back
+    /// use gvrtex::formats::{PixelFormat, TextureType};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// let image = RgbaImage::from_fn(65528, 8, |x, y| {
+    ///     let level = if (x / 7 + y) % 2 == 0 { 0x00 } else { 0xFF };
+    ///     Rgba([level, level, level, 0xFF])
+    /// });
+    /// let argb8888 = TextureEncoder::new(TextureType::Gcix, DataFormat::Argb8888).unwrap();
+    /// let index4 = TextureEncoder::new_palettized(
+    ///     TextureType::Gcix,
+    ///     PixelFormat::RGB565,
+    ///     DataFormat::Index4,
+    /// )
+    /// .unwrap();
+    /// for mut encoder in [argb8888, index4] {
+    ///     let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone())).unwrap();
+    ///     let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    ///     decoder.decode().unwrap();
+    ///     assert_eq!(decoder.into_decoded().unwrap(), image);
+    /// }
+    /// ```
+    fn validate_dimensions(&self, width: u32, height: u32) -> Result<(), TextureEncodeError> {
         let (x_block_size, y_block_size) = self.get_block_size();
         let biggest_block = x_block_size.max(y_block_size);
 
-        let width = image.width();
-        let height = image.height();
-
         if width < x_block_size || height < y_block_size {
             return Err(TextureEncodeError::SmallDimensions(
                 width,
@@ -23,7 +148,7 @@ pub trait GvrEncoderBase: GvrBase {
             ));
         }
 
-        if width % biggest_block != 0 || height % biggest_block != 0 {
+        if !width.is_multiple_of(biggest_block) || !height.is_multiple_of(biggest_block) {
             return Err(TextureEncodeError::InvalidDimensions(
                 width,
                 height,
@@ -31,32 +156,138 @@ pub trait GvrEncoderBase: GvrBase {
             ));
         }
 
+        // The header stores the dimensions as u16, and the buffers of the encoders hold up to
+        // 4 bytes for each pixel
+        let fits_header = u16::try_from(width).is_ok() && u16::try_from(height).is_ok();
+        let fits_buffer = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .is_some();
+        if !fits_header || !fits_buffer {
+            return Err(TextureEncodeError::DimensionsTooLarge(width, height));
+        }
+
         Ok(())
     }
 }
 
+/// Encodes images into the texture data of a data format.
 pub trait GvrEncoder: GvrEncoderBase {
+    /// Encodes the given `image`, which has already been checked with
+    /// [`GvrEncoderBase::validate_input()`].
     fn encode(&self, image: &RgbaImage) -> Vec<u8>;
 }
 
+/// Encodes images into the texture data of a palettized data format, which consists of a color
+/// palette followed by the indices into said palette.
 pub trait GvrEncoderPalette: GvrEncoderBase {
+    /// Generates a color palette in the given `palette_pixel_format` for the given `image`, which
+    /// has already been checked with [`GvrEncoderBase::validate_input()`], and encodes the image
+    /// with it.
     fn encode(
         &self,
         image: &RgbaImage,
         palette_pixel_format: PixelFormat,
     ) -> Result<Vec<u8>, imagequant::Error>;
+
+    /// Encodes an image that has already been split into a color `palette` and row-major
+    /// `indices` into said palette, without quantizing it again.
+    fn encode_indexed(
+        &self,
+        palette: &[Rgba<u8>],
+        indices: &[u8],
+        width: u32,
+        height: u32,
+        palette_pixel_format: PixelFormat,
+    ) -> Vec<u8>;
 }
 
+/// Decodes the texture data of a data format into images.
 pub trait GvrDecoder: GvrBase {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error>;
+    /// Decodes an image of the given dimensions out of the texture data in `reader`.
+    ///
+    /// The `reader` only holds the texture data, as much of it as the header of the texture
+    /// states.
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error>;
+
+    /// Decodes the image in the color type that best fits the data format, for example grayscale
+    /// images for the intensity formats.
+    ///
+    /// By default this returns the same RGBA image as [`Self::decode()`].
+    fn decode_dynamic(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<DynamicImage, std::io::Error> {
+        Ok(DynamicImage::ImageRgba8(
+            self.decode(reader, width, height)?,
+        ))
+    }
+
+    /// Decodes only the given `region` of the image, which is given as a tuple of the x and y
+    /// coordinate, width and height of the region.
+    ///
+    /// By default this decodes the whole image and crops it afterwards.
+    fn decode_region(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+        region: (u32, u32, u32, u32),
+    ) -> Result<RgbaImage, std::io::Error> {
+        let (x, y, region_width, region_height) = region;
+        let image = self.decode(reader, width, height)?;
+        Ok(imageops::crop_imm(&image, x, y, region_width, region_height).to_image())
+    }
 }
 
+/// Decodes the texture data of a palettized data format into the color palette and the indices
+/// into said palette.
 pub trait GvrDecoderPalette: GvrBase {
-    fn decode(
+    /// Decodes the color palette and the palette indices of the image, with the indices being
+    /// in row-major order.
+    fn decode_indexed(
         &self,
-        data: &[u8],
+        reader: &mut dyn Read,
         width: u32,
         height: u32,
         palette_pixel_format: PixelFormat,
-    ) -> Result<RgbaImage, std::io::Error>;
+    ) -> Result<(Vec<Rgba<u8>>, Vec<u8>), std::io::Error>;
+
+    /// Decodes only the palette indices of the image in row-major order, for textures whose color
+    /// palette isn't stored alongside the indices.
+    fn decode_indices(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, std::io::Error>;
+}
+
+/// Flips the given row-major `indices` of an image with the given `width` vertically.
+pub(crate) fn flip_indices_vertical(indices: &[u8], width: u32) -> Vec<u8> {
+    indices
+        .chunks(width as usize)
+        .rev()
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Creates an image out of the given `palette` and row-major `indices` into the palette.
+pub(crate) fn expand_indexed(
+    palette: &[Rgba<u8>],
+    indices: &[u8],
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        palette[indices[(y * width + x) as usize] as usize]
+    })
 }
@@ -0,0 +1,476 @@
+use crate::error::TextureEncodeError;
+use crate::formats::{DataFormat, DxtLayout};
+use crate::pixel_codecs::create_new_decoder;
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use image::error::{DecodingError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+use image::{imageops, ImageError, Rgba, RgbaImage};
+
+/// The size of the DDS header, not counting the magic string in front of it.
+const DDS_HEADER_SIZE: u32 = 124;
+/// The size of the pixel format structure inside the DDS header.
+const DDS_PIXEL_FORMAT_SIZE: u32 = 32;
+/// The size of the extended header that follows the DDS header in files with the "DX10" fourCC.
+const DDS_DX10_HEADER_SIZE: usize = 20;
+/// The DXGI formats that hold BC1 blocks, in linear and sRGB color space.
+const DXGI_FORMAT_BC1_UNORM: u32 = 71;
+const DXGI_FORMAT_BC1_UNORM_SRGB: u32 = 72;
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+/// The layout of the pixels in a DDS file.
+enum DdsPixelFormat {
+    /// BC1 compressed blocks, stored under the "DXT1" fourCC.
+    Dxt1,
+    /// Uncompressed 32-bit pixels, with the color channels in RGBA order.
+    Rgba8,
+}
+
+/// Writes the DDS magic string and header for a texture with the given dimensions and
+/// `mipmap_count`, which counts the full size image as well.
+fn write_header(
+    buf: &mut Vec<u8>,
+    width: u32,
+    height: u32,
+    mipmap_count: u32,
+    pixel_format: DdsPixelFormat,
+) {
+    let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+    let mut caps = DDSCAPS_TEXTURE;
+    if mipmap_count > 1 {
+        flags |= DDSD_MIPMAPCOUNT;
+        caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    }
+
+    let pitch_or_linear_size = match pixel_format {
+        DdsPixelFormat::Dxt1 => {
+            flags |= DDSD_LINEARSIZE;
+            width.div_ceil(4).max(1) * height.div_ceil(4).max(1) * 8
+        }
+        DdsPixelFormat::Rgba8 => {
+            flags |= DDSD_PITCH;
+            width * 4
+        }
+    };
+
+    buf.extend_from_slice(b"DDS ");
+    buf.write_u32::<LittleEndian>(DDS_HEADER_SIZE).unwrap();
+    buf.write_u32::<LittleEndian>(flags).unwrap();
+    buf.write_u32::<LittleEndian>(height).unwrap();
+    buf.write_u32::<LittleEndian>(width).unwrap();
+    buf.write_u32::<LittleEndian>(pitch_or_linear_size).unwrap();
+    // Depth, only used by volume textures
+    buf.write_u32::<LittleEndian>(0).unwrap();
+    buf.write_u32::<LittleEndian>(mipmap_count).unwrap();
+    buf.resize(buf.len() + 11 * size_of::<u32>(), 0);
+
+    buf.write_u32::<LittleEndian>(DDS_PIXEL_FORMAT_SIZE)
+        .unwrap();
+    match pixel_format {
+        DdsPixelFormat::Dxt1 => {
+            buf.write_u32::<LittleEndian>(DDPF_FOURCC).unwrap();
+            buf.extend_from_slice(b"DXT1");
+            // The bit count and masks are unused for compressed formats
+            buf.resize(buf.len() + 5 * size_of::<u32>(), 0);
+        }
+        DdsPixelFormat::Rgba8 => {
+            buf.write_u32::<LittleEndian>(DDPF_RGB | DDPF_ALPHAPIXELS)
+                .unwrap();
+            buf.write_u32::<LittleEndian>(0).unwrap();
+            buf.write_u32::<LittleEndian>(32).unwrap();
+            buf.write_u32::<LittleEndian>(0x000000FF).unwrap();
+            buf.write_u32::<LittleEndian>(0x0000FF00).unwrap();
+            buf.write_u32::<LittleEndian>(0x00FF0000).unwrap();
+            buf.write_u32::<LittleEndian>(0xFF000000).unwrap();
+        }
+    }
+
+    buf.write_u32::<LittleEndian>(caps).unwrap();
+    // The rest of the caps and the reserved field are unused
+    buf.resize(buf.len() + 4 * size_of::<u32>(), 0);
+}
+
+/// Returns whether the DXT1 blocks of an image of the given `height` can be flipped vertically
+/// without decoding them, by reversing the order of the rows of blocks and the rows of pixels in
+/// each block.
+///
+/// This only works if the image fits in a single row of blocks, or every row of blocks is full.
+/// Otherwise the rows of pixels in the last, partial row of blocks would have to move across
+/// blocks, which each have their own colors. For example, the top block of a 6 pixel tall image
+/// has to hold the rows 5, 4, 3 and 2 of the image once flipped, which come from both blocks.
+pub(crate) fn can_flip_blocks(height: u32) -> bool {
+    height <= 4 || height.is_multiple_of(4)
+}
+
+/// Converts a single GameCube DXT1 block into a PC BC1 block, or the other way around.
+///
+/// The GameCube stores the two endpoint colors in big endian, and orders the 2-bit indices in
+/// each row with the leftmost pixel in the highest bits, both the opposite of BC1. The rows of
+/// the block are mirrored if `flip_rows` is set to the amount of rows used by the image.
+fn convert_dxt1_block(block: &[u8], flip_rows: Option<usize>) -> [u8; 8] {
+    let mut indices = [block[4], block[5], block[6], block[7]];
+    if let Some(rows) = flip_rows {
+        indices[..rows].reverse();
+    }
+
+    let [i0, i1, i2, i3] = indices.map(|row| {
+        ((row & 0x03) << 6) | ((row & 0x0C) << 2) | ((row & 0x30) >> 2) | ((row & 0xC0) >> 6)
+    });
+    [block[1], block[0], block[3], block[2], i0, i1, i2, i3]
+}
+
+/// Reorders the GameCube DXT1 blocks of a single image in `data` into the linear block order of a
+/// DDS file, appending them to `buf`.
+///
+/// The GameCube groups the blocks into 8x8 pixel tiles of 2x2 blocks, while a DDS file stores
+/// each row of blocks one after the other. The blocks in `data` are read in the given `layout`.
+/// Blocks that only exist to pad the image out to a full tile are left out. The blocks can only
+/// be flipped with `flip_v` if [`can_flip_blocks()`] allows for it.
+fn write_dxt1_blocks(
+    buf: &mut Vec<u8>,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    flip_v: bool,
+    layout: DxtLayout,
+) {
+    let tiles_x = width.div_ceil(8) as usize;
+    let blocks_x = width.div_ceil(4) as usize;
+    let blocks_y = height.div_ceil(4) as usize;
+    let flip_rows = flip_v.then_some(height.min(4) as usize);
+
+    for dds_block_y in 0..blocks_y {
+        let block_y = if flip_v {
+            blocks_y - 1 - dds_block_y
+        } else {
+            dds_block_y
+        };
+
+        for block_x in 0..blocks_x {
+            let offset = layout.block_index(block_x, block_y, tiles_x) * 8;
+
+            buf.extend_from_slice(&convert_dxt1_block(&data[offset..offset + 8], flip_rows));
+        }
+    }
+}
+
+/// Creates a DDS file out of the GameCube DXT1 image data in `data`, without decoding the blocks.
+///
+/// If `mipmaps` is set, every mipmap that fits inside `data` is carried over as well. The blocks
+/// can only be flipped with `flip_v` if [`can_flip_blocks()`] allows for it at every level.
+pub(crate) fn dxt1_to_dds(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    mipmaps: bool,
+    flip_v: bool,
+    layout: DxtLayout,
+) -> Vec<u8> {
+    // Every mipmap halves the dimensions of the previous one, all the way down to 1x1
+    let mut levels = vec![(width, height)];
+    let mut image_len = DataFormat::Dxt1.image_size(width, height);
+    let (mut level_width, mut level_height) = (width, height);
+    while mipmaps && (level_width > 1 || level_height > 1) {
+        level_width = (level_width / 2).max(1);
+        level_height = (level_height / 2).max(1);
+
+        let mipmap_len = DataFormat::Dxt1.image_size(level_width, level_height);
+        if image_len + mipmap_len > data.len() {
+            break;
+        }
+        image_len += mipmap_len;
+        levels.push((level_width, level_height));
+    }
+
+    let mut buf = Vec::new();
+    write_header(
+        &mut buf,
+        width,
+        height,
+        levels.len() as u32,
+        DdsPixelFormat::Dxt1,
+    );
+
+    let mut offset = 0;
+    for (level_width, level_height) in levels {
+        write_dxt1_blocks(
+            &mut buf,
+            &data[offset..],
+            level_width,
+            level_height,
+            flip_v,
+            layout,
+        );
+        offset += DataFormat::Dxt1.image_size(level_width, level_height);
+    }
+    buf
+}
+
+/// Creates an uncompressed DDS file out of the given `image`.
+pub(crate) fn rgba_to_dds(image: &RgbaImage) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(128 + image.as_raw().len());
+    write_header(
+        &mut buf,
+        image.width(),
+        image.height(),
+        1,
+        DdsPixelFormat::Rgba8,
+    );
+    buf.extend_from_slice(image.as_raw());
+    buf
+}
+
+/// The image data of a DDS file parsed by [`parse_dds()`].
+pub(crate) enum DdsData<'a> {
+    /// The BC1 blocks of the full size image, followed by the blocks of every mipmap.
+    Bc1(&'a [u8]),
+    /// The full size image, decoded from uncompressed pixels.
+    Rgba(RgbaImage),
+}
+
+/// A DDS file parsed by [`parse_dds()`].
+pub(crate) struct DdsTexture<'a> {
+    pub width: u32,
+    pub height: u32,
+    /// The amount of images stored in the file, counting the full size image as well. Only
+    /// mipmaps that fully fit inside the file are counted.
+    pub mipmap_count: u32,
+    pub data: DdsData<'a>,
+}
+
+fn invalid_dds(message: &str) -> TextureEncodeError {
+    TextureEncodeError::Encode(ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("DDS".to_string()),
+        message.to_string(),
+    )))
+}
+
+fn unsupported_dds(feature: String) -> TextureEncodeError {
+    TextureEncodeError::Encode(ImageError::Unsupported(
+        UnsupportedError::from_format_and_kind(
+            ImageFormatHint::Name("DDS".to_string()),
+            UnsupportedErrorKind::GenericFeature(feature),
+        ),
+    ))
+}
+
+/// Returns the size of the BC1 blocks of a single image with the given dimensions, or [`None`]
+/// if it doesn't fit in a `usize`.
+fn bc1_size(width: u32, height: u32) -> Option<usize> {
+    (width.div_ceil(4) as usize)
+        .checked_mul(height.div_ceil(4) as usize)?
+        .checked_mul(8)
+}
+
+/// Parses the given DDS file.
+///
+/// Files holding BC1 blocks, under either the "DXT1" fourCC or the BC1 DXGI formats of a "DX10"
+/// header, are kept compressed. Files holding uncompressed 24-bit or 32-bit RGB pixels are decoded
+/// into an image, without their mipmaps. Every other format isn't supported.
+pub(crate) fn parse_dds(dds_bytes: &[u8]) -> Result<DdsTexture<'_>, TextureEncodeError> {
+    let Some(header) = dds_bytes.get(..128) else {
+        return Err(invalid_dds("the file is too short to hold a DDS header"));
+    };
+    if &header[..4] != b"DDS " || LittleEndian::read_u32(&header[4..8]) != DDS_HEADER_SIZE {
+        return Err(invalid_dds("the file doesn't start with a DDS header"));
+    }
+
+    let height = LittleEndian::read_u32(&header[12..16]);
+    let width = LittleEndian::read_u32(&header[16..20]);
+    if width == 0 || height == 0 {
+        return Err(invalid_dds("the image has no pixels"));
+    }
+    let mipmap_count = LittleEndian::read_u32(&header[28..32]).max(1);
+
+    let pixel_flags = LittleEndian::read_u32(&header[80..84]);
+    let four_cc = &header[84..88];
+
+    if pixel_flags & DDPF_FOURCC != 0 {
+        let data = match four_cc {
+            b"DXT1" => &dds_bytes[128..],
+            b"DX10" => {
+                let Some(dx10_header) = dds_bytes.get(128..128 + DDS_DX10_HEADER_SIZE) else {
+                    return Err(invalid_dds("the file is too short to hold a DX10 header"));
+                };
+                let dxgi_format = LittleEndian::read_u32(&dx10_header[..4]);
+                if dxgi_format != DXGI_FORMAT_BC1_UNORM && dxgi_format != DXGI_FORMAT_BC1_UNORM_SRGB
+                {
+                    return Err(unsupported_dds(format!("DXGI format {dxgi_format}")));
+                }
+                &dds_bytes[128 + DDS_DX10_HEADER_SIZE..]
+            }
+            _ => {
+                return Err(unsupported_dds(format!(
+                    "fourCC {}",
+                    String::from_utf8_lossy(four_cc)
+                )))
+            }
+        };
+
+        // Every mipmap halves the dimensions of the previous one, down to 1 pixel
+        let mut data_len: usize = 0;
+        let mut fitting_levels = 0;
+        for level in 0..mipmap_count.min(32) {
+            let level_len = bc1_size((width >> level).max(1), (height >> level).max(1))
+                .ok_or(TextureEncodeError::DimensionsTooLarge(width, height))?;
+            match data_len.checked_add(level_len) {
+                Some(len) if len <= data.len() => data_len = len,
+                _ => break,
+            }
+            fitting_levels += 1;
+        }
+        if fitting_levels == 0 {
+            return Err(invalid_dds("the file is too short to hold the image"));
+        }
+
+        return Ok(DdsTexture {
+            width,
+            height,
+            mipmap_count: fitting_levels,
+            data: DdsData::Bc1(&data[..data_len]),
+        });
+    }
+
+    let bit_count = LittleEndian::read_u32(&header[88..92]);
+    if pixel_flags & DDPF_RGB == 0 || (bit_count != 24 && bit_count != 32) {
+        return Err(unsupported_dds(format!(
+            "pixel format with flags {pixel_flags:#X} and {bit_count} bits per pixel"
+        )));
+    }
+
+    let masks = [
+        LittleEndian::read_u32(&header[92..96]),
+        LittleEndian::read_u32(&header[96..100]),
+        LittleEndian::read_u32(&header[100..104]),
+        if pixel_flags & DDPF_ALPHAPIXELS != 0 {
+            LittleEndian::read_u32(&header[104..108])
+        } else {
+            0
+        },
+    ];
+
+    // The pixels are checked to fit inside the file before the image is allocated, so crafted
+    // dimensions can't make it any bigger than the file itself
+    let bytes_per_pixel = bit_count as usize / 8;
+    let image_len = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(bytes_per_pixel))
+        .ok_or(TextureEncodeError::DimensionsTooLarge(width, height))?;
+    let Some(pixels) = dds_bytes
+        .get(128..)
+        .and_then(|pixels| pixels.get(..image_len))
+    else {
+        return Err(invalid_dds("the file is too short to hold the image"));
+    };
+
+    let mut image = RgbaImage::new(width, height);
+    for (pixel, bytes) in image.pixels_mut().zip(pixels.chunks_exact(bytes_per_pixel)) {
+        let value = LittleEndian::read_uint(bytes, bytes_per_pixel) as u32;
+        *pixel = Rgba(masks.map(|mask| extract_channel(value, mask)));
+    }
+
+    Ok(DdsTexture {
+        width,
+        height,
+        mipmap_count: 1,
+        data: DdsData::Rgba(image),
+    })
+}
+
+/// Extracts the color channel in `mask` out of `value`, scaled to 8 bits. Channels without a mask
+/// are fully opaque.
+fn extract_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0xFF;
+    }
+
+    let channel = (value & mask) >> mask.trailing_zeros();
+    let max = mask >> mask.trailing_zeros();
+    ((u64::from(channel) * 255 + u64::from(max) / 2) / u64::from(max)) as u8
+}
+
+/// Reorders the linear BC1 blocks of a single image in `data` into the given `layout` of GameCube
+/// DXT1 image data.
+///
+/// The image is padded out to full 8x8 pixel tiles by repeating the blocks at the right and bottom
+/// edges. The blocks can only be flipped with `flip_v` if [`can_flip_blocks()`] allows for it.
+pub(crate) fn bc1_to_gvr(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    flip_v: bool,
+    layout: DxtLayout,
+) -> Vec<u8> {
+    let tiles_x = width.div_ceil(8) as usize;
+    let tiles_y = height.div_ceil(8) as usize;
+    let blocks_x = width.div_ceil(4) as usize;
+    let blocks_y = height.div_ceil(4) as usize;
+    let flip_rows = flip_v.then_some(height.min(4) as usize);
+
+    let mut encoded = Vec::with_capacity(tiles_x * tiles_y * 32);
+    for index in 0..tiles_x * tiles_y * 4 {
+        let (block_x, block_y) = layout.block_coords(index, tiles_x);
+        let block_x = block_x.min(blocks_x - 1);
+        let block_y = block_y.min(blocks_y - 1);
+        let block_y = if flip_v {
+            blocks_y - 1 - block_y
+        } else {
+            block_y
+        };
+
+        let offset = (block_y * blocks_x + block_x) * 8;
+        encoded.extend_from_slice(&convert_dxt1_block(&data[offset..offset + 8], flip_rows));
+    }
+    encoded
+}
+
+/// Splits the BC1 blocks in `data` into the blocks of each image, starting with the full size
+/// image and followed by `mipmap_count - 1` mipmaps.
+pub(crate) fn bc1_levels(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    mipmap_count: u32,
+) -> Vec<(&[u8], u32, u32)> {
+    let mut levels = Vec::with_capacity(mipmap_count as usize);
+    let mut offset = 0;
+    for level in 0..mipmap_count {
+        let level_width = (width >> level).max(1);
+        let level_height = (height >> level).max(1);
+        // The levels were already checked to fit inside the file by parse_dds()
+        let level_len = bc1_size(level_width, level_height).unwrap();
+        levels.push((&data[offset..offset + level_len], level_width, level_height));
+        offset += level_len;
+    }
+    levels
+}
+
+/// Decodes the BC1 blocks of a single image in `data`.
+pub(crate) fn decode_bc1(
+    data: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<RgbaImage, TextureEncodeError> {
+    // The GameCube decoder only works on full tiles, so the padding is cropped out afterwards
+    let encoded = bc1_to_gvr(data, width, height, false, DxtLayout::GameCube);
+    let decoder = create_new_decoder(DataFormat::Dxt1, DxtLayout::GameCube);
+    let image = decoder.decode(
+        &mut encoded.as_slice(),
+        width.next_multiple_of(8),
+        height.next_multiple_of(8),
+    )?;
+    Ok(imageops::crop_imm(&image, 0, 0, width, height).to_image())
+}
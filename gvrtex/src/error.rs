@@ -1,5 +1,6 @@
 //! Contains all the possible custom error types from encoding and decoding textures.
 
+use image::error::{DecodingError, ImageFormatHint};
 use image::ImageError;
 use std::error::Error;
 use std::fmt;
@@ -22,6 +23,9 @@ pub enum TextureEncodeError {
     /// or you tried to use data formats *other than the two aforementioned formats* when
     /// instantiating with [`crate::TextureEncoder::new_gcix_palettized()`] or
     /// [`crate::TextureEncoder::new_gbix_palettized()`].
+    ///
+    /// It's also returned if the flags given to [`crate::TextureEncoder::with_data_flags()`] are
+    /// not valid for the data format of the encoder.
     Format,
     /// The given source image file has dimensions that are too small for the given [`crate::DataFormat`].
     SmallDimensions(u32, u32, u32, u32),
@@ -33,6 +37,45 @@ pub enum TextureEncodeError {
     /// Easiest way to fix this is by keeping your image dimensions as powers of 2 (for example:
     /// 64x64, 128x64, 512x256, etc).
     InvalidDimensions(u32, u32, u32),
+    /// The given source image has dimensions that aren't powers of 2, which is required by the
+    /// twiddled layout of PVR textures encoded with [`crate::pvr::encode_pvr()`].
+    NotPowerOfTwo(u32, u32),
+    /// The color palette given to [`crate::TextureEncoder::encode_indexed()`] doesn't have the
+    /// amount of colors the data format needs.
+    ///
+    /// Contains the amount of colors the data format needs, followed by the amount of colors given.
+    PaletteLength(usize, usize),
+    /// The indices given to [`crate::TextureEncoder::encode_indexed()`] don't have one index per
+    /// pixel of the given dimensions.
+    ///
+    /// Contains the amount of indices needed, followed by the amount of indices given.
+    IndicesLength(usize, usize),
+    /// The source image has pixels that aren't fully opaque, but the encoder uses a format that
+    /// can't store an alpha channel.
+    ///
+    /// This error is only returned by encoders with [`crate::TextureEncoder::with_strict_alpha()`].
+    AlphaDiscarded,
+    /// The mipmaps given to [`crate::TextureEncoder::with_mipmap_images()`] don't go all the way
+    /// down to 1x1, or go past it.
+    ///
+    /// Contains the amount of mipmaps the image needs, followed by the amount of mipmaps given.
+    MipmapCount(usize, usize),
+    /// A mipmap given to [`crate::TextureEncoder::with_mipmap_images()`] doesn't have half the
+    /// dimensions of the previous one.
+    ///
+    /// Contains the level of the mipmap (starting from 1 for the first mipmap after the full size
+    /// image), followed by the dimensions it needs and the dimensions it has.
+    MipmapDimensions(u32, (u32, u32), (u32, u32)),
+    /// The channel swizzle given to [`crate::TextureEncoder::with_channel_swizzle()`] doesn't
+    /// hold every channel from 0 to 3 exactly once.
+    ///
+    /// Contains the given swizzle.
+    InvalidSwizzle([usize; 4]),
+    /// The given source image is too large to be stored in a GVR texture. The header stores the
+    /// dimensions as 16-bit values, and the length of the texture data as a 32-bit value.
+    ///
+    /// Contains the width and height of the image.
+    DimensionsTooLarge(u32, u32),
 }
 
 impl Error for TextureEncodeError {}
@@ -51,6 +94,32 @@ impl fmt::Display for TextureEncodeError {
             ),
             Self::SmallDimensions(width, height, x_block, y_block) => write!(f, "The dimensions for the input image ({width}x{height}) are too small! Dimensions have to be at least {x_block}x{y_block}."),
             Self::InvalidDimensions(width, height, block_size) => write!(f, "The dimensions for the input image ({width}x{height}) are invalid! Dimensions have to be a multiple of {block_size}."),
+            Self::DimensionsTooLarge(width, height) => write!(f, "The dimensions for the input image ({width}x{height}) are too large to be stored in a GVR texture!"),
+            Self::NotPowerOfTwo(width, height) => write!(f, "The dimensions for the input image ({width}x{height}) are invalid! Dimensions have to be powers of 2."),
+            Self::PaletteLength(expected, given) => write!(
+                f,
+                "The given palette has {given} colors, but the data format needs exactly {expected}."
+            ),
+            Self::IndicesLength(expected, given) => write!(
+                f,
+                "There are {given} indices given, but the dimensions need exactly {expected}."
+            ),
+            Self::AlphaDiscarded => write!(
+                f,
+                "The input image has transparent pixels, but the texture format can't store an alpha channel."
+            ),
+            Self::MipmapCount(expected, given) => write!(
+                f,
+                "There are {given} mipmaps given, but the dimensions of the image need exactly {expected}."
+            ),
+            Self::MipmapDimensions(level, (width, height), (given_width, given_height)) => write!(
+                f,
+                "The mipmap at level {level} is {given_width}x{given_height}, but it has to be {width}x{height}."
+            ),
+            Self::InvalidSwizzle(swizzle) => write!(
+                f,
+                "The channel swizzle {swizzle:?} has to hold every channel from 0 to 3 exactly once."
+            ),
         }
     }
 }
@@ -80,21 +149,95 @@ pub enum TextureDecodeError {
     ///
     /// This can be because of many things, but it all stems from invalid data used in the header
     /// of the file. This can be because the size of the texture portrayed in the file header
-    /// doesn't match the actual filesize, there are invalid flags set in the header, invalid data
+    /// exceeds the actual filesize, there are invalid flags set in the header, invalid data
     /// formats used, or the header is missing the required magic strings.
     ///
     /// The latter option is the most common reason, with the other options only really being possible
     /// if the file was corrupted in some way or the encoder that encoded said file has a bug in it.
+    ///
+    /// The variants below describe the more common problems in more detail, this variant is
+    /// returned for everything else.
     InvalidFile,
+    /// One of the headers of the texture doesn't start with the magic string it should have.
+    ///
+    /// Contains the offset of the header from the start of the texture, and the bytes found in
+    /// place of the magic string.
+    BadMagic {
+        /// The offset of the header from the start of the texture.
+        offset: u64,
+        /// The bytes found in place of the magic string.
+        found: [u8; 4],
+    },
+    /// The GVRT header holds a data format that doesn't exist.
+    UnknownDataFormat {
+        /// The offset of the data format from the start of the texture.
+        offset: u64,
+        /// The value of the data format.
+        value: u8,
+    },
+    /// The GVRT header holds a pixel format for the color palette that doesn't exist.
+    UnknownPixelFormat {
+        /// The offset of the byte holding the pixel format from the start of the texture.
+        offset: u64,
+        /// The value of the pixel format.
+        value: u8,
+    },
+    /// The GVRT header holds flags that can't be used with the data format of the texture.
+    ///
+    /// Flags that aren't known to this crate aren't an error, see [`crate::GvrHeader::flags`].
+    UnknownFlags {
+        /// The offset of the byte holding the flags from the start of the texture.
+        offset: u64,
+        /// The value of the flags.
+        value: u8,
+    },
+    /// The texture data doesn't have the length it needs to have.
+    ///
+    /// This is returned if the texture states a longer length for its data than what's left of
+    /// the file, or if the stated length is too short to hold the image the headers describe.
+    SizeMismatch {
+        /// The length the texture data needs to have, in bytes.
+        expected: usize,
+        /// The actual length of the texture data, in bytes.
+        actual: usize,
+    },
     /// Returned when attempting to access the decoded image before decoding has started,
     /// or after decoding has failed.
     Undecoded,
+    /// The region given to [`crate::TextureDecoder::decode_region()`] is empty or doesn't fit
+    /// inside the texture.
+    ///
+    /// Contains the x and y coordinate, width and height of the given region.
+    InvalidRegion(u32, u32, u32, u32),
+    /// The color palette given to [`crate::palette_swap()`] or
+    /// [`crate::TextureDecoder::with_external_palette()`] doesn't have the same amount of colors
+    /// as the palette of the texture.
+    ///
+    /// Contains the amount of colors the texture needs, followed by the amount of colors given.
+    PaletteLength(usize, usize),
+    /// The texture uses a color palette stored in a separate file, but no palette was given via
+    /// [`crate::TextureDecoder::with_external_palette()`].
+    MissingPalette,
+    /// The texture uses a data format that GVR textures don't support, such as the C14X2 format
+    /// of TPL textures.
+    ///
+    /// Contains the format code of the texture.
+    UnsupportedFormat(u32),
+    /// The channel swizzle given to [`crate::TextureDecoder::with_channel_swizzle()`] doesn't
+    /// hold every channel from 0 to 3 exactly once.
+    ///
+    /// Contains the given swizzle.
+    InvalidSwizzle([usize; 4]),
     /// A standard IO error has occurred.
     Io(std::io::Error),
     /// Something went wrong saving the decoded image.
     ///
     /// This error can only be encountered when using [`crate::TextureDecoder::save()`].
     Image(ImageError),
+    /// Something went wrong encoding the decoded image into another texture.
+    ///
+    /// This error can only be encountered when using [`crate::TextureDecoder::reencode()`].
+    Encode(TextureEncodeError),
 }
 
 impl Error for TextureDecodeError {}
@@ -103,9 +246,49 @@ impl fmt::Display for TextureDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::InvalidFile => write!(f, "The given file is an invalid GVR texture file."),
+            Self::BadMagic { offset, found } => write!(
+                f,
+                "Expected a magic string at offset {offset:#X}, but found \"{}\".",
+                found.escape_ascii()
+            ),
+            Self::UnknownDataFormat { offset, value } => {
+                write!(f, "Unknown data format {value:#04X} at offset {offset:#X}.")
+            }
+            Self::UnknownPixelFormat { offset, value } => {
+                write!(f, "Unknown pixel format {value:#X} at offset {offset:#X}.")
+            }
+            Self::UnknownFlags { offset, value } => write!(
+                f,
+                "Invalid flags {value:#X} for the data format at offset {offset:#X}."
+            ),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "The texture data is {actual} bytes long, but it needs to be {expected} bytes long."
+            ),
             Self::Undecoded => write!(f, "This texture has not been decoded successfully."),
+            Self::InvalidRegion(x, y, width, height) => write!(
+                f,
+                "The given region ({width}x{height} at {x},{y}) doesn't fit inside the texture."
+            ),
+            Self::PaletteLength(expected, given) => write!(
+                f,
+                "The given palette has {given} colors, but the texture needs exactly {expected}."
+            ),
+            Self::MissingPalette => write!(
+                f,
+                "The texture uses an external color palette, but no palette was given."
+            ),
+            Self::UnsupportedFormat(format) => write!(
+                f,
+                "The texture uses the format {format:#04X}, which isn't supported by GVR textures."
+            ),
+            Self::InvalidSwizzle(swizzle) => write!(
+                f,
+                "The channel swizzle {swizzle:?} has to hold every channel from 0 to 3 exactly once."
+            ),
             Self::Io(err) => write!(f, "{err}"),
             Self::Image(err) => write!(f, "{err}"),
+            Self::Encode(err) => write!(f, "{err}"),
         }
     }
 }
@@ -121,3 +304,45 @@ impl From<ImageError> for TextureDecodeError {
         TextureDecodeError::Image(value)
     }
 }
+
+impl From<TextureEncodeError> for TextureDecodeError {
+    fn from(value: TextureEncodeError) -> Self {
+        TextureDecodeError::Encode(value)
+    }
+}
+
+impl From<TextureDecodeError> for ImageError {
+    fn from(value: TextureDecodeError) -> Self {
+        match value {
+            TextureDecodeError::Io(err) => ImageError::IoError(err),
+            TextureDecodeError::Image(err) => err,
+            err => ImageError::Decoding(DecodingError::new(
+                ImageFormatHint::Name("GVR".to_string()),
+                err,
+            )),
+        }
+    }
+}
+
+/// The error returned when parsing a [`crate::formats::DataFormat`] or
+/// [`crate::formats::PixelFormat`] from a string fails, because the name isn't known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFormatError {
+    pub(crate) kind: &'static str,
+    pub(crate) name: String,
+    pub(crate) valid_names: Vec<&'static str>,
+}
+
+impl Error for ParseFormatError {}
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown {} \"{}\", expected one of: {}.",
+            self.kind,
+            self.name,
+            self.valid_names.join(", ")
+        )
+    }
+}
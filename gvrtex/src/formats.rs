@@ -10,15 +10,96 @@
 //! format.
 //!
 //! See [`crate::TextureEncoder`] for where these are used.
+//!
+//! Besides the formats, a GVR texture also stores a set of [`DataFlags`] in its header, which
+//! specify whether the texture has mipmaps or a color palette.
 
-use crate::TextureDecodeError;
+use crate::codec::{GvrDecoder, GvrDecoderPalette, GvrEncoder, GvrEncoderPalette};
+use crate::error::ParseFormatError;
+use crate::pixel_codecs::{
+    create_new_decoder, create_new_decoder_with_palette, create_new_encoder,
+    create_new_encoder_with_palette, palette_size,
+};
+use crate::{TextureDecodeError, TextureEncoder};
 use bitflags::bitflags;
+use std::fmt;
+use std::str::FromStr;
+
+/// The names [`PixelFormat`] can be parsed from, the first name of each format being the one it's
+/// displayed as.
+const PIXEL_FORMAT_NAMES: [(&str, PixelFormat); 4] = [
+    ("intensitya8", PixelFormat::IntensityA8),
+    ("ia8", PixelFormat::IntensityA8),
+    ("rgb565", PixelFormat::RGB565),
+    ("rgb5a3", PixelFormat::RGB5A3),
+];
+
+/// The names [`DataFormat`] can be parsed from, the first name of each format being the one it's
+/// displayed as.
+const DATA_FORMAT_NAMES: [(&str, DataFormat); 18] = [
+    ("intensity4", DataFormat::Intensity4),
+    ("i4", DataFormat::Intensity4),
+    ("intensity8", DataFormat::Intensity8),
+    ("i8", DataFormat::Intensity8),
+    ("intensitya4", DataFormat::IntensityA4),
+    ("ia4", DataFormat::IntensityA4),
+    ("intensitya8", DataFormat::IntensityA8),
+    ("ia8", DataFormat::IntensityA8),
+    ("rgb565", DataFormat::Rgb565),
+    ("rgb5a3", DataFormat::Rgb5a3),
+    ("argb8888", DataFormat::Argb8888),
+    ("rgba8", DataFormat::Argb8888),
+    ("index4", DataFormat::Index4),
+    ("ci4", DataFormat::Index4),
+    ("index8", DataFormat::Index8),
+    ("ci8", DataFormat::Index8),
+    ("dxt1", DataFormat::Dxt1),
+    ("bc1", DataFormat::Dxt1),
+];
+
+/// Looks up the format with the given `name` (ignoring case) in `names`.
+fn parse_format<T: Copy>(
+    name: &str,
+    names: &[(&'static str, T)],
+    kind: &'static str,
+) -> Result<T, ParseFormatError> {
+    names
+        .iter()
+        .find(|(format_name, _)| format_name.eq_ignore_ascii_case(name))
+        .map(|&(_, format)| format)
+        .ok_or_else(|| ParseFormatError {
+            kind,
+            name: name.to_string(),
+            valid_names: names.iter().map(|&(format_name, _)| format_name).collect(),
+        })
+}
+
+/// Returns the display name of the given `format` in `names`.
+fn format_name<T: PartialEq>(format: &T, names: &[(&'static str, T)]) -> &'static str {
+    names
+        .iter()
+        .find(|(_, named_format)| named_format == format)
+        .map(|&(format_name, _)| format_name)
+        .unwrap()
+}
 
-#[derive(Default, PartialEq, Eq)]
-pub(crate) enum TextureType {
+/// This enum specifies which header a GVR texture starts with, before the GVRT header that
+/// describes the texture data itself.
+///
+/// The GCIX and GBIX headers are identical besides their magic string, which one is used depends
+/// on the game. GameCube games generally use GCIX, while Wii games use GBIX.
+///
+/// The header is chosen when creating an encoder with [`crate::TextureEncoder::new()`] or
+/// [`crate::TextureEncoder::new_palettized()`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureType {
+    /// The texture starts with a GCIX header.
     #[default]
     Gcix,
+    /// The texture starts with a GBIX header.
     Gbix,
+    /// No GCIX/GBIX header at all, the file starts directly with the GVRT header.
+    None,
 }
 
 /// This enum specifies the format the color palette for a palettized GVR texture will be encoded
@@ -30,7 +111,7 @@ pub(crate) enum TextureType {
 /// to refer to the color palette).
 ///
 /// See [`crate::TextureEncoder::new_gcix_palettized()`] and [`crate::TextureEncoder::new_gbix_palettized()`]
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum PixelFormat {
     /// See [`DataFormat::IntensityA8`]
@@ -48,6 +129,34 @@ impl From<PixelFormat> for u8 {
     }
 }
 
+impl PixelFormat {
+    /// Checks if this format can store an alpha channel.
+    pub fn has_alpha(&self) -> bool {
+        match self {
+            Self::IntensityA8 | Self::RGB5A3 => true,
+            Self::RGB565 => false,
+        }
+    }
+}
+
+impl fmt::Display for PixelFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(format_name(self, &PIXEL_FORMAT_NAMES))
+    }
+}
+
+/// Parses a pixel format from its name, ignoring case.
+///
+/// The names are the same as the ones of the matching [`DataFormat`]: "intensitya8" (or "ia8"),
+/// "rgb565" and "rgb5a3".
+impl FromStr for PixelFormat {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_format(s, &PIXEL_FORMAT_NAMES, "pixel format")
+    }
+}
+
 impl TryFrom<u8> for PixelFormat {
     type Error = TextureDecodeError;
 
@@ -68,7 +177,7 @@ impl TryFrom<u8> for PixelFormat {
 /// [`DataFormat::Index8`], then use [`crate::TextureEncoder::new_gcix_palettized()`] or
 /// [`crate::TextureEncoder::new_gbix_palettized()`]. That way you can specify the color format for
 /// the color palette alongside the data format.
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum DataFormat {
     /// Stores 4-bit intensity values (each pixel is composed of just one value). This makes the
@@ -82,6 +191,29 @@ pub enum DataFormat {
     IntensityA4 = 0x02,
     /// Stores 8-bit intensity values (each pixel is composed of just one value) along with an
     /// alpha channel. This makes the image look grayscale.
+    ///
+    /// Each pixel takes up 2 bytes, the alpha followed by the intensity. The alpha is stored as is,
+    /// and the intensity is worked out from the color channels alone, not weighted by the alpha.
+    /// An opaque image stores `0xFF` as the alpha of every pixel, which [`DataFormat::Intensity8`]
+    /// leaves out. See [`crate::ImageStats::suggest_format()`] for picking between the two.
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 4, |x, y| Rgba([(x * 32) as u8, (y * 64) as u8, 0x80, 0xFF]));
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::IntensityA8)?.without_outer_header();
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// let data = &encoded[0x10..];
+    /// assert_eq!(data.len(), 8 * 4 * 2);
+    /// assert!(data.chunks(2).all(|pixel| pixel[0] == 0xFF));
+    /// # Ok(())
+    /// # }
+    /// ```
     IntensityA8 = 0x03,
     /// Stores 16-bit color values, but does not save an alpha channel.
     Rgb565 = 0x04,
@@ -92,6 +224,36 @@ pub enum DataFormat {
     ///
     /// This format is by far the one with the largest filesize, although the most accurate in terms of
     /// color.
+    ///
+    /// The pixels are stored in blocks of 4x4 pixels, each block taking up 64 bytes. The first 32
+    /// bytes hold the alpha and red channels of each pixel as A,R pairs, and the last 32 bytes hold
+    /// the green and blue channels as G,B pairs, both going through the pixels of the block row by
+    /// row. This is the same layout as the RGBA8 format of TPL textures.
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(4, 4, |x, y| {
+    ///     let i = (y * 4 + x) as u8;
+    ///     Rgba([0x10 + i, 0x20 + i, 0x30 + i, 0x40 + i])
+    /// });
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Argb8888)?.without_outer_header();
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    ///
+    /// let data = &encoded[0x10..];
+    /// assert_eq!(&data[..4], &[0x40, 0x10, 0x41, 0x11]); // A,R of the first two pixels
+    /// assert_eq!(&data[30..32], &[0x4F, 0x1F]); // A,R of the last pixel
+    /// assert_eq!(&data[32..36], &[0x20, 0x30, 0x21, 0x31]); // G,B of the first two pixels
+    /// assert_eq!(&data[62..64], &[0x2F, 0x3F]); // G,B of the last pixel
+    ///
+    /// assert_eq!(gvrtex::decode_bytes(&encoded)?, image);
+    /// # Ok(())
+    /// # }
+    /// ```
     Argb8888 = 0x06,
     /// Stores 4-bit indices into a quantized color palette.
     ///
@@ -108,15 +270,313 @@ pub enum DataFormat {
     /// Works well in environments where the texture cannot be easily viewed (like a 3D model in
     /// motion), but not that well in other cases (like on a 2D menu), as the compression artifacts
     /// can be quite visible at times.
+    ///
+    /// The image is compressed in blocks of 4x4 pixels, so the dimensions of the image have to be
+    /// a multiple of 4. The blocks themselves are grouped into tiles of 8x8 pixels, meaning
+    /// images that aren't a multiple of 8 get padded to the next full tile in the encoded file.
+    /// The padding repeats the pixels at the right and bottom edges, so it's the same as encoding
+    /// the image padded that way, and cropping the padding back out after decoding.
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{imageops, DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// for (width, height) in [(12, 12), (20, 12), (8, 20)] {
+    ///     let image = RgbaImage::from_fn(width, height, |x, y| {
+    ///         Rgba([x as u8 * 12, y as u8 * 12, (x ^ y) as u8 * 8, 0xFF])
+    ///     });
+    ///     let padded = RgbaImage::from_fn(width.next_multiple_of(8), height.next_multiple_of(8), |x, y| {
+    ///         *image.get_pixel(x.min(width - 1), y.min(height - 1))
+    ///     });
+    ///
+    ///     let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?;
+    ///     let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///     let encoded_padded = encoder.encode_image(DynamicImage::ImageRgba8(padded))?;
+    ///
+    ///     let decoded = gvrtex::decode_bytes(&encoded)?;
+    ///     let decoded_padded = gvrtex::decode_bytes(&encoded_padded)?;
+    ///     let cropped = imageops::crop_imm(&decoded_padded, 0, 0, width, height).to_image();
+    ///     assert_eq!(decoded, cropped);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Dimensions that aren't a multiple of 4 are rejected:
+    ///
+    /// ```
+    /// use gvrtex::error::TextureEncodeError;
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?;
+    /// let mut encode = |width, height| {
+    ///     encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(width, height)))
+    /// };
+    ///
+    /// for size in [4, 8, 12] {
+    ///     let decoded = gvrtex::decode_bytes(&encode(size, size)?)?;
+    ///     assert_eq!(decoded.dimensions(), (size, size));
+    /// }
+    /// assert!(matches!(
+    ///     encode(6, 6),
+    ///     Err(TextureEncodeError::InvalidDimensions(6, 6, 4))
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
     Dxt1 = 0x0E,
 }
 
+impl DataFormat {
+    /// Every data format, in the order of their format codes.
+    pub const ALL: [DataFormat; 10] = [
+        Self::Intensity4,
+        Self::Intensity8,
+        Self::IntensityA4,
+        Self::IntensityA8,
+        Self::Rgb565,
+        Self::Rgb5a3,
+        Self::Argb8888,
+        Self::Index4,
+        Self::Index8,
+        Self::Dxt1,
+    ];
+
+    /// Checks if this format can store an alpha channel.
+    ///
+    /// [`DataFormat::Dxt1`] only stores 1-bit alpha, pixels are either fully opaque or fully
+    /// transparent. For [`DataFormat::Index4`] and [`DataFormat::Index8`], the alpha channel is
+    /// stored in the color palette, so it depends on the [`PixelFormat`] of the palette (see
+    /// [`PixelFormat::has_alpha()`]). This method returns `true` for them.
+    pub fn has_alpha(&self) -> bool {
+        match self {
+            Self::Intensity4 | Self::Intensity8 | Self::Rgb565 => false,
+            Self::IntensityA4
+            | Self::IntensityA8
+            | Self::Rgb5a3
+            | Self::Argb8888
+            | Self::Index4
+            | Self::Index8
+            | Self::Dxt1 => true,
+        }
+    }
+
+    /// Checks if this format can be encoded with mipmaps.
+    ///
+    /// Only [`DataFormat::Dxt1`], [`DataFormat::Rgb565`] and [`DataFormat::Rgb5a3`] support
+    /// mipmaps.
+    pub fn supports_mipmaps(&self) -> bool {
+        matches!(self, Self::Dxt1 | Self::Rgb565 | Self::Rgb5a3)
+    }
+
+    /// Checks if this format stores indices into a color palette, which is the case for
+    /// [`DataFormat::Index4`] and [`DataFormat::Index8`].
+    pub fn requires_palette(&self) -> bool {
+        matches!(self, Self::Index4 | Self::Index8)
+    }
+
+    /// Returns the width and height of the blocks the pixels of this format are grouped into.
+    ///
+    /// Every block takes up 32 bytes (64 bytes for [`DataFormat::Argb8888`]), and images are
+    /// padded to a full block on the right and bottom edges. For [`DataFormat::Dxt1`], this is
+    /// the size of the 8x8 tiles holding four compressed 4x4 blocks.
+    pub fn block_size(&self) -> (u32, u32) {
+        match self {
+            Self::Intensity4 | Self::Index4 | Self::Dxt1 => (8, 8),
+            Self::Intensity8 | Self::IntensityA4 | Self::Index8 => (8, 4),
+            Self::IntensityA8 | Self::Rgb565 | Self::Rgb5a3 | Self::Argb8888 => (4, 4),
+        }
+    }
+
+    /// Returns the amount of bits each pixel takes up in this format, not counting the color
+    /// palette.
+    pub fn bits_per_pixel(&self) -> u32 {
+        match self {
+            Self::Intensity4 | Self::Index4 | Self::Dxt1 => 4,
+            Self::Intensity8 | Self::IntensityA4 | Self::Index8 => 8,
+            Self::IntensityA8 | Self::Rgb565 | Self::Rgb5a3 => 16,
+            Self::Argb8888 => 32,
+        }
+    }
+
+    /// Returns the size of the texture data for a texture of this format with the given
+    /// dimensions and amount of `mip_levels`, not counting the headers.
+    ///
+    /// This is the same size as the data encoded by [`crate::TextureEncoder`]. Each image is
+    /// padded to a full block (see [`Self::block_size()`]), and each mipmap halves the dimensions
+    /// of the previous image, down to 1x1. For [`DataFormat::Index4`] and [`DataFormat::Index8`],
+    /// the size of a color palette stored in the texture is included.
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    ///
+    /// let sizes = [
+    ///     (DataFormat::Intensity4, 512),
+    ///     (DataFormat::Intensity8, 1024),
+    ///     (DataFormat::IntensityA4, 1024),
+    ///     (DataFormat::IntensityA8, 2048),
+    ///     (DataFormat::Rgb565, 2048),
+    ///     (DataFormat::Rgb5a3, 2048),
+    ///     (DataFormat::Argb8888, 4096),
+    ///     (DataFormat::Index4, 512 + 16 * 2),
+    ///     (DataFormat::Index8, 1024 + 256 * 2),
+    ///     (DataFormat::Dxt1, 512),
+    /// ];
+    /// for (format, size) in sizes {
+    ///     assert_eq!(format.data_size(32, 32, 0), size);
+    /// }
+    ///
+    /// // 32x32, 16x16, 8x8, and the 4x4, 2x2 and 1x1 mipmaps padded to a full 8x8 tile
+    /// assert_eq!(DataFormat::Dxt1.data_size(32, 32, 5), 512 + 128 + 32 * 4);
+    /// ```
+    pub fn data_size(&self, width: u32, height: u32, mip_levels: u32) -> usize {
+        let palette_len = if self.requires_palette() {
+            palette_size(*self) as usize * size_of::<u16>()
+        } else {
+            0
+        };
+
+        let image_len: usize = (0..=mip_levels)
+            .map(|level| self.image_size((width >> level).max(1), (height >> level).max(1)))
+            .sum();
+        palette_len + image_len
+    }
+
+    /// Returns the encoder of this format, which encodes images into raw GX image data, with no
+    /// GVR header. Returns [`None`] for the palettized formats, see [`Self::palette_encoder()`].
+    ///
+    /// For [`DataFormat::Dxt1`], the encoder uses [`TextureEncoder::DEFAULT_ALPHA_THRESHOLD`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use image::{Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8 * 32, y as u8 * 32, 0, 0xFF]));
+    ///
+    /// let encoder = DataFormat::Argb8888.encoder().unwrap();
+    /// encoder.validate_input(&image)?;
+    /// let data = encoder.encode(&image);
+    /// assert_eq!(data.len(), DataFormat::Argb8888.data_size(8, 8, 0));
+    ///
+    /// let decoder = DataFormat::Argb8888.decoder().unwrap();
+    /// assert_eq!(decoder.decode(&mut data.as_slice(), 8, 8)?, image);
+    ///
+    /// assert!(DataFormat::Index8.encoder().is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encoder(&self) -> Option<Box<dyn GvrEncoder + Send + Sync>> {
+        (!self.requires_palette()).then(|| {
+            let alpha_threshold = TextureEncoder::DEFAULT_ALPHA_THRESHOLD;
+            create_new_encoder(*self, alpha_threshold, 0, DxtLayout::default())
+        })
+    }
+
+    /// Returns the decoder of this format, which decodes raw GX image data, with no GVR header.
+    /// Returns [`None`] for the palettized formats, see [`Self::palette_decoder()`].
+    pub fn decoder(&self) -> Option<Box<dyn GvrDecoder + Send + Sync>> {
+        (!self.requires_palette()).then(|| create_new_decoder(*self, DxtLayout::default()))
+    }
+
+    /// Returns the encoder of this palettized format, which encodes images into a color palette
+    /// followed by the indices into said palette, with no GVR header. Returns [`None`] for the
+    /// formats that don't use a color palette.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use image::{Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, _| match x % 2 {
+    ///     0 => Rgba([0xFF, 0x00, 0x00, 0xFF]),
+    ///     _ => Rgba([0x00, 0x00, 0xFF, 0xFF]),
+    /// });
+    ///
+    /// let encoder = DataFormat::Index4.palette_encoder().unwrap();
+    /// encoder.validate_input(&image)?;
+    /// let data = encoder.encode(&image, PixelFormat::RGB565)?;
+    /// assert_eq!(data.len(), DataFormat::Index4.data_size(8, 8, 0));
+    ///
+    /// let decoder = DataFormat::Index4.palette_decoder().unwrap();
+    /// let (palette, indices) = decoder.decode_indexed(&mut data.as_slice(), 8, 8, PixelFormat::RGB565)?;
+    /// let decoded = RgbaImage::from_fn(8, 8, |x, y| palette[indices[(y * 8 + x) as usize] as usize]);
+    /// assert_eq!(decoded, image);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn palette_encoder(&self) -> Option<Box<dyn GvrEncoderPalette + Send + Sync>> {
+        self.requires_palette()
+            .then(|| create_new_encoder_with_palette(*self))
+    }
+
+    /// Returns the decoder of this palettized format, which decodes a color palette followed by
+    /// the indices into said palette, with no GVR header. Returns [`None`] for the formats that
+    /// don't use a color palette.
+    pub fn palette_decoder(&self) -> Option<Box<dyn GvrDecoderPalette + Send + Sync>> {
+        self.requires_palette()
+            .then(|| create_new_decoder_with_palette(*self))
+    }
+
+    /// Returns the size of the pixel data of a single image of this format with the given
+    /// dimensions, padded to a full block.
+    pub(crate) fn image_size(&self, width: u32, height: u32) -> usize {
+        let (block_width, block_height) = self.block_size();
+        let block_len = (block_width * block_height * self.bits_per_pixel() / 8) as usize;
+
+        let blocks_x = width.div_ceil(block_width) as usize;
+        let blocks_y = height.div_ceil(block_height) as usize;
+        blocks_x * blocks_y * block_len
+    }
+}
+
 impl From<DataFormat> for u8 {
     fn from(value: DataFormat) -> Self {
         value as u8
     }
 }
 
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(format_name(self, &DATA_FORMAT_NAMES))
+    }
+}
+
+/// Parses a data format from its name, ignoring case.
+///
+/// Each format is displayed as its lowercase name ("intensity4", "rgb5a3", "dxt1", etc.), which
+/// can be parsed back. The short names used by other tools are accepted as well: "i4", "i8",
+/// "ia4", "ia8", "rgba8", "ci4", "ci8" and "bc1".
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+///
+/// assert_eq!("DXT1".parse(), Ok(DataFormat::Dxt1));
+/// assert_eq!("bc1".parse(), Ok(DataFormat::Dxt1));
+/// assert_eq!(DataFormat::IntensityA8.to_string(), "intensitya8");
+/// assert!("dxt5".parse::<DataFormat>().is_err());
+///
+/// for format in DataFormat::ALL {
+///     assert_eq!(format.to_string().parse(), Ok(format));
+/// }
+/// ```
+impl FromStr for DataFormat {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_format(s, &DATA_FORMAT_NAMES, "data format")
+    }
+}
+
 impl TryFrom<u8> for DataFormat {
     type Error = TextureDecodeError;
 
@@ -147,17 +607,88 @@ impl From<PixelFormat> for DataFormat {
     }
 }
 
+/// The order the compressed 4x4 blocks of [`DataFormat::Dxt1`] textures are stored in, set with
+/// [`TextureEncoder::with_dxt_layout()`] and
+/// [`TextureDecoder::with_dxt_layout()`](crate::TextureDecoder::with_dxt_layout()).
+///
+/// Both layouts pad the image out to full 8x8 tiles, so textures take up the same amount of
+/// space in either layout, and only the order of the blocks differs.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DxtLayout {
+    /// The blocks are grouped into 8x8 tiles of 2x2 blocks. The tiles are stored left to right,
+    /// top to bottom, and the blocks within a tile row by row. This is the layout used by the
+    /// GameCube, and by most Wii titles.
+    #[default]
+    GameCube,
+    /// The blocks are stored row by row across the whole width of the image, without grouping
+    /// them into tiles. Some Wii titles store their textures in this layout.
+    Wii,
+}
+
+impl DxtLayout {
+    /// Returns the position of the block at (`block_x`, `block_y`), counted in blocks, in an image
+    /// that's `tiles_x` 8x8 tiles wide.
+    pub(crate) fn block_index(self, block_x: usize, block_y: usize, tiles_x: usize) -> usize {
+        match self {
+            Self::GameCube => {
+                let tile = (block_y / 2) * tiles_x + block_x / 2;
+                tile * 4 + (block_y % 2) * 2 + block_x % 2
+            }
+            Self::Wii => block_y * tiles_x * 2 + block_x,
+        }
+    }
+
+    /// Returns the coordinates, counted in blocks, of the block at position `index` in an image
+    /// that's `tiles_x` 8x8 tiles wide. This is the inverse of [`Self::block_index()`].
+    pub(crate) fn block_coords(self, index: usize, tiles_x: usize) -> (usize, usize) {
+        match self {
+            Self::GameCube => {
+                let (tile, sub_block) = (index / 4, index % 4);
+                let block_x = (tile % tiles_x) * 2 + sub_block % 2;
+                let block_y = (tile / tiles_x) * 2 + sub_block / 2;
+                (block_x, block_y)
+            }
+            Self::Wii => (index % (tiles_x * 2), index / (tiles_x * 2)),
+        }
+    }
+}
+
 bitflags! {
+    /// The flags stored in the header of a GVR texture, specifying what kind of data the texture
+    /// contains besides the main image.
+    ///
+    /// Not every combination of flags is valid, see [`DataFlags::is_valid_for()`].
     #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-    pub(crate) struct DataFlags: u8 {
+    pub struct DataFlags: u8 {
+        /// No flags are set.
         const None = 0;
+        /// The texture data is followed by mipmaps of the texture.
         const Mipmaps = 0x1;
+        /// The texture uses a color palette that's stored in a separate file (usually a GVP file).
         const ExternalPalette = 0x2;
+        /// The texture data starts with a color palette.
         const InternalPalette = 0x8;
+        /// Either of the palette flags.
         const Palette = Self::ExternalPalette.bits() | Self::InternalPalette.bits();
     }
 }
 
+impl DataFlags {
+    /// Checks if these flags form a valid combination for a texture with the given `data_format`.
+    ///
+    /// The flags are invalid if both [`DataFlags::ExternalPalette`] and
+    /// [`DataFlags::InternalPalette`] are set, or if a palette flag is set for a data format that
+    /// isn't [`DataFormat::Index4`] or [`DataFormat::Index8`]. The opposite also applies, these two
+    /// data formats always need one of the palette flags to be set.
+    pub fn is_valid_for(&self, data_format: DataFormat) -> bool {
+        if self.contains(DataFlags::Palette) {
+            return false;
+        }
+
+        data_format.requires_palette() == self.intersects(DataFlags::Palette)
+    }
+}
+
 impl From<DataFlags> for u8 {
     fn from(val: DataFlags) -> Self {
         val.bits()
@@ -0,0 +1,568 @@
+use crate::error::TextureDecodeError;
+use crate::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+use crate::layout::*;
+use crate::pixel_codecs::palette_size;
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{ErrorKind, Read, Write};
+
+/// The headers at the start of a GVR texture, which describe the texture data following them.
+///
+/// A GVR texture starts with an optional GCIX/GBIX header holding the global index, followed by
+/// the GVRT header holding the formats, flags and dimensions of the texture. This struct holds
+/// both of them, so the headers of a texture can be inspected or rewritten without touching its
+/// pixel data.
+///
+/// The length of the GCIX/GBIX header varies between games. It usually holds 4 padding bytes
+/// after the global index, but some games leave them out or add more, which is kept in
+/// [`Self::index_padding`] so the headers are written back the way they were read.
+///
+/// # Examples
+///
+/// Turning off the mipmap flag of a texture, leaving the mipmaps themselves in the file:
+///
+/// ```no_run
+/// use gvrtex::formats::DataFlags;
+/// use gvrtex::GvrHeader;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let gvr_path: &str = "";
+/// let mut gvr_bytes = std::fs::read(gvr_path)?;
+///
+/// let mut header = GvrHeader::read(&mut gvr_bytes.as_slice())?;
+/// header.flags.remove(DataFlags::Mipmaps);
+/// header.write(&mut &mut gvr_bytes[..header.size()])?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Headers can be used as keys of a [`HashSet`](std::collections::HashSet) or
+/// [`HashMap`](std::collections::HashMap), for example to find the distinct kinds of textures
+/// in a set of files:
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+/// use gvrtex::{GvrHeader, TextureEncoder};
+/// use image::{DynamicImage, RgbaImage};
+/// use std::collections::HashSet;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut headers = HashSet::new();
+/// for (data_format, width) in [
+///     (DataFormat::Rgb565, 16),
+///     (DataFormat::Dxt1, 16),
+///     (DataFormat::Rgb565, 16),
+///     (DataFormat::Rgb565, 32),
+///     (DataFormat::Dxt1, 16),
+/// ] {
+///     let mut encoder = TextureEncoder::new_gcix(data_format)?;
+///     let image = DynamicImage::ImageRgba8(RgbaImage::new(width, 8));
+///     let encoded = encoder.encode_image(image)?;
+///     headers.insert(GvrHeader::read(&mut encoded.as_slice())?);
+/// }
+/// assert_eq!(headers.len(), 3);
+///
+/// let formats: HashSet<_> = headers.iter().map(|header| header.data_format).collect();
+/// assert_eq!(formats, HashSet::from([DataFormat::Rgb565, DataFormat::Dxt1]));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GvrHeader {
+    /// Which header the texture starts with, before the GVRT header.
+    pub texture_type: TextureType,
+    /// The global index stored in the GCIX/GBIX header. This is always 0 for textures without
+    /// that header.
+    pub global_index: u32,
+    /// The bytes after the global index in the GCIX/GBIX header, which are 4 bytes of padding in
+    /// most textures. This is always empty for textures without that header.
+    pub index_padding: Vec<u8>,
+    /// The format of the color palette. Only meaningful for textures with one of the palette
+    /// flags.
+    pub pixel_format: PixelFormat,
+    /// The format of the texture data.
+    pub data_format: DataFormat,
+    /// The flags specifying what the texture data holds besides the main image.
+    ///
+    /// Flags this crate doesn't know about (like 0x4, which some games set) are kept as they are,
+    /// so they're written back the way they were read.
+    pub flags: DataFlags,
+    /// The width of the texture.
+    pub width: u16,
+    /// The height of the texture.
+    pub height: u16,
+    /// The length of the texture data following the headers, including the color palette and
+    /// mipmaps.
+    pub data_len: u32,
+    /// The reserved field of the GVRT header, right after the length of the texture data.
+    ///
+    /// See [`crate::TextureEncoder::with_gvrt_reserved()`].
+    pub reserved: u16,
+}
+
+/// Turns a `magic` string into the exact one it stands for when reading headers with relaxed
+/// magic strings, which means ignoring case and accepting a NUL byte in place of the last
+/// character. Without `relaxed`, or if `magic` doesn't stand for any of them, it's returned as is.
+pub(crate) fn normalize_magic(magic: [u8; 4], relaxed: bool) -> [u8; 4] {
+    if !relaxed {
+        return magic;
+    }
+
+    let mut upper = magic;
+    upper.make_ascii_uppercase();
+    if upper[3] == 0 {
+        for known in [b"GVRT", b"GCIX", b"GBIX"] {
+            if upper[..3] == known[..3] {
+                return *known;
+            }
+        }
+    }
+    upper
+}
+
+impl GvrHeader {
+    /// Reads the headers of the GVR texture starting at the current position of the `reader`.
+    ///
+    /// After this, the `reader` points to the start of the texture data.
+    ///
+    /// # Examples
+    ///
+    /// Reading textures with 4, 8 and 12 byte long GBIX headers:
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, TextureType};
+    /// use gvrtex::GvrHeader;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// for padding_len in [0, 4, 8] {
+    ///     let mut bytes = b"GBIX".to_vec();
+    ///     bytes.extend_from_slice(&(4 + padding_len as u32).to_le_bytes());
+    ///     bytes.extend_from_slice(&42u32.to_be_bytes());
+    ///     bytes.resize(bytes.len() + padding_len, 0xAA);
+    ///     bytes.extend_from_slice(b"GVRT\x88\x00\x00\x00\x00\x00\x00\x04\x00\x08\x00\x08");
+    ///
+    ///     let header = GvrHeader::read(&mut bytes.as_slice())?;
+    ///     assert_eq!(header.texture_type, TextureType::Gbix);
+    ///     assert_eq!(header.global_index, 42);
+    ///     assert_eq!(header.index_padding, vec![0xAA; padding_len]);
+    ///     assert_eq!(header.data_format, DataFormat::Rgb565);
+    ///     assert_eq!(header.size(), bytes.len());
+    ///
+    ///     // The header is written back the way it was read
+    ///     let mut written = Vec::new();
+    ///     header.write(&mut written)?;
+    ///     assert_eq!(written, bytes);
+    ///
+    ///     bytes.resize(bytes.len() + 0x80, 0);
+    ///     assert_eq!(gvrtex::decode_bytes(&bytes)?.dimensions(), (8, 8));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Unknown flags are kept, and don't stop the texture from being decoded:
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFlags, DataFormat};
+    /// use gvrtex::GvrHeader;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut bytes = b"GVRT\x28\x00\x00\x00\x00\x00\x04\x04\x00\x04\x00\x04".to_vec();
+    /// bytes.resize(bytes.len() + 0x20, 0xFF);
+    ///
+    /// let header = GvrHeader::read(&mut bytes.as_slice())?;
+    /// assert_eq!(header.flags.bits(), 0x4);
+    /// assert!(!header.flags.intersects(DataFlags::all()));
+    /// assert_eq!(header.data_format, DataFormat::Rgb565);
+    ///
+    /// let mut written = Vec::new();
+    /// header.write(&mut written)?;
+    /// assert_eq!(written, bytes[..header.size()]);
+    ///
+    /// assert_eq!(gvrtex::decode_bytes(&bytes)?.dimensions(), (4, 4));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the headers are missing their magic strings, a [`TextureDecodeError::BadMagic`] is
+    /// returned. Invalid flags or formats are returned as a [`TextureDecodeError::UnknownFlags`],
+    /// [`TextureDecodeError::UnknownPixelFormat`] or [`TextureDecodeError::UnknownDataFormat`].
+    /// The offsets in these errors are counted from where the `reader` started.
+    ///
+    /// If the GCIX/GBIX header is too short to hold the global index, or the `reader` ends before
+    /// the end of the headers, a [`TextureDecodeError::InvalidFile`] is returned. Any other IO
+    /// error is returned as a [`TextureDecodeError::Io`].
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, TextureDecodeError> {
+        Ok(Self::read_with_custom(reader, |_| false, false)?.0)
+    }
+
+    /// Reads the headers like [`Self::read()`], also accepting the data formats for which
+    /// `is_custom` returns true, which have a custom decoder. The value of the data format is
+    /// returned alongside the headers.
+    ///
+    /// For custom data formats, the data format of the returned headers is left at its default,
+    /// and the flags can't include a color palette.
+    ///
+    /// With `relaxed_magic`, the magic strings are matched as described in
+    /// [`crate::TextureDecoder::with_relaxed_magic()`].
+    pub(crate) fn read_with_custom<R: Read>(
+        reader: &mut R,
+        is_custom: impl Fn(u8) -> bool,
+        relaxed_magic: bool,
+    ) -> Result<(Self, u8), TextureDecodeError> {
+        Self::read_internal(reader, is_custom, relaxed_magic).map_err(|err| match err {
+            TextureDecodeError::Io(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                TextureDecodeError::InvalidFile
+            }
+            err => err,
+        })
+    }
+
+    fn read_internal<R: Read>(
+        reader: &mut R,
+        is_custom: impl Fn(u8) -> bool,
+        relaxed_magic: bool,
+    ) -> Result<(Self, u8), TextureDecodeError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        // The GCIX/GBIX header is optional
+        let mut gvrt_offset = 0;
+        let (texture_type, global_index, index_padding) =
+            match &normalize_magic(magic, relaxed_magic) {
+                b"GVRT" => (TextureType::None, 0, Vec::new()),
+                known @ (b"GCIX" | b"GBIX") => {
+                    let texture_type = if known == b"GCIX" {
+                        TextureType::Gcix
+                    } else {
+                        TextureType::Gbix
+                    };
+
+                    // The length of the rest of the header varies between games
+                    let Some(padding_len) = reader.read_u32::<LittleEndian>()?.checked_sub(4)
+                    else {
+                        return Err(TextureDecodeError::InvalidFile);
+                    };
+                    let global_index = reader.read_u32::<BigEndian>()?;
+
+                    let mut index_padding = Vec::new();
+                    reader
+                        .by_ref()
+                        .take(padding_len.into())
+                        .read_to_end(&mut index_padding)?;
+                    if index_padding.len() != padding_len as usize {
+                        return Err(TextureDecodeError::InvalidFile);
+                    }
+
+                    gvrt_offset = (GBIX_PADDING_OFFSET + padding_len as usize) as u64;
+                    reader.read_exact(&mut magic)?;
+                    if &normalize_magic(magic, relaxed_magic) != b"GVRT" {
+                        return Err(TextureDecodeError::BadMagic {
+                            offset: gvrt_offset,
+                            found: magic,
+                        });
+                    }
+                    (texture_type, global_index, index_padding)
+                }
+                _ => {
+                    return Err(TextureDecodeError::BadMagic {
+                        offset: 0,
+                        found: magic,
+                    })
+                }
+            };
+
+        // The stated length also counts the rest of the GVRT header
+        let Some(data_len) = reader.read_u32::<LittleEndian>()?.checked_sub(8) else {
+            return Err(TextureDecodeError::InvalidFile);
+        };
+        let reserved = reader.read_u16::<LittleEndian>()?;
+
+        let flags = reader.read_u8()?;
+        let invalid_flags = TextureDecodeError::UnknownFlags {
+            offset: gvrt_offset + GVRT_FORMATS_OFFSET as u64,
+            value: flags & 0xF,
+        };
+        // Some games set flags that aren't known, which don't seem to change the texture data
+        let data_flags = DataFlags::from_bits_retain(flags & 0xF);
+        let unknown_flags = data_flags.bits() & !DataFlags::all().bits();
+        if unknown_flags != 0 {
+            log::warn!(
+                "Unknown flags {unknown_flags:#X} at offset {:#X}, ignoring them.",
+                gvrt_offset + GVRT_FORMATS_OFFSET as u64
+            );
+        }
+        let Ok(pixel_format) = PixelFormat::try_from((flags >> 4) & 0xF) else {
+            return Err(TextureDecodeError::UnknownPixelFormat {
+                offset: gvrt_offset + GVRT_FORMATS_OFFSET as u64,
+                value: (flags >> 4) & 0xF,
+            });
+        };
+
+        let value = reader.read_u8()?;
+        let data_format = if is_custom(value) {
+            if data_flags.intersects(DataFlags::Palette) {
+                return Err(invalid_flags);
+            }
+            DataFormat::default()
+        } else {
+            let Ok(data_format) = DataFormat::try_from(value) else {
+                return Err(TextureDecodeError::UnknownDataFormat {
+                    offset: gvrt_offset + GVRT_DATA_FORMAT_OFFSET as u64,
+                    value,
+                });
+            };
+            if !data_flags.is_valid_for(data_format) {
+                return Err(invalid_flags);
+            }
+            data_format
+        };
+
+        let header = Self {
+            texture_type,
+            global_index,
+            index_padding,
+            pixel_format,
+            data_format,
+            flags: data_flags,
+            width: reader.read_u16::<BigEndian>()?,
+            height: reader.read_u16::<BigEndian>()?,
+            data_len,
+            reserved,
+        };
+        Ok((header, value))
+    }
+
+    /// Writes the headers into the `writer`, taking up exactly [`Self::size()`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+    /// use gvrtex::GvrHeader;
+    /// use std::io::ErrorKind;
+    ///
+    /// let mut header = GvrHeader {
+    ///     texture_type: TextureType::Gcix,
+    ///     global_index: 0,
+    ///     index_padding: vec![0; 4],
+    ///     pixel_format: PixelFormat::IntensityA8,
+    ///     data_format: DataFormat::Rgb565,
+    ///     flags: DataFlags::None,
+    ///     width: 8,
+    ///     height: 8,
+    ///     data_len: 8 * 8 * 2,
+    ///     reserved: 0,
+    /// };
+    /// let mut written = Vec::new();
+    /// header.write(&mut written).unwrap();
+    /// assert_eq!(written.len(), header.size());
+    ///
+    /// // The stored length counts the 8 bytes of header after it, which have to fit in a u32
+    /// header.data_len = u32::MAX;
+    /// let error = header.write(&mut Vec::new()).unwrap_err();
+    /// assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If writing to the `writer` fails, the IO error is returned. If [`Self::data_len`] is too
+    /// large to be stored, an [`ErrorKind::InvalidInput`] error is returned.
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.write_with_format(writer, self.data_format.into())
+    }
+
+    /// Writes the headers like [`Self::write()`], with `format_value` as the value of the data
+    /// format instead, for textures encoded with a custom encoder.
+    pub(crate) fn write_with_format<W: Write>(
+        &self,
+        writer: &mut W,
+        format_value: u8,
+    ) -> std::io::Result<()> {
+        // The stored length counts the rest of the GVRT header as well
+        let Some(data_len) = self.data_len.checked_add(8) else {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "the length of the texture data is too large to be stored",
+            ));
+        };
+
+        match self.texture_type {
+            TextureType::Gcix => writer.write_all(b"GCIX")?,
+            TextureType::Gbix => writer.write_all(b"GBIX")?,
+            TextureType::None => {}
+        }
+        if self.texture_type != TextureType::None {
+            let len = self.index_padding.len() + size_of::<u32>();
+            writer.write_u32::<LittleEndian>(len.try_into().unwrap())?;
+            writer.write_u32::<BigEndian>(self.global_index)?;
+            writer.write_all(&self.index_padding)?;
+        }
+
+        writer.write_all(b"GVRT")?;
+        writer.write_u32::<LittleEndian>(data_len)?;
+        writer.write_u16::<LittleEndian>(self.reserved)?;
+
+        let pixel_format = u8::from(self.pixel_format) << 4;
+        let data_flags = u8::from(self.flags);
+        writer.write_u8(pixel_format | data_flags)?;
+        writer.write_u8(format_value)?;
+        writer.write_u16::<BigEndian>(self.width)?;
+        writer.write_u16::<BigEndian>(self.height)?;
+
+        Ok(())
+    }
+
+    /// Returns the amount of colors stored in the color palette at the start of the texture data,
+    /// or [`None`] if the texture doesn't have the [`DataFlags::InternalPalette`] flag.
+    ///
+    /// This is usually the full amount of colors the data format allows for (16 for
+    /// [`DataFormat::Index4`], 256 for [`DataFormat::Index8`]), but some textures leave out the
+    /// unused colors at the end of the palette. The headers don't hold the amount of colors, so
+    /// it's worked out from [`Self::data_len`] instead. When decoding these textures, the rest of
+    /// the palette is filled with transparent black.
+    ///
+    /// # Examples
+    ///
+    /// Shortening the palette of a texture down to the 4 colors it uses:
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use gvrtex::{GvrHeader, TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let colors = [[0xFF, 0, 0, 0xFF], [0, 0xFF, 0, 0xFF], [0, 0, 0xFF, 0xFF], [0, 0, 0, 0xFF]];
+    /// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba(colors[((x + y) % 4) as usize]));
+    /// let mut encoder = TextureEncoder::new_gcix_palettized(PixelFormat::RGB565, DataFormat::Index8)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    ///
+    /// let mut header = GvrHeader::read(&mut encoded.as_slice())?;
+    /// assert_eq!(header.palette_colors(), Some(256));
+    ///
+    /// let palette_start = header.size();
+    /// let mut short = encoded[..palette_start + 4 * 2].to_vec();
+    /// short.extend_from_slice(&encoded[palette_start + 256 * 2..]);
+    /// header.data_len -= 252 * 2;
+    /// header.write(&mut &mut short[..palette_start])?;
+    /// assert_eq!(GvrHeader::read(&mut short.as_slice())?.palette_colors(), Some(4));
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(short);
+    /// decoder.decode()?;
+    /// let palette = decoder.palette().unwrap();
+    /// assert_eq!(palette.len(), 256);
+    /// assert_eq!(palette[4], Rgba([0, 0, 0, 0]));
+    /// assert_eq!(decoder.into_decoded()?, image);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// A palette without any colors is an error, and so is one that doesn't end on a whole color,
+    /// as that's texture data that has been cut short:
+    ///
+    /// ```
+    /// use gvrtex::error::TextureDecodeError;
+    /// use gvrtex::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+    /// use gvrtex::GvrHeader;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut header = GvrHeader {
+    ///     texture_type: TextureType::Gcix,
+    ///     global_index: 0,
+    ///     index_padding: vec![0; 4],
+    ///     pixel_format: PixelFormat::RGB565,
+    ///     data_format: DataFormat::Index4,
+    ///     flags: DataFlags::InternalPalette,
+    ///     width: 8,
+    ///     height: 8,
+    ///     data_len: 8 * 8 / 2,
+    ///     reserved: 0,
+    /// };
+    /// assert_eq!(header.palette_colors(), Some(0));
+    ///
+    /// let mut texture = Vec::new();
+    /// header.write(&mut texture)?;
+    /// texture.resize(header.size() + 8 * 8 / 2, 0);
+    /// assert!(matches!(
+    ///     gvrtex::decode_bytes(&texture),
+    ///     Err(TextureDecodeError::SizeMismatch { .. })
+    /// ));
+    ///
+    /// for palette_len in [1, 3, 31] {
+    ///     header.data_len = 8 * 8 / 2 + palette_len;
+    ///     let mut texture = Vec::new();
+    ///     header.write(&mut texture)?;
+    ///     texture.resize(header.size() + header.data_len as usize, 0);
+    ///     assert!(matches!(
+    ///         gvrtex::decode_bytes(&texture),
+    ///         Err(TextureDecodeError::SizeMismatch { expected: 0x40, .. })
+    ///     ));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn palette_colors(&self) -> Option<usize> {
+        if !self.flags.intersects(DataFlags::InternalPalette) {
+            return None;
+        }
+
+        let image_size = self
+            .data_format
+            .image_size(self.width.into(), self.height.into());
+        let palette_len = (self.data_len as usize).saturating_sub(image_size);
+        Some((palette_len / size_of::<u16>()).min(palette_size(self.data_format) as usize))
+    }
+
+    /// Checks that the texture data has the stated length, given that there's `available` bytes
+    /// left after the headers, and that the stated length is enough for the image it describes.
+    pub(crate) fn check_data_len(&self, available: u64) -> Result<(), TextureDecodeError> {
+        self.check_available(available)?;
+
+        let image_size = self
+            .data_format
+            .image_size(self.width.into(), self.height.into());
+        let mut expected = image_size;
+        let mut short_palette = false;
+        if self.flags.intersects(DataFlags::InternalPalette) {
+            let palette_len = palette_size(self.data_format) as usize * size_of::<u16>();
+            expected += palette_len;
+
+            // Textures with a shorter color palette still need at least one color in it, and
+            // only whole colors, otherwise the texture data has been cut short instead
+            short_palette = (self.data_len as usize)
+                .checked_sub(image_size)
+                .is_some_and(|len| len > 0 && len.is_multiple_of(2) && len <= palette_len);
+        }
+        if (self.data_len as usize) < expected && !short_palette {
+            return Err(TextureDecodeError::SizeMismatch {
+                expected,
+                actual: self.data_len as usize,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the texture data has the stated length, given that there's `available` bytes
+    /// left after the headers.
+    pub(crate) fn check_available(&self, available: u64) -> Result<(), TextureDecodeError> {
+        if available < u64::from(self.data_len) {
+            return Err(TextureDecodeError::SizeMismatch {
+                expected: self.data_len as usize,
+                actual: available as usize,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the size of the headers in bytes, which is where the texture data starts.
+    pub fn size(&self) -> usize {
+        match self.texture_type {
+            TextureType::None => GVRT_HEADER_SIZE,
+            TextureType::Gcix | TextureType::Gbix => {
+                GBIX_PADDING_OFFSET + self.index_padding.len() + GVRT_HEADER_SIZE
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::error::TextureDecodeError;
+use crate::formats::DataFormat;
+use crate::header::GvrHeader;
+use crate::TextureDecoder;
+use image::{ColorType, ImageDecoder, ImageResult};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Decodes GVR textures through the [`ImageDecoder`] trait of the [`image`] crate.
+///
+/// This lets GVR textures be opened by any code that works with the traits of the [`image`]
+/// crate, for example with [`image::DynamicImage::from_decoder()`]. The headers are read when the
+/// decoder is created, the pixel data is only decoded in [`ImageDecoder::read_image()`].
+///
+/// The intensity formats ([`DataFormat::Intensity4`] and [`DataFormat::Intensity8`]) are decoded
+/// as [`ColorType::L8`], and the intensity formats with alpha ([`DataFormat::IntensityA4`] and
+/// [`DataFormat::IntensityA8`]) as [`ColorType::La8`]. Every other data format is decoded as
+/// [`ColorType::Rgba8`].
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+/// use gvrtex::{GvrImageDecoder, TextureEncoder};
+/// use image::{DynamicImage, Rgba, RgbaImage};
+/// use std::io::Cursor;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8 * 32, y as u8 * 32, 0, 0xFF]));
+/// let mut encoder = TextureEncoder::new_gcix(DataFormat::Argb8888)?;
+/// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+///
+/// let decoder = GvrImageDecoder::new(Cursor::new(encoded))?;
+/// let decoded = DynamicImage::from_decoder(decoder)?;
+/// assert_eq!(decoded.into_rgba8(), image);
+/// # Ok(())
+/// # }
+/// ```
+pub struct GvrImageDecoder<R: Read + Seek> {
+    reader: R,
+    offset: u64,
+    data_format: DataFormat,
+    width: u32,
+    height: u32,
+}
+
+impl<R: Read + Seek> GvrImageDecoder<R> {
+    /// Creates a new decoder for the GVR texture that starts at the current position of the
+    /// `reader`, reading its headers.
+    ///
+    /// # Errors
+    ///
+    /// If the headers of the texture are invalid, an error describing the problem is returned,
+    /// as with [`crate::GvrHeader::read()`]. If something goes wrong while reading them, a [`TextureDecodeError::Io`] is
+    /// returned.
+    pub fn new(mut reader: R) -> Result<Self, TextureDecodeError> {
+        let offset = reader.stream_position()?;
+        let header = GvrHeader::read(&mut reader)?;
+
+        Ok(Self {
+            reader,
+            offset,
+            data_format: header.data_format,
+            width: header.width.into(),
+            height: header.height.into(),
+        })
+    }
+}
+
+impl<R: Read + Seek> ImageDecoder for GvrImageDecoder<R> {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn color_type(&self) -> ColorType {
+        match self.data_format {
+            DataFormat::Intensity4 | DataFormat::Intensity8 => ColorType::L8,
+            DataFormat::IntensityA4 | DataFormat::IntensityA8 => ColorType::La8,
+            _ => ColorType::Rgba8,
+        }
+    }
+
+    fn read_image(mut self, buf: &mut [u8]) -> ImageResult<()> {
+        assert_eq!(u64::try_from(buf.len()), Ok(self.total_bytes()));
+
+        self.reader.seek(SeekFrom::Start(self.offset))?;
+        let decoder = TextureDecoder {
+            offset: self.offset,
+            ..TextureDecoder::new_from_reader(self.reader)
+        };
+
+        let image = decoder.into_dynamic()?;
+        buf.copy_from_slice(image.as_bytes());
+        Ok(())
+    }
+
+    fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
+        (*self).read_image(buf)
+    }
+}
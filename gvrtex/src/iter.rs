@@ -1,5 +1,125 @@
+//! Iterators that walk through an image in the order the pixels are stored in GVR textures.
+//!
+//! The image data of GameCube/Wii textures isn't stored row by row, but split into blocks (also
+//! called tiles) whose size depends on the data format, such as 8x4 pixels for
+//! [`DataFormat::Intensity8`](crate::formats::DataFormat::Intensity8). The blocks are stored left
+//! to right, top to bottom, and the pixels within a block are stored row by row.
+//! [`DataFormat::block_size()`](crate::formats::DataFormat::block_size) gives the block size of
+//! each data format.
+//!
+//! These iterators only produce the order of the pixels, so they can be used for data formats this
+//! crate doesn't support, or for textures stored in other containers.
+//!
+//! # Examples
+//!
+//! Walking through a 16x8 image in 8x4 blocks, and storing one byte per pixel like
+//! [`DataFormat::Intensity8`](crate::formats::DataFormat::Intensity8) does:
+//!
+//! ```
+//! use gvrtex::iter::PixelBlockIterator;
+//! use image::{Luma, GrayImage};
+//!
+//! let image = GrayImage::from_fn(16, 8, |x, y| Luma([(y * 16 + x) as u8]));
+//!
+//! let data: Vec<u8> = PixelBlockIterator::new(16, 8, (8, 4))
+//!     .map(|(x, y)| image.get_pixel(x, y).0[0])
+//!     .collect();
+//!
+//! // The first row of the first block, followed by its second row
+//! assert_eq!(data[..8], [0, 1, 2, 3, 4, 5, 6, 7]);
+//! assert_eq!(data[8..16], [16, 17, 18, 19, 20, 21, 22, 23]);
+//! // The second block starts with the rest of the first row of the image
+//! assert_eq!(data[32..40], [8, 9, 10, 11, 12, 13, 14, 15]);
+//! ```
+//!
+//! Every iterator knows exactly how many items it has left, including for images whose dimensions
+//! aren't multiples of the block size:
+//!
+//! ```
+//! use gvrtex::iter::{
+//!     DecodeDxtBlockIterator, EncodeDxtBlockIterator, PixelBlockIterator, PixelBlockIteratorExt,
+//!     PixelBlockViewIterator,
+//! };
+//! use image::RgbaImage;
+//!
+//! fn check_len(mut iter: impl ExactSizeIterator) {
+//!     while iter.len() > 0 {
+//!         assert_eq!(iter.size_hint(), (iter.len(), Some(iter.len())));
+//!         iter.next().unwrap();
+//!     }
+//!     assert!(iter.next().is_none());
+//! }
+//!
+//! let sizes = [(1, 1), (7, 13), (8, 4), (13, 7), (16, 16), (3, 64), (33, 5), (0, 8), (8, 0)];
+//! for (width, height) in sizes {
+//!     for block_size in [(4, 4), (8, 4), (4, 8), (8, 8), (3, 5)] {
+//!         let iter = PixelBlockIterator::new(width, height, block_size);
+//!         assert_eq!(iter.len(), (width * height) as usize);
+//!         assert_eq!(iter.len(), iter.count());
+//!
+//!         let iter = PixelBlockIteratorExt::new(width, height, block_size);
+//!         assert_eq!(iter.len(), iter.count());
+//!
+//!         check_len(PixelBlockIterator::new(width, height, block_size));
+//!         check_len(PixelBlockIteratorExt::new(width, height, block_size));
+//!
+//!         let image = RgbaImage::new(width, height);
+//!         let iter = PixelBlockViewIterator::new(&image, block_size);
+//!         assert_eq!(iter.len(), iter.count());
+//!         check_len(PixelBlockViewIterator::new(&image, block_size));
+//!     }
+//!
+//!     let image = RgbaImage::new(width, height);
+//!     let iter = EncodeDxtBlockIterator::new(&image);
+//!     assert_eq!(iter.len(), iter.count());
+//!     check_len(EncodeDxtBlockIterator::new(&image));
+//!
+//!     let iter = DecodeDxtBlockIterator::new(width, height);
+//!     assert_eq!(iter.len(), iter.count());
+//!     check_len(DecodeDxtBlockIterator::new(width, height));
+//! }
+//! ```
+
+use crate::formats::DxtLayout;
 use image::RgbaImage;
 
+/// The size of the blocks the block iterators go through an image in.
+///
+/// This is implemented for a (width, height) tuple, for block sizes only known at runtime, and
+/// for [`ConstBlockSize`], for block sizes known at compile time. With the latter, the iterators
+/// are compiled for that block size specifically, which makes them faster.
+pub trait BlockSize: Copy {
+    /// Returns the width and height of a block.
+    fn get(&self) -> (u32, u32);
+}
+
+impl BlockSize for (u32, u32) {
+    #[inline]
+    fn get(&self) -> (u32, u32) {
+        *self
+    }
+}
+
+/// A block size of `BX` by `BY` pixels that's known at compile time, see [`BlockSize`].
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::iter::{ConstBlockSize, PixelBlockIterator};
+///
+/// let coords = PixelBlockIterator::new(16, 8, ConstBlockSize::<8, 4>);
+/// assert!(coords.eq(PixelBlockIterator::new(16, 8, (8, 4))));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConstBlockSize<const BX: u32, const BY: u32>;
+
+impl<const BX: u32, const BY: u32> BlockSize for ConstBlockSize<BX, BY> {
+    #[inline]
+    fn get(&self) -> (u32, u32) {
+        (BX, BY)
+    }
+}
+
 /// Provides the internal implementation for a [`Iterator::next()`] function, catered to the pixel
 /// block iterators.
 ///
@@ -19,28 +139,29 @@ macro_rules! impl_pixelblockiterator {
             }
 
             let next_point = $next_point;
+            let (x_block_size, y_block_size) = $iter.block_size.get();
 
             $iter.x += 1;
-            if $iter.x == $iter.x_block_size {
+            if $iter.x == x_block_size {
                 $iter.x = 0;
                 $iter.y += 1;
             } else {
                 return Some(next_point);
             }
 
-            if $iter.y == $iter.y_block_size {
+            if $iter.y == y_block_size {
                 $iter.y = 0;
 
                 $each_block
 
-                $iter.x_block += $iter.x_block_size;
+                $iter.x_block += x_block_size;
             } else {
                 return Some(next_point);
             }
 
             if $iter.x_block >= $iter.width {
                 $iter.x_block = 0;
-                $iter.y_block += $iter.y_block_size;
+                $iter.y_block += y_block_size;
             }
 
             Some(next_point)
@@ -53,70 +174,138 @@ macro_rules! impl_pixelblockiterator {
 /// on each iteration.
 ///
 /// It works by iterating through a block row by row, before moving on to the next block, which it
-/// also iterates through row by row until the end of the image.
-pub struct PixelBlockIterator {
+/// also iterates through row by row until the end of the image. The blocks are visited left to
+/// right, and a row of blocks is finished before moving down to the next one.
+///
+/// If the block size doesn't divide the width or height of the image, the blocks on the right and
+/// bottom edges go past the image. The coordinates of those blocks that fall outside the image are
+/// skipped, so every coordinate returned is within the image.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::iter::PixelBlockIterator;
+///
+/// let coords: Vec<_> = PixelBlockIterator::new(16, 4, (8, 4)).collect();
+///
+/// assert_eq!(coords[..3], [(0, 0), (1, 0), (2, 0)]);
+/// // The second row of the first block
+/// assert_eq!(coords[8], (0, 1));
+/// // The second block, to the right of the first one
+/// assert_eq!(coords[32], (8, 0));
+/// assert_eq!(coords.len(), 16 * 4);
+/// ```
+///
+/// The parts of the edge blocks outside the image are skipped:
+///
+/// ```
+/// use gvrtex::iter::PixelBlockIterator;
+///
+/// let coords: Vec<_> = PixelBlockIterator::new(10, 6, (8, 4)).collect();
+///
+/// // The second block only has 2 columns within the image
+/// assert_eq!(coords[32..35], [(8, 0), (9, 0), (8, 1)]);
+/// assert_eq!(coords.len(), 10 * 6);
+/// ```
+pub struct PixelBlockIterator<B = (u32, u32)> {
     width: u32,
     height: u32,
-    x_block_size: u32,
-    y_block_size: u32,
+    block_size: B,
 
     x_block: u32,
     y_block: u32,
     x: u32,
     y: u32,
+    /// The amount of coordinates within the image that haven't been returned yet.
+    remaining: usize,
 }
 
-impl PixelBlockIterator {
-    pub fn new(width: u32, height: u32, block_size: (u32, u32)) -> Self {
-        let (x_block_size, y_block_size) = block_size;
-
+impl<B: BlockSize> PixelBlockIterator<B> {
+    /// Creates an iterator over an image of the given `width` and `height`, in blocks of the given
+    /// `block_size`, such as a (width, height) tuple. Neither dimension of the block size may be 0.
+    pub fn new(width: u32, height: u32, block_size: B) -> Self {
         Self {
             width,
             height,
-            x_block_size,
-            y_block_size,
+            block_size,
 
             x_block: 0,
             y_block: 0,
             x: 0,
             y: 0,
+            remaining: width as usize * height as usize,
         }
     }
+
+    /// Returns the x and y coordinate of the next pixel in the blocks, whether it's within the
+    /// image or not.
+    fn next_point(&mut self) -> Option<(u32, u32)> {
+        impl_pixelblockiterator!(self, (self.x_block + self.x, self.y_block + self.y), {})
+    }
+
+    fn contains(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height
+    }
 }
 
-impl Iterator for PixelBlockIterator {
+impl<B: BlockSize> Iterator for PixelBlockIterator<B> {
     type Item = (u32, u32);
 
     /// Iterates over each pixel, returning the x and y coordinate of the next pixel as a tuple.
     fn next(&mut self) -> Option<Self::Item> {
-        impl_pixelblockiterator!(self, (self.x_block + self.x, self.y_block + self.y), {})
+        loop {
+            let (x, y) = self.next_point()?;
+            if self.contains(x, y) {
+                self.remaining -= 1;
+                return Some((x, y));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<B: BlockSize> ExactSizeIterator for PixelBlockIterator<B> {}
+
 /// See [`PixelBlockIterator`] for specifics on how this iterator works.
 ///
 /// This is an extension upon that iterator, that also returns the amount of blocks that have been
 /// processed thus far, and the current column index (x coordinate) in the current block,
-/// which some encodings need.
-pub struct PixelBlockIteratorExt {
-    iterator: PixelBlockIterator,
+/// which some encodings need. Like [`PixelBlockIterator`], it skips the coordinates that fall
+/// outside the image, though the blocks they're in still count towards the amount of blocks.
+///
+/// Each item is a tuple of the block index, the column in the block, and the x and y coordinate.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::iter::PixelBlockIteratorExt;
+///
+/// let points: Vec<_> = PixelBlockIteratorExt::new(16, 4, (8, 4)).collect();
+///
+/// assert_eq!(points[0], (0, 0, 0, 0));
+/// assert_eq!(points[9], (0, 1, 1, 1));
+/// assert_eq!(points[33], (1, 1, 9, 0));
+/// ```
+pub struct PixelBlockIteratorExt<B = (u32, u32)> {
+    iterator: PixelBlockIterator<B>,
     blocks: u32,
 }
 
-impl PixelBlockIteratorExt {
-    pub fn new(width: u32, height: u32, block_size: (u32, u32)) -> Self {
+impl<B: BlockSize> PixelBlockIteratorExt<B> {
+    /// Creates an iterator over an image of the given `width` and `height`, in blocks of the given
+    /// `block_size`, such as a (width, height) tuple. Neither dimension of the block size may be 0.
+    pub fn new(width: u32, height: u32, block_size: B) -> Self {
         Self {
             iterator: PixelBlockIterator::new(width, height, block_size),
             blocks: 0,
         }
     }
-}
 
-impl Iterator for PixelBlockIteratorExt {
-    type Item = (u32, u32, u32, u32);
-
-    /// Iterates over each pixel, returning the x and y coordinate of the next pixel as a tuple.
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Returns the next pixel in the blocks, whether it's within the image or not.
+    fn next_point(&mut self) -> Option<(u32, u32, u32, u32)> {
         let iter = &mut self.iterator;
         impl_pixelblockiterator!(
             iter,
@@ -133,135 +322,367 @@ impl Iterator for PixelBlockIteratorExt {
     }
 }
 
-pub struct EncodeDxtBlockIterator<'a> {
-    image: &'a RgbaImage,
+impl<B: BlockSize> Iterator for PixelBlockIteratorExt<B> {
+    type Item = (u32, u32, u32, u32);
+
+    /// Iterates over each pixel, returning the x and y coordinate of the next pixel as a tuple.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let point = self.next_point()?;
+            if self.iterator.contains(point.2, point.3) {
+                self.iterator.remaining -= 1;
+                return Some(point);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<B: BlockSize> ExactSizeIterator for PixelBlockIteratorExt<B> {}
+
+/// The most pixels a block of [`PixelBlockViewIterator`] can hold, which is the size of the
+/// biggest block any data format uses (8x8).
+const MAX_BLOCK_PIXELS: usize = 64;
+
+/// The pixels of a single block of an image, as returned by [`PixelBlockViewIterator`].
+///
+/// Only the part of the block that's within the image is included, so the blocks on the right and
+/// bottom edges of an image can be smaller than the block size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelBlock {
+    pixels: [[u8; 4]; MAX_BLOCK_PIXELS],
     width: u32,
     height: u32,
+}
+
+impl PixelBlock {
+    /// Returns the RGBA pixels of the block, row by row.
+    pub fn pixels(&self) -> &[[u8; 4]] {
+        &self.pixels[..(self.width * self.height) as usize]
+    }
+
+    /// Returns the width of the block in pixels, which is the amount of pixels in each row of
+    /// [`Self::pixels()`].
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the block in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Iterates through an image in blocks with a given block size, like [`PixelBlockIterator`], but
+/// returns all the pixels of a block at once as a [`PixelBlock`].
+///
+/// The pixels come in the same order as the coordinates of [`PixelBlockIterator`], which includes
+/// skipping the parts of the blocks outside the image. Copying the pixels a row at a time out of
+/// the image is considerably faster than looking each pixel up separately.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::iter::{PixelBlockIterator, PixelBlockViewIterator};
+/// use image::{Rgba, RgbaImage};
+///
+/// let image = RgbaImage::from_fn(12, 6, |x, y| Rgba([x as u8, y as u8, 0, 0xFF]));
+///
+/// let blocks: Vec<_> = PixelBlockViewIterator::new(&image, (8, 4)).collect();
+/// assert_eq!(blocks.len(), 4);
+/// assert_eq!(blocks[0].pixels()[9], [1, 1, 0, 0xFF]);
+///
+/// // The last block only has the 4x2 pixels within the image
+/// assert_eq!((blocks[3].width(), blocks[3].height()), (4, 2));
+/// assert_eq!(blocks[3].pixels()[4], [8, 5, 0, 0xFF]);
+///
+/// let pixels = blocks.iter().flat_map(|block| block.pixels().iter().copied());
+/// let coords = PixelBlockIterator::new(12, 6, (8, 4));
+/// assert!(pixels.eq(coords.map(|(x, y)| image.get_pixel(x, y).0)));
+/// ```
+pub struct PixelBlockViewIterator<'a, B = (u32, u32)> {
+    image: &'a RgbaImage,
+    block_size: B,
 
-    x: u32,
-    y: u32,
     x_block: u32,
     y_block: u32,
+    /// The amount of blocks that haven't been returned yet.
+    remaining: usize,
 }
 
-impl<'a> EncodeDxtBlockIterator<'a> {
-    pub fn new(image: &'a RgbaImage) -> Self {
+impl<'a, B: BlockSize> PixelBlockViewIterator<'a, B> {
+    /// Creates an iterator over the given `image`, in blocks of the given `block_size`, such as a
+    /// (width, height) tuple.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either dimension of `block_size` is 0, or if the block is bigger than 64
+    /// pixels (8x8).
+    pub fn new(image: &'a RgbaImage, block_size: B) -> Self {
+        let (x_block_size, y_block_size) = block_size.get();
+        assert!(
+            x_block_size > 0 && y_block_size > 0,
+            "the block size can't be 0"
+        );
+        assert!(
+            (x_block_size * y_block_size) as usize <= MAX_BLOCK_PIXELS,
+            "blocks can be at most {MAX_BLOCK_PIXELS} pixels"
+        );
+
+        let (width, height) = image.dimensions();
+        let remaining = if width == 0 {
+            0
+        } else {
+            width.div_ceil(x_block_size) as usize * height.div_ceil(y_block_size) as usize
+        };
+
         Self {
             image,
-            width: image.width(),
-            height: image.height(),
+            block_size,
 
-            x: 0,
-            y: 0,
             x_block: 0,
             y_block: 0,
+            remaining,
         }
     }
 }
 
-impl Iterator for EncodeDxtBlockIterator<'_> {
-    type Item = Vec<u8>;
+impl<B: BlockSize> Iterator for PixelBlockViewIterator<'_, B> {
+    type Item = PixelBlock;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.y >= self.height {
+        if self.remaining == 0 {
             return None;
         }
 
-        let mut block = Vec::with_capacity(64);
-
-        for y_sub_block in (0..4).take_while(|i| self.y + self.y_block + i < self.height) {
-            for x_sub_block in (0..4).take_while(|i| self.x + self.x_block + i < self.width) {
-                let x = self.x + self.x_block + x_sub_block;
-                let y = self.y + self.y_block + y_sub_block;
-                let p = self.image.get_pixel(x, y);
+        let (width, height) = self.image.dimensions();
+        let (x_block_size, y_block_size) = self.block_size.get();
+        let block_width = x_block_size.min(width - self.x_block);
+        let block_height = y_block_size.min(height - self.y_block);
+
+        let mut block = PixelBlock {
+            pixels: [[0; 4]; MAX_BLOCK_PIXELS],
+            width: block_width,
+            height: block_height,
+        };
+
+        let raw = self.image.as_raw();
+        let row_len = block_width as usize * 4;
+        for (row, dest) in block
+            .pixels
+            .as_flattened_mut()
+            .chunks_exact_mut(row_len)
+            .take(block_height as usize)
+            .enumerate()
+        {
+            let start =
+                ((self.y_block as usize + row) * width as usize + self.x_block as usize) * 4;
+            dest.copy_from_slice(&raw[start..start + row_len]);
+        }
 
-                block.push(p.0[2]);
-                block.push(p.0[1]);
-                block.push(p.0[0]);
-                block.push(p.0[3]);
-            }
+        self.remaining -= 1;
+        self.x_block += x_block_size;
+        if self.x_block >= width {
+            self.x_block = 0;
+            self.y_block += y_block_size;
         }
 
-        block.resize(64, 0);
+        Some(block)
+    }
 
-        self.x_block += 4;
-        if self.x_block == 8 {
-            self.x_block = 0;
-            self.y_block += 4;
-        } else {
-            return Some(block);
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<B: BlockSize> ExactSizeIterator for PixelBlockViewIterator<'_, B> {}
+
+/// Iterates through an image in the blocks of [`DataFormat::Dxt1`](crate::formats::DataFormat::Dxt1)
+/// textures, returning the pixels of each block ready for compression.
+///
+/// DXT1 textures are made of 8x8 tiles, each consisting of four compressed 4x4 blocks. The tiles
+/// are visited left to right, top to bottom, and the blocks within a tile row by row. Each item
+/// is the 16 pixels of a 4x4 block, row by row, as an array of BGRA bytes. Textures that store
+/// their blocks in a different order can be visited with [`Self::with_layout()`].
+///
+/// Blocks that go past the right or bottom edge of the image are filled out by repeating the
+/// pixels on the edge. Images with a width or height of 0 have no blocks at all.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::iter::EncodeDxtBlockIterator;
+/// use image::{Rgba, RgbaImage};
+///
+/// let image = RgbaImage::from_fn(16, 8, |x, y| Rgba([x as u8, y as u8, 0, 0xFF]));
+/// let blocks: Vec<_> = EncodeDxtBlockIterator::new(&image).collect();
+///
+/// assert_eq!(blocks.len(), 8);
+/// // The first pixel of the second block in the first tile is at (4, 0)
+/// assert_eq!(blocks[1][..4], [0, 0, 4, 0xFF]);
+/// ```
+pub struct EncodeDxtBlockIterator<'a> {
+    image: &'a RgbaImage,
+    layout: DxtLayout,
+    /// The width of the image in 8x8 tiles.
+    tiles_x: usize,
+    /// The position of the next block.
+    index: usize,
+    /// The amount of blocks that haven't been returned yet.
+    remaining: usize,
+}
+
+impl<'a> EncodeDxtBlockIterator<'a> {
+    /// Creates an iterator over the blocks of the given `image`.
+    pub fn new(image: &'a RgbaImage) -> Self {
+        Self {
+            image,
+            layout: DxtLayout::default(),
+            tiles_x: dxt_tiles_x(image.width()),
+            index: 0,
+            remaining: dxt_block_count(image.width(), image.height()),
         }
+    }
 
-        if self.y_block == 8 {
-            self.y_block = 0;
-            self.x += 8;
-        } else {
-            return Some(block);
+    /// Visits the blocks in the order of the given `layout`, instead of the default
+    /// [`DxtLayout::GameCube`] order.
+    pub fn with_layout(mut self, layout: DxtLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+}
+
+impl Iterator for EncodeDxtBlockIterator<'_> {
+    type Item = [u8; 64];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
         }
 
-        if self.x >= self.width {
-            self.x = 0;
-            self.y += 8;
+        let (block_x, block_y) = self.layout.block_coords(self.index, self.tiles_x);
+        let (x_block, y_block) = (block_x as u32 * 4, block_y as u32 * 4);
+        let (width, height) = self.image.dimensions();
+        let mut block = [0; 64];
+
+        // Images smaller than a block (like the smallest mipmaps) get their edge pixels repeated
+        // to fill out the rest of the block
+        for y_sub_block in 0..4 {
+            for x_sub_block in 0..4 {
+                let x = (x_block + x_sub_block).min(width - 1);
+                let y = (y_block + y_sub_block).min(height - 1);
+                let [r, g, b, a] = self.image.get_pixel(x, y).0;
+
+                let i = ((y_sub_block * 4 + x_sub_block) * 4) as usize;
+                block[i..i + 4].copy_from_slice(&[b, g, r, a]);
+            }
         }
 
+        self.index += 1;
+        self.remaining -= 1;
         Some(block)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
-pub struct DecodeDxtBlockIterator {
-    width: u32,
-    height: u32,
+impl ExactSizeIterator for EncodeDxtBlockIterator<'_> {}
 
-    x: u32,
-    y: u32,
-    x_block: u32,
-    y_block: u32,
+/// Iterates through an image of the given width and height in the blocks of
+/// [`DataFormat::Dxt1`](crate::formats::DataFormat::Dxt1) textures, returning the x and y
+/// coordinate of the top left pixel of each 4x4 block.
+///
+/// The blocks are visited in the same order as in [`EncodeDxtBlockIterator`]. All the blocks of an
+/// 8x8 tile are returned, so on the right and bottom edges of images whose dimensions aren't
+/// multiples of 8, the coordinates can be outside the image.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::formats::DxtLayout;
+/// use gvrtex::iter::DecodeDxtBlockIterator;
+///
+/// let coords: Vec<_> = DecodeDxtBlockIterator::new(16, 8).collect();
+/// assert_eq!(
+///     coords,
+///     [(0, 0), (4, 0), (0, 4), (4, 4), (8, 0), (12, 0), (8, 4), (12, 4)]
+/// );
+///
+/// let coords: Vec<_> = DecodeDxtBlockIterator::new(16, 8)
+///     .with_layout(DxtLayout::Wii)
+///     .collect();
+/// assert_eq!(
+///     coords,
+///     [(0, 0), (4, 0), (8, 0), (12, 0), (0, 4), (4, 4), (8, 4), (12, 4)]
+/// );
+/// ```
+pub struct DecodeDxtBlockIterator {
+    layout: DxtLayout,
+    /// The width of the image in 8x8 tiles.
+    tiles_x: usize,
+    /// The position of the next block.
+    index: usize,
+    /// The amount of blocks that haven't been returned yet.
+    remaining: usize,
 }
 
 impl DecodeDxtBlockIterator {
+    /// Creates an iterator over the blocks of an image of the given `width` and `height`.
     pub fn new(width: u32, height: u32) -> Self {
         Self {
-            width,
-            height,
-
-            x: 0,
-            y: 0,
-            x_block: 0,
-            y_block: 0,
+            layout: DxtLayout::default(),
+            tiles_x: dxt_tiles_x(width),
+            index: 0,
+            remaining: dxt_block_count(width, height),
         }
     }
+
+    /// Visits the blocks in the order of the given `layout`, instead of the default
+    /// [`DxtLayout::GameCube`] order.
+    pub fn with_layout(mut self, layout: DxtLayout) -> Self {
+        self.layout = layout;
+        self
+    }
 }
 
 impl Iterator for DecodeDxtBlockIterator {
     type Item = (u32, u32);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.y >= self.height {
+        if self.remaining == 0 {
             return None;
         }
 
-        let coords = (self.x_block + self.x, self.y_block + self.y);
+        let (block_x, block_y) = self.layout.block_coords(self.index, self.tiles_x);
 
-        self.x_block += 4;
-        if self.x_block == 8 {
-            self.x_block = 0;
-            self.y_block += 4;
-        } else {
-            return Some(coords);
-        }
+        self.index += 1;
+        self.remaining -= 1;
+        Some((block_x as u32 * 4, block_y as u32 * 4))
+    }
 
-        if self.y_block == 8 {
-            self.y_block = 0;
-            self.x += 8;
-        } else {
-            return Some(coords);
-        }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
-        if self.x >= self.width {
-            self.x = 0;
-            self.y += 8;
-        }
+impl ExactSizeIterator for DecodeDxtBlockIterator {}
 
-        Some(coords)
-    }
+/// Returns the amount of 4x4 blocks the DXT block iterators return for an image of the given
+/// `width` and `height`, which is four for each 8x8 tile. Images without any pixels have no
+/// blocks.
+fn dxt_block_count(width: u32, height: u32) -> usize {
+    let tiles_x = width.div_ceil(8) as usize;
+    let tiles_y = height.div_ceil(8) as usize;
+    tiles_x * tiles_y * 4
+}
+
+/// Returns the width in 8x8 tiles of a DXT1 image of the given `width`.
+fn dxt_tiles_x(width: u32) -> usize {
+    // A row of tiles has at least one tile, even in an image with a width of 0
+    width.div_ceil(8).max(1) as usize
 }
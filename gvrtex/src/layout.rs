@@ -0,0 +1,96 @@
+//! Contains the sizes and offsets of the headers at the start of a GVR texture, for tools that
+//! read or patch the headers directly instead of going through [`crate::GvrHeader`].
+//!
+//! A texture starts with an optional GCIX/GBIX header, followed by the GVRT header and then the
+//! texture data. The offsets of the fields in each header are given from the start of that header.
+//! In most textures, the GCIX/GBIX header is [`GBIX_HEADER_SIZE`] bytes long, which puts the GVRT
+//! header at [`GVRT_OFFSET`] and the texture data at [`GVRT_DATA_OFFSET`]:
+//!
+//! | Offset | Size | Field                                          | Byte order    |
+//! |--------|------|------------------------------------------------|---------------|
+//! | 0x00   | 4    | `GCIX` or `GBIX`                               |               |
+//! | 0x04   | 4    | The length of the rest of the GCIX/GBIX header | Little endian |
+//! | 0x08   | 4    | Global index                                   | Big endian    |
+//! | 0x0C   | 4    | Padding                                        |               |
+//! | 0x10   | 4    | `GVRT`                                         |               |
+//! | 0x14   | 4    | The length of the rest of the texture          | Little endian |
+//! | 0x18   | 2    | Reserved                                       | Little endian |
+//! | 0x1A   | 1    | Pixel format (upper 4 bits), flags (lower 4)   |               |
+//! | 0x1B   | 1    | Data format                                    |               |
+//! | 0x1C   | 2    | Width                                          | Big endian    |
+//! | 0x1E   | 2    | Height                                         | Big endian    |
+//! | 0x20   |      | Texture data                                   |               |
+//!
+//! Some games use a longer or shorter padding in the GCIX/GBIX header, which moves everything
+//! after it. Use [`crate::GvrHeader::size()`] to find where the texture data of a given texture
+//! starts.
+//!
+//! # Examples
+//!
+//! ```
+//! use gvrtex::formats::DataFormat;
+//! use gvrtex::layout::*;
+//! use gvrtex::TextureEncoder;
+//! use image::{DynamicImage, RgbaImage};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut encoder = TextureEncoder::new_gbix(DataFormat::Rgb565)?.with_global_index(0x1234);
+//! let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(16, 8)))?;
+//!
+//! let field = |offset: usize, len: usize| &encoded[offset..offset + len];
+//! assert_eq!(field(0, 4), b"GBIX");
+//! assert_eq!(field(GBIX_LENGTH_OFFSET, 4), 8u32.to_le_bytes());
+//! assert_eq!(field(GBIX_GLOBAL_INDEX_OFFSET, 4), 0x1234u32.to_be_bytes());
+//! assert_eq!(GBIX_PADDING_OFFSET + 4, GBIX_HEADER_SIZE);
+//!
+//! let field = |offset: usize, len: usize| &encoded[GVRT_OFFSET + offset..][..len];
+//! assert_eq!(field(0, 4), b"GVRT");
+//! let data_len: u32 = 16 * 8 * 2;
+//! assert_eq!(field(GVRT_LENGTH_OFFSET, 4), (data_len + 8).to_le_bytes());
+//! assert_eq!(field(GVRT_RESERVED_OFFSET, 2), [0, 0]);
+//! assert_eq!(field(GVRT_FORMATS_OFFSET, 1), [0x00]);
+//! assert_eq!(field(GVRT_DATA_FORMAT_OFFSET, 1), [u8::from(DataFormat::Rgb565)]);
+//! assert_eq!(field(GVRT_WIDTH_OFFSET, 2), 16u16.to_be_bytes());
+//! assert_eq!(field(GVRT_HEIGHT_OFFSET, 2), 8u16.to_be_bytes());
+//!
+//! assert_eq!(GVRT_OFFSET + GVRT_HEADER_SIZE, GVRT_DATA_OFFSET);
+//! assert_eq!(GVR_HEADER_SIZE, GVRT_DATA_OFFSET);
+//! assert_eq!(encoded.len(), GVRT_DATA_OFFSET + data_len as usize);
+//! # Ok(())
+//! # }
+//! ```
+
+/// The size of the GCIX/GBIX header in most textures, with 4 bytes of padding.
+pub const GBIX_HEADER_SIZE: usize = 0x10;
+/// The offset of the length of the rest of the GCIX/GBIX header, as a little endian `u32`.
+pub const GBIX_LENGTH_OFFSET: usize = 0x4;
+/// The offset of the global index in the GCIX/GBIX header, as a big endian `u32`.
+pub const GBIX_GLOBAL_INDEX_OFFSET: usize = 0x8;
+/// The offset of the padding in the GCIX/GBIX header, which runs until the GVRT header.
+pub const GBIX_PADDING_OFFSET: usize = 0xC;
+
+/// The size of the GVRT header.
+pub const GVRT_HEADER_SIZE: usize = 0x10;
+/// The offset of the length of the rest of the texture in the GVRT header, as a little endian
+/// `u32`. This counts the texture data and the 8 bytes of the GVRT header after this field.
+pub const GVRT_LENGTH_OFFSET: usize = 0x4;
+/// The offset of the reserved field in the GVRT header, as a little endian `u16`.
+pub const GVRT_RESERVED_OFFSET: usize = 0x8;
+/// The offset of the byte in the GVRT header holding the pixel format in its upper 4 bits and the
+/// data flags in its lower 4 bits.
+pub const GVRT_FORMATS_OFFSET: usize = 0xA;
+/// The offset of the data format in the GVRT header.
+pub const GVRT_DATA_FORMAT_OFFSET: usize = 0xB;
+/// The offset of the width in the GVRT header, as a big endian `u16`.
+pub const GVRT_WIDTH_OFFSET: usize = 0xC;
+/// The offset of the height in the GVRT header, as a big endian `u16`.
+pub const GVRT_HEIGHT_OFFSET: usize = 0xE;
+
+/// The offset of the GVRT header in textures with a [`GBIX_HEADER_SIZE`] bytes long GCIX/GBIX
+/// header.
+pub const GVRT_OFFSET: usize = GBIX_HEADER_SIZE;
+/// The size of both headers in textures with a [`GBIX_HEADER_SIZE`] bytes long GCIX/GBIX header.
+pub const GVR_HEADER_SIZE: usize = GBIX_HEADER_SIZE + GVRT_HEADER_SIZE;
+/// The offset of the texture data in textures with a [`GBIX_HEADER_SIZE`] bytes long GCIX/GBIX
+/// header.
+pub const GVRT_DATA_OFFSET: usize = GVR_HEADER_SIZE;
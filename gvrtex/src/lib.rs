@@ -16,11 +16,11 @@
 //! use gvrtex::formats::DataFormat;
 //! use gvrtex::TextureEncoder;
 //!
-//! # fn main() -> Result<Vec<u8>, TextureEncodeError> {
+//! # fn main() -> Result<(), TextureEncodeError> {
 //! # let img_path: &str = "";
 //! let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?;
 //! let encoded_file = encoder.encode(img_path)?;
-//! # Ok(encoded_file)
+//! # Ok(())
 //! # }
 //! ```
 //!
@@ -49,25 +49,103 @@
 //! # Hints
 //!
 //! Easiest place to start off is to look at [`TextureEncoder`] for encoding GVR textures and
-//! [`TextureDecoder`] for decoding GVR textures.
+//! [`TextureDecoder`] for decoding GVR textures. To have invalid combinations of formats rejected
+//! at compile time, set up the encoder with [`builder::GvrEncoderBuilder`] instead.
+//!
+//! # Features
+//!
+//! * `std` *(enabled by default)* - Enables everything that depends on the standard library,
+//!   which is everything except the [`pixel`] module. Disabling it makes the crate `no_std`
+//!   compatible (an allocator is still required).
+//! * `simd` - Encodes [`formats::DataFormat::Rgb565`] and [`formats::DataFormat::Rgb5a3`] textures
+//!   8 pixels at a time with SSE2 on x86_64, see [`pixel::encode_rgb565_pixels()`]. This has no
+//!   effect on other targets.
+//! * `rayon` - Decodes textures on multiple threads with [rayon](https://docs.rs/rayon), see
+//!   [`TextureDecoder::decode()`].
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use crate::error::*;
-use crate::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+#[cfg(feature = "std")]
+use crate::formats::{DataFlags, DataFormat, DxtLayout, PixelFormat, TextureType};
+#[cfg(feature = "std")]
 use crate::pixel_codecs::*;
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
-use codec::GvrEncoder;
-use image::imageops::FilterType;
-use image::{DynamicImage, ImageReader, RgbaImage};
+#[cfg(feature = "std")]
+use codec::{expand_indexed, flip_indices_vertical, GvrDecoder, GvrEncoder, GvrEncoderBase};
+#[cfg(feature = "std")]
+use image::imageops::{self, FilterType};
+#[cfg(feature = "std")]
+use image::{DynamicImage, ImageReader, Rgba, RgbaImage};
+#[cfg(feature = "std")]
+use iter::PixelBlockIteratorExt;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
-use std::ops::Not;
 
-mod codec;
+#[cfg(feature = "std")]
+mod analyze;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+mod bytes;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+pub mod codec;
+#[cfg(feature = "std")]
+mod dds;
+#[cfg(feature = "std")]
 pub mod error;
+#[cfg(feature = "std")]
 pub mod formats;
-mod iter;
+#[cfg(feature = "std")]
+mod header;
+#[cfg(feature = "std")]
+mod image_decoder;
+#[cfg(feature = "std")]
+pub mod iter;
+pub mod layout;
+#[cfg(feature = "std")]
+mod locate;
+#[cfg(feature = "std")]
+mod palette;
+pub mod pixel;
+#[cfg(feature = "std")]
 mod pixel_codecs;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod pixel_simd;
+#[cfg(feature = "std")]
+pub mod pvr;
+#[cfg(feature = "std")]
+mod rounding;
+#[cfg(feature = "std")]
+mod swizzle;
+#[cfg(feature = "std")]
+pub mod tpl;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+pub use analyze::{analyze_image, compare_images, ImageDiff, ImageStats};
+#[cfg(feature = "std")]
+pub use bytes::{decode_bytes, decode_raw, encode_raw, hash_encoded};
+#[cfg(feature = "std")]
+pub use header::GvrHeader;
+#[cfg(feature = "std")]
+pub use image_decoder::GvrImageDecoder;
+#[cfg(feature = "std")]
+pub use locate::{extract_all, scan, GvrLocation};
+#[cfg(feature = "std")]
+pub use palette::palette_swap;
+#[cfg(feature = "std")]
+pub use swizzle::{deswizzle, swizzle};
 
 /// Provides all the functionality needed to encode a GVR texture file.
 ///
@@ -76,49 +154,125 @@ mod pixel_codecs;
 /// file yourself.
 ///
 /// For examples, see the documentation on the root of the [`crate`]
-#[derive(Default)]
+#[cfg(feature = "std")]
 pub struct TextureEncoder {
     texture_type: TextureType,
     pixel_format: PixelFormat,
     data_format: DataFormat,
     data_flags: DataFlags,
     global_index: u32,
+    alpha_threshold: u8,
+    padding_byte: u8,
+    flip_v: bool,
+    strict_alpha: bool,
+    gvrt_reserved: u16,
+    raw_format_byte: Option<u8>,
+    auto_lossless: bool,
+    round_16bit: bool,
+    premultiplied_alpha: bool,
+    mip_generation: MipGeneration,
+    alpha_coverage: Option<u8>,
+    mipmap_sharpen: f32,
+    mipmap_images: Option<Vec<RgbaImage>>,
+    dxt_layout: DxtLayout,
+    channel_swizzle: Option<[usize; 4]>,
+    color_dithering: bool,
+    resize: Option<(u32, u32, FilterType)>,
+    custom_encoder: Option<(u8, Box<dyn GvrEncoder + Send + Sync>)>,
+}
+
+#[cfg(feature = "std")]
+impl Default for TextureEncoder {
+    fn default() -> Self {
+        Self {
+            texture_type: Default::default(),
+            pixel_format: Default::default(),
+            data_format: Default::default(),
+            data_flags: Default::default(),
+            global_index: 0,
+            alpha_threshold: Self::DEFAULT_ALPHA_THRESHOLD,
+            padding_byte: 0,
+            flip_v: false,
+            strict_alpha: false,
+            gvrt_reserved: 0,
+            raw_format_byte: None,
+            auto_lossless: false,
+            round_16bit: false,
+            premultiplied_alpha: false,
+            mip_generation: MipGeneration::default(),
+            alpha_coverage: None,
+            mipmap_sharpen: 0.0,
+            mipmap_images: None,
+            dxt_layout: DxtLayout::default(),
+            channel_swizzle: None,
+            color_dithering: false,
+            resize: None,
+            custom_encoder: None,
+        }
+    }
 }
 
+#[cfg(feature = "std")]
 impl TextureEncoder {
+    /// The alpha threshold used by the encoder, unless specified otherwise with
+    /// [`Self::with_alpha_threshold()`].
+    ///
+    /// This is the standard cutoff for the 1-bit alpha of DXT1 (BC1) compressed textures.
+    pub const DEFAULT_ALPHA_THRESHOLD: u8 = 128;
+
     fn check_given_formats(data_format: DataFormat) -> Result<(), TextureEncodeError> {
-        match data_format {
-            DataFormat::Index4 | DataFormat::Index8 => Err(TextureEncodeError::Format),
-            _ => Ok(()),
+        if data_format.requires_palette() {
+            return Err(TextureEncodeError::Format);
         }
+        Ok(())
     }
 
     fn check_given_formats_palettized(data_format: DataFormat) -> Result<(), TextureEncodeError> {
-        match data_format {
-            DataFormat::Index4 | DataFormat::Index8 => Ok(()),
-            _ => Err(TextureEncodeError::Format),
+        if !data_format.requires_palette() {
+            return Err(TextureEncodeError::Format);
         }
+        Ok(())
     }
 
-    /// Creates a new encoder, that encodes palettized GVR texture files using the given `data_format`
-    /// and `pixel_format`.
+    /// Creates a new encoder, that encodes GVR texture files using the given `data_format`, starting
+    /// with the header given in `texture_type`.
     ///
-    /// This specific function sets the magic strings in the header of the encoded texture file to
-    /// "GCIX".
+    /// # Errors
+    ///
+    /// This function will return a [`TextureEncodeError::Format`] if you pass in a data format
+    /// that is [`DataFormat::Index4`] or [`DataFormat::Index8`]. If you want to encode textures
+    /// that you want to generate a color palette for, see [`Self::new_palettized()`], as that
+    /// allows you to set the data format for the color palette as well.
+    pub fn new(
+        texture_type: TextureType,
+        data_format: DataFormat,
+    ) -> Result<Self, TextureEncodeError> {
+        Self::check_given_formats(data_format)?;
+
+        Ok(Self {
+            texture_type,
+            data_format,
+            ..Default::default()
+        })
+    }
+
+    /// Creates a new encoder, that encodes palettized GVR texture files using the given
+    /// `data_format` and `pixel_format`, starting with the header given in `texture_type`.
     ///
     /// # Errors
     ///
     /// This function will return a [`TextureEncodeError::Format`] if you pass in a data format
     /// that isn't [`DataFormat::Index4`] or [`DataFormat::Index8`]. If you want to encode textures
-    /// that you don't want to generate a color palette for, see [`Self::new_gcix()`].
-    pub fn new_gcix_palettized(
+    /// that you don't want to generate a color palette for, see [`Self::new()`].
+    pub fn new_palettized(
+        texture_type: TextureType,
         pixel_format: PixelFormat,
         data_format: DataFormat,
     ) -> Result<Self, TextureEncodeError> {
         Self::check_given_formats_palettized(data_format)?;
 
         Ok(Self {
-            texture_type: TextureType::Gcix,
+            texture_type,
             pixel_format,
             data_format,
             data_flags: DataFlags::InternalPalette,
@@ -126,6 +280,24 @@ impl TextureEncoder {
         })
     }
 
+    /// Creates a new encoder, that encodes palettized GVR texture files using the given `data_format`
+    /// and `pixel_format`.
+    ///
+    /// This specific function sets the magic strings in the header of the encoded texture file to
+    /// "GCIX".
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`TextureEncodeError::Format`] if you pass in a data format
+    /// that isn't [`DataFormat::Index4`] or [`DataFormat::Index8`]. If you want to encode textures
+    /// that you don't want to generate a color palette for, see [`Self::new_gcix()`].
+    pub fn new_gcix_palettized(
+        pixel_format: PixelFormat,
+        data_format: DataFormat,
+    ) -> Result<Self, TextureEncodeError> {
+        Self::new_palettized(TextureType::Gcix, pixel_format, data_format)
+    }
+
     /// Creates a new encoder, that encodes GVR texture files using the given `data_format`.
     ///
     /// This specific function sets the magic strings in the header of the encoded texture file to
@@ -138,13 +310,7 @@ impl TextureEncoder {
     /// that you want to generate a color palette for, see [`Self::new_gcix_palettized()`], as that
     /// allows you to set the data format for the color palette as well.
     pub fn new_gcix(data_format: DataFormat) -> Result<Self, TextureEncodeError> {
-        Self::check_given_formats(data_format)?;
-
-        Ok(Self {
-            texture_type: TextureType::Gcix,
-            data_format,
-            ..Default::default()
-        })
+        Self::new(TextureType::Gcix, data_format)
     }
 
     /// Creates a new encoder, that encodes palettized GVR texture files using the given `data_format`
@@ -162,15 +328,7 @@ impl TextureEncoder {
         pixel_format: PixelFormat,
         data_format: DataFormat,
     ) -> Result<Self, TextureEncodeError> {
-        Self::check_given_formats_palettized(data_format)?;
-
-        Ok(Self {
-            texture_type: TextureType::Gbix,
-            pixel_format,
-            data_format,
-            data_flags: DataFlags::InternalPalette,
-            ..Default::default()
-        })
+        Self::new_palettized(TextureType::Gbix, pixel_format, data_format)
     }
 
     /// Creates a new encoder, that encodes GVR texture files using the given `data_format`.
@@ -185,13 +343,115 @@ impl TextureEncoder {
     /// that you want to generate a color palette for, see [`Self::new_gbix_palettized()`], as that
     /// allows you to set the data format for the color palette as well.
     pub fn new_gbix(data_format: DataFormat) -> Result<Self, TextureEncodeError> {
-        Self::check_given_formats(data_format)?;
+        Self::new(TextureType::Gbix, data_format)
+    }
 
-        Ok(Self {
-            texture_type: TextureType::Gbix,
-            data_format,
+    /// Creates a new encoder, that picks the smallest data format that stores each encoded image
+    /// without any loss, starting with the header given in `texture_type`.
+    ///
+    /// The data format is picked separately for each image, by going through these checks in
+    /// order and using the first one that applies:
+    ///
+    /// 1. Grayscale images without transparency are encoded as [`DataFormat::Intensity8`].
+    /// 2. Grayscale images with transparency are encoded as [`DataFormat::IntensityA8`].
+    /// 3. Images with at most 256 unique colors are encoded as [`DataFormat::Index8`], if all of
+    ///    the colors can be stored in the color palette as is. The palette uses
+    ///    [`PixelFormat::RGB565`] if possible, and [`PixelFormat::RGB5A3`] otherwise.
+    /// 4. Every other image is encoded as [`DataFormat::Argb8888`].
+    ///
+    /// The picked data format is reported in the [`EncodeInfo`] returned by
+    /// [`Self::encode_with_info()`]. Each image still has to have dimensions that the picked data
+    /// format supports. As none of these data formats support mipmaps, neither does this encoder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, PixelFormat, TextureType};
+    /// use gvrtex::pixel::decode_rgb565;
+    /// use gvrtex::{GvrHeader, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut encoder = TextureEncoder::new_auto_lossless(TextureType::Gcix);
+    /// let mut encode = |image: RgbaImage| -> Result<GvrHeader, Box<dyn std::error::Error>> {
+    ///     let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///     Ok(GvrHeader::read(&mut encoded.as_slice())?)
+    /// };
+    ///
+    /// let grayscale = RgbaImage::from_fn(16, 16, |x, y| {
+    ///     let value = (x * 16 + y) as u8;
+    ///     Rgba([value, value, value, 0xFF])
+    /// });
+    /// assert_eq!(encode(grayscale)?.data_format, DataFormat::Intensity8);
+    ///
+    /// // 200 colors that RGB565 stores exactly
+    /// let colors = RgbaImage::from_fn(16, 16, |x, y| {
+    ///     Rgba(decode_rgb565(((y * 16 + x) % 200) as u16 * 300))
+    /// });
+    /// let header = encode(colors)?;
+    /// assert_eq!(header.data_format, DataFormat::Index8);
+    /// assert_eq!(header.pixel_format, PixelFormat::RGB565);
+    ///
+    /// let full_color = RgbaImage::from_fn(32, 32, |x, y| Rgba([x as u8 * 8, y as u8 * 8, 0, 0xFF]));
+    /// assert_eq!(encode(full_color)?.data_format, DataFormat::Argb8888);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_auto_lossless(texture_type: TextureType) -> Self {
+        Self {
+            texture_type,
+            data_format: DataFormat::Argb8888,
+            auto_lossless: true,
             ..Default::default()
-        })
+        }
+    }
+
+    /// Picks the formats for an image encoded with [`Self::new_auto_lossless()`], see its
+    /// documentation for how they're picked.
+    fn pick_lossless_formats(&mut self, image: &RgbaImage) {
+        let is_grayscale = image.pixels().all(|p| p.0[0] == p.0[1] && p.0[1] == p.0[2]);
+        let is_opaque = image.pixels().all(|p| p.0[3] == 0xFF);
+
+        let mut colors = HashSet::new();
+        for p in image.pixels() {
+            colors.insert(p.0);
+            if colors.len() > 256 {
+                break;
+            }
+        }
+        let stores_all = |encode: fn([u8; 4]) -> u16, decode: fn(u16) -> [u8; 4]| {
+            colors.iter().all(|&color| decode(encode(color)) == color)
+        };
+
+        let palette_format = if colors.len() > 256 {
+            None
+        } else if stores_all(pixel::encode_rgb565, pixel::decode_rgb565) {
+            Some(PixelFormat::RGB565)
+        } else if stores_all(pixel::encode_rgb5a3, pixel::decode_rgb5a3) {
+            Some(PixelFormat::RGB5A3)
+        } else {
+            None
+        };
+
+        (self.data_format, self.data_flags) = match (is_grayscale, is_opaque, palette_format) {
+            (true, true, _) => (DataFormat::Intensity8, DataFlags::empty()),
+            (true, false, _) => (DataFormat::IntensityA8, DataFlags::empty()),
+            (false, _, Some(pixel_format)) => {
+                self.pixel_format = pixel_format;
+                (DataFormat::Index8, DataFlags::InternalPalette)
+            }
+            (false, _, None) => (DataFormat::Argb8888, DataFlags::empty()),
+        };
+    }
+
+    /// Instructs the encoder to leave out the GCIX/GBIX header, so that the encoded file starts
+    /// directly with the GVRT header.
+    ///
+    /// Some tools expect textures in this form. As the global index is stored in the GCIX/GBIX
+    /// header, the value given to [`Self::with_global_index()`] is not saved in the file.
+    pub fn without_outer_header(mut self) -> Self {
+        self.texture_type = TextureType::None;
+        self
     }
 
     /// Instructs the encoder to also generate mipmaps alongside the original texture.
@@ -203,261 +463,3159 @@ impl TextureEncoder {
     ///
     /// </div>
     ///
+    /// Each mipmap halves the width and height of the previous one, down to 1x1. A dimension that
+    /// reaches 1 before the other stays at 1, so a 256x64 texture gets 8 mipmaps, from 128x32 down
+    /// to 1x1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{GvrHeader, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_pixel(256, 64, Rgba([0xFF, 0, 0, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?.with_mipmaps()?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// let header = GvrHeader::read(&mut encoded.as_slice())?;
+    /// assert_eq!(header.data_len as usize, DataFormat::Dxt1.data_size(256, 64, 8));
+    ///
+    /// let sizes = [
+    ///     (256, 64),
+    ///     (128, 32),
+    ///     (64, 16),
+    ///     (32, 8),
+    ///     (16, 4),
+    ///     (8, 2),
+    ///     (4, 1),
+    ///     (2, 1),
+    ///     (1, 1),
+    /// ];
+    /// let mut data = &encoded[header.size()..];
+    /// for (width, height) in sizes {
+    ///     let level = gvrtex::decode_raw(data, width, height, DataFormat::Dxt1, None)?;
+    ///     assert_eq!(level, RgbaImage::from_pixel(width, height, Rgba([0xFF, 0, 0, 0xFF])));
+    ///     data = &data[DataFormat::Dxt1.data_size(width, height, 0)..];
+    /// }
+    /// assert!(data.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Non-square textures aren't squashed into squares, each mipmap keeps the aspect ratio of the
+    /// texture until its height reaches 1:
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{GvrHeader, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let color = Rgba([0xFF, 0xFF, 0x00, 0xFF]);
+    /// let image = RgbaImage::from_pixel(64, 32, color);
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_mipmaps()?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// let header = GvrHeader::read(&mut encoded.as_slice())?;
+    /// assert_eq!((header.width, header.height), (64, 32));
+    /// assert_eq!(header.data_len as usize, DataFormat::Rgb565.data_size(64, 32, 6));
+    ///
+    /// let mut data = &encoded[header.size()..];
+    /// for (width, height) in [(64, 32), (32, 16), (16, 8), (8, 4), (4, 2), (2, 1), (1, 1)] {
+    ///     let level = gvrtex::decode_raw(data, width, height, DataFormat::Rgb565, None)?;
+    ///     assert_eq!(level, RgbaImage::from_pixel(width, height, color));
+    ///     data = &data[DataFormat::Rgb565.data_size(width, height, 0)..];
+    /// }
+    /// assert!(data.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
     /// # Errors
     ///
     /// If you try to enable mipmaps on data formats that aren't listed above, a
     /// [`TextureEncodeError::Mipmap`] error is returned.
     pub fn with_mipmaps(mut self) -> Result<Self, TextureEncodeError> {
-        match self.data_format {
-            DataFormat::Dxt1 | DataFormat::Rgb565 | DataFormat::Rgb5a3 => {
-                self.data_flags.set(DataFlags::Mipmaps, true);
-                Ok(self)
-            }
-            _ => Err(TextureEncodeError::Mipmap),
-        }
+        Self::check_mipmap_support(self.data_format)?;
+        self.data_flags.set(DataFlags::Mipmaps, true);
+        Ok(self)
     }
 
-    /// Sets the global index in the header of the encoded GVR texture file.
+    /// Instructs the encoder to encode the given mipmaps along with the texture, instead of
+    /// generating them by scaling down the image, which lets you use mipmaps made by hand.
     ///
-    /// Most GameCube and Wii games don't really use this but some games do. If this method is not
-    /// used in the process of instantiating the encoder, then the global index will default to 0.
-    pub fn with_global_index(mut self, global_index: u32) -> Self {
-        self.global_index = global_index;
-        self
+    /// `levels` starts with the first mipmap after the full size image, and has to go all the
+    /// way down to 1x1. Each mipmap has to halve both dimensions of the previous one, with a
+    /// dimension that reached 1 staying at 1, the same as the mipmaps generated by
+    /// [`Self::with_mipmaps()`]. The mipmaps are flipped with [`Self::with_flip_v()`] and
+    /// converted with [`Self::with_premultiplied_alpha()`] like the full size image, but the
+    /// options that only apply to generated mipmaps (such as [`Self::with_mipmap_sharpen()`])
+    /// don't affect them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::error::TextureEncodeError;
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let colors = [[0xFF, 0, 0, 0xFF], [0, 0xFF, 0, 0xFF], [0, 0, 0xFF, 0xFF], [0xFF; 4]];
+    /// let levels: Vec<_> = [(8, 4), (4, 2), (2, 1), (1, 1)]
+    ///     .into_iter()
+    ///     .zip(colors)
+    ///     .map(|((width, height), color)| RgbaImage::from_pixel(width, height, Rgba(color)))
+    ///     .collect();
+    ///
+    /// let image = DynamicImage::ImageRgba8(RgbaImage::new(16, 8));
+    /// let mut encoder =
+    ///     TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_mipmap_images(levels.clone())?;
+    /// let encoded = encoder.encode_image(image.clone())?;
+    ///
+    /// let mut data = &encoded[0x20 + DataFormat::Rgb565.data_size(16, 8, 0)..];
+    /// for level in &levels {
+    ///     let (width, height) = level.dimensions();
+    ///     assert_eq!(&gvrtex::decode_raw(data, width, height, DataFormat::Rgb565, None)?, level);
+    ///     data = &data[DataFormat::Rgb565.data_size(width, height, 0)..];
+    /// }
+    /// assert!(data.is_empty());
+    ///
+    /// // Every mipmap down to 1x1 has to be given, with half the dimensions of the previous one
+    /// let mut encoder =
+    ///     TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_mipmap_images(levels[..3].to_vec())?;
+    /// let result = encoder.encode_image(image.clone());
+    /// assert!(matches!(result, Err(TextureEncodeError::MipmapCount(4, 3))));
+    ///
+    /// let mut levels = levels;
+    /// levels[1] = RgbaImage::new(4, 4);
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_mipmap_images(levels)?;
+    /// let result = encoder.encode_image(image);
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(TextureEncodeError::MipmapDimensions(2, (4, 2), (4, 4)))
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the data format doesn't support mipmaps (see [`Self::with_mipmaps()`]), a
+    /// [`TextureEncodeError::Mipmap`] error is returned. The mipmaps are checked against the
+    /// dimensions of the image when it's encoded, where a
+    /// [`TextureEncodeError::MipmapCount`] or [`TextureEncodeError::MipmapDimensions`] error is
+    /// returned if they don't match.
+    pub fn with_mipmap_images(
+        mut self,
+        levels: Vec<RgbaImage>,
+    ) -> Result<Self, TextureEncodeError> {
+        Self::check_mipmap_support(self.data_format)?;
+        self.data_flags.set(DataFlags::Mipmaps, true);
+        self.mipmap_images = Some(levels);
+        Ok(self)
     }
 
-    fn encode_mipmaps(&self, img: &RgbaImage, encoder: &dyn GvrEncoder) -> Vec<u8> {
-        let mut mipmaps: Vec<u8> = vec![];
-        let mipmap_count = img.width().ilog2();
-        let mut tex_size = img.width() / 2;
-
-        for _ in 0..mipmap_count {
-            if tex_size < 1 {
-                break;
-            }
-
-            let mipmap = DynamicImage::ImageRgba8(img.clone()).resize_exact(
-                tex_size,
-                tex_size,
-                FilterType::Triangle,
-            );
-
-            let mut encoded = encoder.encode(&mipmap.into_rgba8());
-
-            if encoded.len() < 32 {
-                encoded.resize(32, 0);
-            }
-
-            mipmaps.append(&mut encoded);
-            tex_size /= 2;
+    /// Replaces all the [`DataFlags`] the encoder will write into the header of the encoded
+    /// texture, for when you need full control over them (for example to encode a texture with
+    /// [`DataFlags::ExternalPalette`]).
+    ///
+    /// This overrides any flags set by the constructor or by [`Self::with_mipmaps()`]. Textures
+    /// encoded with [`DataFlags::ExternalPalette`] only contain the palette indices, the color
+    /// palette itself is left out of the file.
+    ///
+    /// # Errors
+    ///
+    /// If the flags aren't a valid combination for the data format of the encoder (see
+    /// [`DataFlags::is_valid_for()`]), a [`TextureEncodeError::Format`] is returned. If mipmaps
+    /// are requested for a data format that doesn't support them (see [`Self::with_mipmaps()`]),
+    /// a [`TextureEncodeError::Mipmap`] is returned.
+    pub fn with_data_flags(mut self, data_flags: DataFlags) -> Result<Self, TextureEncodeError> {
+        if !data_flags.is_valid_for(self.data_format) {
+            return Err(TextureEncodeError::Format);
+        }
+        if data_flags.intersects(DataFlags::Mipmaps) {
+            Self::check_mipmap_support(self.data_format)?;
         }
 
-        mipmaps
+        self.data_flags = data_flags;
+        Ok(self)
     }
 
-    /// Encodes the image file given in `img_path` into a GVR texture.
+    /// Changes the data format of an existing encoder, keeping all of its other settings.
     ///
-    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    /// The new data format has to fit the [`DataFlags`] of the encoder, so an encoder created
+    /// without a color palette can't be changed to a palettized data format, and the other way
+    /// around. The data format also replaces a custom encoder set with
+    /// [`Self::with_custom_encoder()`], and turns off the format selection of
+    /// [`Self::new_auto_lossless()`].
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// If anything goes wrong in the encoding process, a [`TextureEncodeError`] is returned
-    /// instead.
-    pub fn encode(&mut self, img_path: &str) -> Result<Vec<u8>, TextureEncodeError> {
-        let img = ImageReader::open(img_path)?.decode()?;
-        self.encode_internal(img)
-    }
-
-    /// Encodes the image file given in the `image_buffer` into a GVR texture. The format of the
-    /// image is guessed.
+    /// ```
+    /// use gvrtex::error::TextureEncodeError;
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use gvrtex::TextureEncoder;
     ///
-    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    /// # fn main() -> Result<(), TextureEncodeError> {
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_mipmaps()?;
+    /// encoder.set_data_format(DataFormat::Dxt1)?;
+    ///
+    /// // Rgb565 doesn't use a color palette, and Intensity8 doesn't support mipmaps
+    /// let result = encoder.set_data_format(DataFormat::Index8);
+    /// assert!(matches!(result, Err(TextureEncodeError::Format)));
+    /// let result = encoder.set_data_format(DataFormat::Intensity8);
+    /// assert!(matches!(result, Err(TextureEncodeError::Mipmap)));
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix_palettized(PixelFormat::RGB5A3, DataFormat::Index8)?;
+    /// encoder.set_data_format(DataFormat::Index4)?;
+    /// let result = encoder.set_data_format(DataFormat::Argb8888);
+    /// assert!(matches!(result, Err(TextureEncodeError::Format)));
+    /// # Ok(())
+    /// # }
+    /// ```
     ///
     /// # Errors
     ///
-    /// If anything goes wrong in the encoding process, a [`TextureEncodeError`] is returned
-    /// instead.
-    pub fn encode_buffer(&mut self, image_buffer: Vec<u8>) -> Result<Vec<u8>, TextureEncodeError> {
-        let img = ImageReader::new(Cursor::new(image_buffer))
-            .with_guessed_format()?
-            .decode()?;
-        self.encode_internal(img)
-    }
-
-    fn encode_internal(&mut self, img: DynamicImage) -> Result<Vec<u8>, TextureEncodeError> {
-        let mut result = Vec::new();
-        let rgba_img = img.into_rgba8();
-
-        let mut encoded;
-        if self.data_flags.intersects(DataFlags::InternalPalette) {
-            let encoder = create_new_encoder_with_palette(self.data_format);
-            encoder.validate_input(&rgba_img)?;
-            encoded = encoder.encode(&rgba_img, self.pixel_format)?;
-        } else {
-            let encoder = create_new_encoder(self.data_format);
-            encoder.validate_input(&rgba_img)?;
-            encoded = encoder.encode(&rgba_img);
-
-            if self.data_flags.intersects(DataFlags::Mipmaps) {
-                let mut encoded_mipmaps = self.encode_mipmaps(&rgba_img, &*encoder);
-                encoded.append(&mut encoded_mipmaps);
-            }
+    /// If the flags of the encoder aren't a valid combination for `format` (see
+    /// [`DataFlags::is_valid_for()`]), a [`TextureEncodeError::Format`] is returned. If the encoder
+    /// generates mipmaps and `format` doesn't support them, a [`TextureEncodeError::Mipmap`] is
+    /// returned. The encoder is left unchanged in both cases.
+    pub fn set_data_format(&mut self, format: DataFormat) -> Result<(), TextureEncodeError> {
+        if !self.data_flags.is_valid_for(format) {
+            return Err(TextureEncodeError::Format);
+        }
+        if self.data_flags.intersects(DataFlags::Mipmaps) {
+            Self::check_mipmap_support(format)?;
         }
 
-        self.write_header(&rgba_img, &encoded, &mut result)?;
-        result.write_all(&encoded)?;
-
-        Ok(result)
+        self.data_format = format;
+        self.custom_encoder = None;
+        self.auto_lossless = false;
+        Ok(())
     }
 
-    fn write_header(
-        &self,
-        image: &RgbaImage,
-        encoded: &[u8],
-        buf: &mut Vec<u8>,
-    ) -> std::io::Result<()> {
-        if self.texture_type == TextureType::Gcix {
-            buf.write_all(b"GCIX")?;
-        } else {
-            buf.write_all(b"GBIX")?;
+    fn check_mipmap_support(data_format: DataFormat) -> Result<(), TextureEncodeError> {
+        if !data_format.supports_mipmaps() {
+            return Err(TextureEncodeError::Mipmap);
         }
-        buf.write_u32::<LittleEndian>(8)?;
-        buf.write_u32::<BigEndian>(self.global_index)?;
-        buf.resize(0x10, 0); // padding
-
-        buf.write_all(b"GVRT")?;
-        buf.write_u32::<LittleEndian>((encoded.len() + 8).try_into().unwrap())?;
-        buf.write_u16::<LittleEndian>(0)?; // padding
-
-        let pixel_format = (self.pixel_format as u8) << 4;
-        let data_flags: u8 = self.data_flags.into();
-        let flags = pixel_format | data_flags;
-
-        buf.write_u8(flags)?;
-        buf.write_u8(self.data_format.into())?;
-        buf.write_u16::<BigEndian>(image.width().try_into().unwrap())?;
-        buf.write_u16::<BigEndian>(image.height().try_into().unwrap())?;
-
         Ok(())
     }
-}
 
-/// Provides all the functionality needed to decode a GVR texture file.
-///
-/// When the file is decoded using [`Self::decode()`], the image is not given to you from that
-/// method. You can retrieve it via [`Self::as_decoded()`] or [`Self::into_decoded()`], or if you
-/// don't want an in-memory representation of the file, you can immediately save the file via
-/// [`Self::save()`].
-///
-/// For examples, see the documentation on the root of the [`crate`]
-#[derive(Default)]
-pub struct TextureDecoder {
-    cursor: Cursor<Vec<u8>>,
-    image: Option<RgbaImage>,
-}
+    /// Sets the global index in the header of the encoded GVR texture file.
+    ///
+    /// Most GameCube and Wii games don't really use this but some games do. If this method is not
+    /// used in the process of instantiating the encoder, then the global index will default to 0.
+    pub fn with_global_index(mut self, global_index: u32) -> Self {
+        self.global_index = global_index;
+        self
+    }
 
-impl TextureDecoder {
-    /// Instantiate a new [`TextureDecoder`], that can decode the file in the given `gvr_path`,
-    /// reading the file's contents.
+    /// Sets the alpha threshold used when encoding [`DataFormat::Dxt1`] textures.
     ///
-    /// This function doesn't decode the file by itself, [`Self::decode()`] must be called.
+    /// DXT1 can only store a single bit of alpha per pixel, so every pixel with an alpha value
+    /// below `alpha_threshold` will be encoded as fully transparent, while the rest will be
+    /// encoded as fully opaque. Passing in 0 makes every pixel opaque.
     ///
-    /// # Errors
+    /// If this method is not used, then the threshold will default to
+    /// [`Self::DEFAULT_ALPHA_THRESHOLD`]. Other data formats are not affected by this setting.
+    pub fn with_alpha_threshold(mut self, alpha_threshold: u8) -> Self {
+        self.alpha_threshold = alpha_threshold;
+        self
+    }
+
+    /// Sets the byte value used to fill the padding in the encoded GVR texture file.
     ///
-    /// An IO error will be returned if the given `gvr_path` is invalid in any way.
-    pub fn new(gvr_path: &str) -> Result<Self, std::io::Error> {
-        Ok(Self {
-            cursor: Cursor::new(std::fs::read(gvr_path)?),
-            ..Default::default()
-        })
+    /// This applies to the padding at the end of the GCIX/GBIX header, as well as the padding
+    /// added after texture data (including mipmaps) that's smaller than the minimum size of 32
+    /// bytes. Some games fill these regions with a value other than 0 (commonly `0xFF`), so this
+    /// is needed for encoding textures that are byte-for-byte identical to the original files.
+    ///
+    /// If this method is not used, then the padding byte will default to 0.
+    pub fn with_padding_byte(mut self, padding_byte: u8) -> Self {
+        self.padding_byte = padding_byte;
+        self
     }
 
-    /// Instantiate a new [`TextureDecoder`], that can decode the texture in the given `buffer`.
+    /// Sets the reserved field in the GVRT header of the encoded GVR texture file.
     ///
-    /// This function doesn't decode the file by itself, [`Self::decode()`] must be called.
+    /// These are the two bytes following the length of the texture data, at offset 0x8 of the
+    /// GVRT header (offset 0x18 of the file, unless [`Self::without_outer_header()`] is used). The
+    /// `value` is stored in little endian, like the length before it. Most games leave this at 0,
+    /// but some store their own values here, so this is only needed to reproduce their files
+    /// exactly.
     ///
-    /// # Notes
+    /// If this method is not used, then the reserved field will default to 0.
     ///
-    /// The start of the `buffer` should properly point to the start of the texture.
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, RgbaImage};
     ///
-    /// The length of `buffer` should be large enough to contain the whole texture. It can be
-    /// bigger than the actual texture data, the `buffer` will only be read up until the full
-    /// length of the texture.
-    pub fn new_from_buffer(buffer: Vec<u8>) -> Self {
-        Self {
-            cursor: Cursor::new(buffer),
-            ..Default::default()
-        }
+    /// # fn main() -> Result<(), gvrtex::error::TextureEncodeError> {
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_gvrt_reserved(0x1234);
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(4, 4)))?;
+    /// assert_eq!(&encoded[0x18..0x1A], &[0x34, 0x12]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_gvrt_reserved(mut self, value: u16) -> Self {
+        self.gvrt_reserved = value;
+        self
     }
 
-    /// Decodes the given image from [`Self::new()`].
+    /// Writes the given `value` into the GVRT header as the data format, while still encoding
+    /// the texture data in the data format the encoder was set up with.
     ///
-    /// # Errors
+    /// This is an escape hatch for experimenting with data format values that show up in game
+    /// files but aren't documented anywhere. Nothing checks that `value` matches the texture data,
+    /// so the resulting texture can easily be one that neither this crate (without a custom
+    /// decoder, see [`TextureDecoder::with_custom_decoder()`]) nor the game can decode. This also
+    /// overrides the value set with [`Self::with_custom_encoder()`].
     ///
-    /// If something goes wrong while decoding, or the given file is not a valid GVR texture file,
-    /// a [`TextureDecodeError`] is returned.
-    pub fn decode(&mut self) -> Result<(), TextureDecodeError> {
-        self.is_valid_gvr()?;
-
-        self.cursor.seek(SeekFrom::Start(0x14))?;
-        let data_len = (self.cursor.read_u32::<LittleEndian>()? - 8)
-            .try_into()
-            .unwrap();
-
-        self.cursor.seek(SeekFrom::Start(0x1A))?;
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::layout::{GVRT_DATA_FORMAT_OFFSET, GVRT_DATA_OFFSET, GVRT_OFFSET};
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8 * 32, y as u8 * 32, 0, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_raw_format_byte(0x0F);
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    ///
+    /// assert_eq!(encoded[GVRT_OFFSET + GVRT_DATA_FORMAT_OFFSET], 0x0F);
+    /// let data = DataFormat::Rgb565.encoder().unwrap().encode(&image);
+    /// assert_eq!(encoded[GVRT_DATA_OFFSET..], data);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_raw_format_byte(mut self, value: u8) -> Self {
+        self.raw_format_byte = Some(value);
+        self
+    }
 
-        let flags = self.cursor.read_u8()?;
-        let Some(data_flags) = DataFlags::from_bits(flags & 0xF) else {
-            return Err(TextureDecodeError::InvalidFile);
-        };
-        let Ok(palette_format) = PixelFormat::try_from((flags >> 4) & 0xF) else {
-            return Err(TextureDecodeError::InvalidFile);
-        };
+    /// Resizes the image to exactly `width` by `height` pixels with the given `filter` before
+    /// it's encoded, for example to give it power of two dimensions.
+    ///
+    /// The image is stretched to the new dimensions, without keeping its aspect ratio. The
+    /// headers of the encoded texture store the new dimensions, and mipmaps are generated from
+    /// the resized image. This applies to every encoding method that takes an image, but not to
+    /// [`Self::encode_indexed()`]. [`Self::encode_dds()`] decodes BC1 blocks to resize them
+    /// instead of copying them over, and [`Self::encode_with_header()`] leaves the dimensions in
+    /// its header as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::imageops::FilterType;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(100, 100, |x, y| Rgba([x as u8, y as u8, 0, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?
+    ///     .with_resize(128, 128, FilterType::Triangle)
+    ///     .with_mipmaps()?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.into_decoded()?.dimensions(), (128, 128));
+    ///
+    /// // DXT1 textures from DDS files are resized as well
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(64, 64)))?;
+    /// let dds = TextureDecoder::new_from_buffer(encoded).to_dds_bytes()?;
+    /// let mut encoder = encoder.with_resize(32, 16, FilterType::Triangle);
+    /// let decoded = gvrtex::decode_bytes(&encoder.encode_dds(&dds)?)?;
+    /// assert_eq!(decoded.dimensions(), (32, 16));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_resize(mut self, width: u32, height: u32, filter: FilterType) -> Self {
+        self.resize = Some((width, height, filter));
+        self
+    }
+
+    /// Flips the image vertically before it's encoded.
+    ///
+    /// Some engines sample textures with a flipped vertical texture coordinate, which makes
+    /// textures authored for other engines appear upside down in-game. This applies to every
+    /// encoding method, including [`Self::encode_indexed()`], where the rows of the indices are
+    /// flipped instead.
+    pub fn with_flip_v(mut self) -> Self {
+        self.flip_v = true;
+        self
+    }
+
+    /// Instructs the encoder to fail instead of silently dropping the alpha channel of the image.
+    ///
+    /// With this, encoding an image that has pixels that aren't fully opaque into a format that
+    /// can't store an alpha channel (see [`DataFormat::has_alpha()`] and
+    /// [`PixelFormat::has_alpha()`]) returns a [`TextureEncodeError::AlphaDiscarded`]. For
+    /// [`Self::encode_indexed()`], the colors of the given palette are checked instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::error::TextureEncodeError;
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), TextureEncodeError> {
+    /// let image = RgbaImage::from_pixel(8, 8, Rgba([0xFF, 0x80, 0x00, 0x80]));
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_strict_alpha();
+    /// let result = encoder.encode_image(DynamicImage::ImageRgba8(image));
+    /// assert!(matches!(result, Err(TextureEncodeError::AlphaDiscarded)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_strict_alpha(mut self) -> Self {
+        self.strict_alpha = true;
+        self
+    }
+
+    /// Rounds images with 16 bits per channel straight to the bit depth of the data format,
+    /// instead of reducing them to 8 bits per channel first.
+    ///
+    /// By default, 16-bit images are rounded to 8 bits per channel, which the encoders then
+    /// truncate to the bit depth of the data format. Rounding twice can end up a whole level off
+    /// in the formats with fewer bits per channel, such as [`DataFormat::Rgb565`]. This has no
+    /// effect on images with 8 bits per channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{decode_bytes, TextureEncoder};
+    /// use image::{DynamicImage, ImageBuffer, Rgba};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = ImageBuffer::from_pixel(8, 8, Rgba([1268u16, 1268, 1268, 65535]));
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba16(image.clone()))?;
+    /// assert_eq!(decode_bytes(&encoded)?.get_pixel(0, 0), &Rgba([0, 4, 0, 255]));
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_16bit_rounding();
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba16(image))?;
+    /// assert_eq!(decode_bytes(&encoded)?.get_pixel(0, 0), &Rgba([8, 4, 8, 255]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_16bit_rounding(mut self) -> Self {
+        self.round_16bit = true;
+        self
+    }
+
+    /// Treats the colors of the images given to the encoder as having premultiplied alpha,
+    /// turning them back into straight alpha before encoding, as that's what GVR textures store.
+    ///
+    /// Encoding premultiplied colors as they are makes the partially transparent pixels darker
+    /// than they should be, which shows up as dark fringes around transparent edges. Fully
+    /// transparent pixels are left as they are, as there's no color to recover from them. This
+    /// applies to every encoding method that's given an image, but not to the color palette given
+    /// to [`Self::encode_indexed()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{decode_bytes, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // A red sprite fading out at its right edge, with premultiplied alpha
+    /// let image = RgbaImage::from_fn(8, 8, |x, _| match x {
+    ///     0..6 => Rgba([0xFF, 0x00, 0x00, 0xFF]),
+    ///     6 => Rgba([0x80, 0x00, 0x00, 0x80]),
+    ///     _ => Rgba([0x00, 0x00, 0x00, 0x00]),
+    /// });
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb5a3)?;
+    /// let decoded = decode_bytes(&encoder.encode_image(image.clone())?)?;
+    /// assert_eq!(decoded.get_pixel(6, 0), &Rgba([0x88, 0x00, 0x00, 0x92]));
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb5a3)?.with_premultiplied_alpha();
+    /// let decoded = decode_bytes(&encoder.encode_image(image)?)?;
+    /// assert_eq!(decoded.get_pixel(5, 0), &Rgba([0xFF, 0x00, 0x00, 0xFF]));
+    /// assert_eq!(decoded.get_pixel(6, 0), &Rgba([0xFF, 0x00, 0x00, 0x92]));
+    /// assert_eq!(decoded.get_pixel(7, 0), &Rgba([0x00, 0x00, 0x00, 0x00]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_premultiplied_alpha(mut self) -> Self {
+        self.premultiplied_alpha = true;
+        self
+    }
+
+    /// Sets how the mipmaps enabled with [`Self::with_mipmaps()`] are generated, see
+    /// [`MipGeneration`]. By default, they're generated with [`MipGeneration::FromBase`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{MipGeneration, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(64, 64, |x, y| Rgba([x as u8 * 4, y as u8 * 4, 0, 0xFF]));
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// let mut from_base = TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_mipmaps()?;
+    /// let mut cascade = TextureEncoder::new_gcix(DataFormat::Rgb565)?
+    ///     .with_mipmaps()?
+    ///     .with_mip_generation(MipGeneration::Cascade);
+    ///
+    /// let from_base = from_base.encode_image(image.clone())?;
+    /// let cascade = cascade.encode_image(image)?;
+    /// assert_eq!(from_base.len(), cascade.len());
+    ///
+    /// // The full size image and its first mipmap are scaled down from the same image
+    /// let first_mipmap_end = 0x20 + 64 * 64 * 2 + 32 * 32 * 2;
+    /// assert_eq!(from_base[..first_mipmap_end], cascade[..first_mipmap_end]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_mip_generation(mut self, mip_generation: MipGeneration) -> Self {
+        self.mip_generation = mip_generation;
+        self
+    }
+
+    /// Instructs the encoder to preserve the alpha coverage of the full size image in the
+    /// mipmaps enabled with [`Self::with_mipmaps()`].
+    ///
+    /// Scaling an image down blurs its alpha channel, so with alpha tested textures such as
+    /// foliage or fences, fewer and fewer pixels pass the alpha test at each mipmap, making them
+    /// fade away in the distance. With this enabled, the alpha channel of each mipmap is scaled so
+    /// that the fraction of its pixels with an alpha of at least `threshold` matches the one of
+    /// the full size image. `threshold` should be the alpha reference the game tests against.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Scattered opaque pixels, covering about 1/4 of the image
+    /// let image = RgbaImage::from_fn(64, 64, |x, y| {
+    ///     let hash = (x * 0x9E37 + y * 0x85EB).wrapping_mul(0x2545_F491) >> 24;
+    ///     Rgba([0x40, 0xC0, 0x40, if hash < 0x40 { 0xFF } else { 0 }])
+    /// });
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// let coverage = |encoded: &[u8]| -> Result<f32, Box<dyn std::error::Error>> {
+    ///     let offset = 0x20 + DataFormat::Rgb5a3.data_size(64, 64, 0);
+    ///     let mip = gvrtex::decode_raw(&encoded[offset..], 32, 32, DataFormat::Rgb5a3, None)?;
+    ///     let covered = mip.pixels().filter(|p| p[3] >= 0x80).count();
+    ///     Ok(covered as f32 / (32 * 32) as f32)
+    /// };
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb5a3)?.with_mipmaps()?;
+    /// let blurred = encoder.encode_image(image.clone())?;
+    /// assert!(coverage(&blurred)? < 0.1);
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb5a3)?
+    ///     .with_mipmaps()?
+    ///     .with_alpha_coverage(0x80);
+    /// let preserved = encoder.encode_image(image)?;
+    /// assert!((coverage(&preserved)? - 0.25).abs() < 0.05);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_alpha_coverage(mut self, threshold: u8) -> Self {
+        self.alpha_coverage = Some(threshold);
+        self
+    }
+
+    /// Sharpens each mipmap enabled with [`Self::with_mipmaps()`] with an unsharp mask after it's
+    /// scaled down, bringing back some of the detail lost to the filtering.
+    ///
+    /// The difference between each mipmap and a blurred copy of it is multiplied by `amount` and
+    /// added back to the mipmap, so higher amounts give sharper mipmaps. An `amount` of 0.0
+    /// disables sharpening, which is the default. Only the colors are sharpened, the alpha
+    /// channel is left as is. The full size image is never sharpened.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(32, 32, |x, y| {
+    ///     Rgba([(x * 8) as u8, (y * 8) as u8, ((x ^ y) * 8) as u8, 0xFF])
+    /// });
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// for format in [DataFormat::Dxt1, DataFormat::Rgb565, DataFormat::Rgb5a3] {
+    ///     let mut encoder = TextureEncoder::new_gcix(format)?.with_mipmaps()?;
+    ///     let soft = encoder.encode_image(image.clone())?;
+    ///     let mut encoder = TextureEncoder::new_gcix(format)?
+    ///         .with_mipmaps()?
+    ///         .with_mipmap_sharpen(1.0);
+    ///     let sharp = encoder.encode_image(image.clone())?;
+    ///
+    ///     // Only the mipmaps after the full size image are sharpened
+    ///     let base_end = 0x20 + format.data_size(32, 32, 0);
+    ///     assert_eq!(soft.len(), sharp.len());
+    ///     assert_eq!(soft[..base_end], sharp[..base_end]);
+    ///     assert_ne!(soft[base_end..], sharp[base_end..]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_mipmap_sharpen(mut self, amount: f32) -> Self {
+        self.mipmap_sharpen = amount;
+        self
+    }
+
+    /// Sets the order the blocks of [`DataFormat::Dxt1`] textures are stored in, see
+    /// [`DxtLayout`]. By default, they're stored in the [`DxtLayout::GameCube`] order.
+    ///
+    /// Textures encoded with a different layout have to be decoded with the same layout, set
+    /// with [`TextureDecoder::with_dxt_layout()`]. Other data formats aren't affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, DxtLayout};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(16, 16, |x, y| Rgba([x as u8 * 16, y as u8 * 16, 0, 0xFF]));
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?;
+    /// let gamecube = encoder.encode_image(image.clone())?;
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?.with_dxt_layout(DxtLayout::Wii);
+    /// let wii = encoder.encode_image(image)?;
+    ///
+    /// // Both layouts store the same blocks, in a different order
+    /// assert_eq!(gamecube.len(), wii.len());
+    /// assert_ne!(gamecube, wii);
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(gamecube);
+    /// decoder.decode()?;
+    /// let gamecube = decoder.into_decoded()?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(wii).with_dxt_layout(DxtLayout::Wii);
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.into_decoded()?, gamecube);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_dxt_layout(mut self, layout: DxtLayout) -> Self {
+        self.dxt_layout = layout;
+        self
+    }
+
+    /// Reorders the channels of [`DataFormat::Argb8888`] textures, for games that expect them
+    /// in a different order than the usual A,R and G,B pairs.
+    ///
+    /// Each entry of `swizzle` picks the channel of the image (0 for red, 1 for green, 2 for blue
+    /// and 3 for alpha) that's stored in place of the red, green, blue and alpha channel
+    /// respectively. The default of `[0, 1, 2, 3]` stores every channel in its usual place, while
+    /// `[2, 1, 0, 3]` swaps red and blue. Other data formats aren't affected.
+    ///
+    /// Textures encoded with a swizzle are decoded back into the original image by giving
+    /// [`TextureDecoder::with_channel_swizzle()`] the inverse swizzle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8, y as u8, 0x80, 0xC0]));
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// // Stores the blue channel in place of the red one and the other way around
+    /// let bgra = [2, 1, 0, 3];
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Argb8888)?.with_channel_swizzle(bgra)?;
+    /// let encoded = encoder.encode_image(image.clone())?;
+    /// assert_eq!(encoded[0x20..0x24], [0xC0, 0x80, 0xC0, 0x80]); // A,B of the first two pixels
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded).with_channel_swizzle(bgra)?;
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.into_decoded()?, image.to_rgba8());
+    ///
+    /// // Swizzles that aren't their own inverse are undone with their inverse
+    /// let mut encoder =
+    ///     TextureEncoder::new_gcix(DataFormat::Argb8888)?.with_channel_swizzle([3, 0, 1, 2])?;
+    /// let encoded = encoder.encode_image(image.clone())?;
+    /// let mut decoder =
+    ///     TextureDecoder::new_from_buffer(encoded).with_channel_swizzle([1, 2, 3, 0])?;
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.into_decoded()?, image.to_rgba8());
+    ///
+    /// let encoder = TextureEncoder::new_gcix(DataFormat::Argb8888)?;
+    /// assert!(encoder.with_channel_swizzle([0, 0, 1, 2]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If `swizzle` doesn't hold every channel from 0 to 3 exactly once, a
+    /// [`TextureEncodeError::InvalidSwizzle`] is returned.
+    pub fn with_channel_swizzle(mut self, swizzle: [usize; 4]) -> Result<Self, TextureEncodeError> {
+        if !is_channel_permutation(swizzle) {
+            return Err(TextureEncodeError::InvalidSwizzle(swizzle));
+        }
+        self.channel_swizzle = Some(swizzle);
+        Ok(self)
+    }
+
+    /// Instructs the encoder to dither the colors of [`DataFormat::Rgb565`] and
+    /// [`DataFormat::Rgb5a3`] textures, which reduces the banding of smooth gradients.
+    ///
+    /// Storing colors with 4 to 6 bits per channel turns smooth gradients into visible bands of
+    /// color. With dithering, the error of storing each color is spread over the neighboring
+    /// pixels (Floyd-Steinberg dithering), so that each area of the texture averages out to the
+    /// colors of the original image. The texture gets a little noisier in exchange. Only the
+    /// color channels are dithered, the alpha channel is stored as is. Other data formats aren't
+    /// affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{imageops, DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // A gradient with 8 shades for each shade RGB565 can store
+    /// let gradient = RgbaImage::from_fn(256, 64, |x, _| Rgba([x as u8 / 4, 0x40, 0x80, 0xFF]));
+    ///
+    /// // The peak signal-to-noise ratio of the areas of 4x4 pixels of the decoded texture,
+    /// // which is what the gradient looks like from a distance
+    /// let psnr = |encoder: &mut TextureEncoder| -> Result<f64, Box<dyn std::error::Error>> {
+    ///     let encoded = encoder.encode_image(DynamicImage::ImageRgba8(gradient.clone()))?;
+    ///     let decoded = gvrtex::decode_bytes(&encoded)?;
+    ///     let filter = imageops::FilterType::Triangle;
+    ///     let original = imageops::resize(&gradient, 64, 16, filter);
+    ///     let decoded = imageops::resize(&decoded, 64, 16, filter);
+    ///     let diff = gvrtex::compare_images(&original, &decoded).unwrap();
+    ///     Ok(10.0 * (255.0 * 255.0 / diff.mean_squared_error).log10())
+    /// };
+    ///
+    /// let banded = psnr(&mut TextureEncoder::new_gcix(DataFormat::Rgb565)?)?;
+    /// let dithered = psnr(&mut TextureEncoder::new_gcix(DataFormat::Rgb565)?.with_color_dithering())?;
+    /// assert!(dithered > banded + 20.0, "{dithered} dB isn't clearly above {banded} dB");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_color_dithering(mut self) -> Self {
+        self.color_dithering = true;
+        self
+    }
+
+    /// Instructs the encoder to encode the texture data with the given custom `encoder`, instead
+    /// of the encoder of its data format. This is used for data formats this crate doesn't know
+    /// about, see the [`codec`] module for an example.
+    ///
+    /// The data format in the header of the encoded textures is set to `format_value`. As custom
+    /// encoders don't generate a color palette, the palette flags are removed from the encoder.
+    /// [`Self::with_strict_alpha()`] has no effect, as there's no telling whether the custom
+    /// data format stores an alpha channel.
+    pub fn with_custom_encoder(
+        mut self,
+        format_value: u8,
+        encoder: Box<dyn GvrEncoder + Send + Sync>,
+    ) -> Self {
+        self.data_flags.remove(DataFlags::Palette);
+        self.custom_encoder = Some((format_value, encoder));
+        self
+    }
+
+    /// Returns a [`TextureEncodeError::AlphaDiscarded`] if strict alpha is enabled, the encoder
+    /// can't store an alpha channel, and `is_transparent` returns true.
+    fn check_alpha(&self, is_transparent: impl FnOnce() -> bool) -> Result<(), TextureEncodeError> {
+        let has_alpha = if self.data_flags.intersects(DataFlags::Palette) {
+            self.pixel_format.has_alpha()
+        } else {
+            self.data_format.has_alpha()
+        };
+
+        let is_custom = self.custom_encoder.is_some();
+        if self.strict_alpha && !is_custom && !has_alpha && is_transparent() {
+            return Err(TextureEncodeError::AlphaDiscarded);
+        }
+        Ok(())
+    }
+
+    /// Encodes all the mipmaps for the given `img`, returning the encoded mipmaps alongside the
+    /// amount of mipmaps that were encoded.
+    ///
+    /// Each mipmap halves both dimensions of the previous one, down to 1x1. Once one dimension
+    /// reaches 1, it stays at 1 while the other one keeps halving, so non-square textures get as
+    /// many mipmaps as their bigger dimension allows for.
+    ///
+    /// The mipmaps given to [`Self::with_mipmap_images()`] are encoded instead of generated ones,
+    /// if there are any.
+    fn encode_mipmaps(
+        &self,
+        img: &RgbaImage,
+        encoder: &dyn GvrEncoder,
+    ) -> Result<(Vec<u8>, u32), TextureEncodeError> {
+        let mut mipmaps: Vec<u8> = vec![];
+        let mipmap_count = img.width().max(img.height()).ilog2();
+        if let Some(levels) = &self.mipmap_images {
+            if levels.len() != mipmap_count as usize {
+                return Err(TextureEncodeError::MipmapCount(
+                    mipmap_count as usize,
+                    levels.len(),
+                ));
+            }
+
+            for (level, mipmap) in (1..).zip(levels) {
+                let expected = (
+                    (img.width() >> level).max(1),
+                    (img.height() >> level).max(1),
+                );
+                if mipmap.dimensions() != expected {
+                    return Err(TextureEncodeError::MipmapDimensions(
+                        level,
+                        expected,
+                        mipmap.dimensions(),
+                    ));
+                }
+
+                let mut mipmap = mipmap.clone();
+                self.unpremultiply_alpha(&mut mipmap);
+                if self.flip_v {
+                    imageops::flip_vertical_in_place(&mut mipmap);
+                }
+
+                let mut encoded = encoder.encode(&self.dithered(&mipmap));
+                if encoded.len() < 32 {
+                    encoded.resize(32, self.padding_byte);
+                }
+                mipmaps.append(&mut encoded);
+            }
+            return Ok((mipmaps, mipmap_count));
+        }
+
+        let mut previous: Option<RgbaImage> = None;
+        let base_coverage = self
+            .alpha_coverage
+            .map(|threshold| (threshold, alpha_coverage(img, threshold, 1.0)));
+
+        for level in 1..=mipmap_count {
+            let tex_size_w = (img.width() >> level).max(1);
+            let tex_size_h = (img.height() >> level).max(1);
+
+            let source = match self.mip_generation {
+                MipGeneration::FromBase => img,
+                MipGeneration::Cascade => previous.as_ref().unwrap_or(img),
+            };
+            let mut mipmap = imageops::resize(source, tex_size_w, tex_size_h, FilterType::Triangle);
+            if let Some((threshold, coverage)) = base_coverage {
+                scale_alpha_to_coverage(&mut mipmap, threshold, coverage);
+            }
+
+            // The mipmaps are scaled down from the unsharpened ones, so the sharpening doesn't
+            // add up over the levels
+            let mut encoded = if self.mipmap_sharpen > 0.0 {
+                encoder.encode(&self.dithered(&sharpen(&mipmap, self.mipmap_sharpen)))
+            } else {
+                encoder.encode(&self.dithered(&mipmap))
+            };
+
+            if encoded.len() < 32 {
+                encoded.resize(32, self.padding_byte);
+            }
+
+            mipmaps.append(&mut encoded);
+            previous = Some(mipmap);
+        }
+
+        Ok((mipmaps, mipmap_count))
+    }
+
+    /// Encodes the image file given in `img_path` into a GVR texture.
+    ///
+    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    ///
+    /// # Errors
+    ///
+    /// If anything goes wrong in the encoding process, a [`TextureEncodeError`] is returned
+    /// instead.
+    pub fn encode(&mut self, img_path: &str) -> Result<Vec<u8>, TextureEncodeError> {
+        let img = ImageReader::open(img_path)?.decode()?;
+        Ok(self.encode_internal(img)?.0)
+    }
+
+    /// Encodes the image file given in `img_path` into a GVR texture, same as [`Self::encode()`].
+    ///
+    /// Alongside the in-memory representation of the file, this method also returns an
+    /// [`EncodeInfo`], which summarizes what was encoded. This is useful for logging or showing
+    /// feedback to the user.
+    ///
+    /// # Errors
+    ///
+    /// If anything goes wrong in the encoding process, a [`TextureEncodeError`] is returned
+    /// instead.
+    pub fn encode_with_info(
+        &mut self,
+        img_path: &str,
+    ) -> Result<(Vec<u8>, EncodeInfo), TextureEncodeError> {
+        let img = ImageReader::open(img_path)?.decode()?;
+        self.encode_internal(img)
+    }
+
+    /// Encodes the image file given in `img_path` into a GVR texture, same as [`Self::encode()`].
+    ///
+    /// Alongside the in-memory representation of the file, this method also returns a hash of
+    /// it, as given by [`hash_encoded()`]. Encoding the same image with the same settings always
+    /// gives the same hash, which makes it useful for caching encoded textures.
+    ///
+    /// # Errors
+    ///
+    /// If anything goes wrong in the encoding process, a [`TextureEncodeError`] is returned
+    /// instead.
+    pub fn encode_with_hash(
+        &mut self,
+        img_path: &str,
+    ) -> Result<(Vec<u8>, u64), TextureEncodeError> {
+        let encoded = self.encode(img_path)?;
+        let hash = hash_encoded(&encoded);
+        Ok((encoded, hash))
+    }
+
+    /// Encodes the image file given in the `image_buffer` into a GVR texture. The format of the
+    /// image is guessed.
+    ///
+    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    ///
+    /// # Errors
+    ///
+    /// If anything goes wrong in the encoding process, a [`TextureEncodeError`] is returned
+    /// instead.
+    pub fn encode_buffer(&mut self, image_buffer: Vec<u8>) -> Result<Vec<u8>, TextureEncodeError> {
+        let img = ImageReader::new(Cursor::new(image_buffer))
+            .with_guessed_format()?
+            .decode()?;
+        Ok(self.encode_internal(img)?.0)
+    }
+
+    /// Encodes the given in-memory `image` into a GVR texture.
+    ///
+    /// This is useful when the image doesn't come from a file, for example when it was decoded
+    /// from another texture format, or generated by your own code. Images with 16 bits per
+    /// channel are rounded to 8 bits per channel, see [`Self::with_16bit_rounding()`].
+    ///
+    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    ///
+    /// # Errors
+    ///
+    /// If anything goes wrong in the encoding process, a [`TextureEncodeError`] is returned
+    /// instead.
+    pub fn encode_image(&mut self, image: DynamicImage) -> Result<Vec<u8>, TextureEncodeError> {
+        Ok(self.encode_internal(image)?.0)
+    }
+
+    /// Encodes the given in-memory `image` into a DXT1 compressed GVR texture, same as
+    /// [`Self::encode_image()`].
+    ///
+    /// Alongside the in-memory representation of the file, this method also returns
+    /// [`DxtStats`] about the blocks of the full size image, which helps with deciding whether
+    /// [`DataFormat::Dxt1`] suits the image. Mipmaps aren't counted in the statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // The left half is transparent, the right half is a gradient, apart from a solid block
+    /// let image = RgbaImage::from_fn(16, 8, |x, y| match (x, y) {
+    ///     (0..8, _) => Rgba([0, 0, 0, 0]),
+    ///     (8..12, 0..4) => Rgba([0x40, 0x80, 0xC0, 0xFF]),
+    ///     _ => Rgba([x as u8 * 16, y as u8 * 32, 0, 0xFF]),
+    /// });
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?;
+    /// let (_, stats) = encoder.encode_dxt_with_stats(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// assert_eq!(stats.total_blocks, 8);
+    /// assert_eq!(stats.alpha_blocks, 4);
+    /// assert_eq!(stats.single_color_blocks, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the encoder wasn't instantiated with [`DataFormat::Dxt1`], or it uses a custom encoder
+    /// (see [`Self::with_custom_encoder()`]), a [`TextureEncodeError::Format`] is returned. Otherwise, the same errors as with
+    /// [`Self::encode_image()`] apply.
+    pub fn encode_dxt_with_stats(
+        &mut self,
+        image: DynamicImage,
+    ) -> Result<(Vec<u8>, DxtStats), TextureEncodeError> {
+        if self.data_format != DataFormat::Dxt1 || self.custom_encoder.is_some() {
+            return Err(TextureEncodeError::Format);
+        }
+
+        let rgba_img = self.prepare_image(image)?;
+        let encoder = DXT1Encoder {
+            alpha_threshold: self.alpha_threshold,
+            padding_byte: self.padding_byte,
+            layout: self.dxt_layout,
+        };
+        encoder.validate_input(&rgba_img)?;
+
+        let mut stats = DxtStats::default();
+        let mut encoded = encoder.encode_with_stats(&rgba_img, &mut stats);
+        if self.data_flags.intersects(DataFlags::Mipmaps) {
+            let (mut encoded_mipmaps, _) = self.encode_mipmaps(&rgba_img, &encoder)?;
+            encoded.append(&mut encoded_mipmaps);
+        }
+
+        let mut result = Vec::new();
+        self.write_header(
+            rgba_img.width(),
+            rgba_img.height(),
+            encoded.len(),
+            &mut result,
+        )?;
+        result.write_all(&encoded)?;
+
+        Ok((result, stats))
+    }
+
+    /// Encodes an image that's already split into a color `palette` and `indices` into said
+    /// palette, such as the ones given by [`TextureDecoder::palette()`] and
+    /// [`TextureDecoder::indices()`], into a GVR texture.
+    ///
+    /// Unlike the other encoding methods, the image isn't quantized again, the palette and
+    /// indices are stored exactly as they're given. This means the order of the palette and the
+    /// index values are preserved, so decoding an indexed texture and encoding it again with this
+    /// method gives back an identical texture.
+    ///
+    /// The `indices` are given in row-major order, one index per pixel. For
+    /// [`DataFormat::Index4`] textures, only the lower 4 bits of each index are used.
+    ///
+    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let gvr_path: &str = "";
+    /// let mut decoder = TextureDecoder::new(gvr_path)?;
+    /// decoder.decode()?;
+    /// let image = decoder.as_decoded().as_ref().unwrap();
+    ///
+    /// let mut encoder =
+    ///     TextureEncoder::new_gcix_palettized(PixelFormat::RGB5A3, DataFormat::Index8)?;
+    /// let encoded = encoder.encode_indexed(
+    ///     decoder.palette().unwrap(),
+    ///     decoder.indices().unwrap(),
+    ///     image.width(),
+    ///     image.height(),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the encoder wasn't instantiated with a palettized data format, a
+    /// [`TextureEncodeError::Format`] is returned. If the length of `palette` doesn't match the
+    /// palette size of the data format, or the length of `indices` doesn't match the given
+    /// dimensions, a [`TextureEncodeError::PaletteLength`] or
+    /// [`TextureEncodeError::IndicesLength`] is returned respectively.
+    pub fn encode_indexed(
+        &mut self,
+        palette: &[Rgba<u8>],
+        indices: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, TextureEncodeError> {
+        if !self.data_flags.intersects(DataFlags::Palette) {
+            return Err(TextureEncodeError::Format);
+        }
+
+        let colors = palette_size(self.data_format) as usize;
+        if palette.len() != colors {
+            return Err(TextureEncodeError::PaletteLength(colors, palette.len()));
+        }
+
+        let pixels = width as usize * height as usize;
+        if indices.len() != pixels {
+            return Err(TextureEncodeError::IndicesLength(pixels, indices.len()));
+        }
+        self.check_alpha(|| palette.iter().any(|color| color.0[3] != 0xFF))?;
+
+        let flipped;
+        let indices = if self.flip_v {
+            flipped = flip_indices_vertical(indices, width);
+            &flipped
+        } else {
+            indices
+        };
+
+        let encoder = create_new_encoder_with_palette(self.data_format);
+        encoder.validate_dimensions(width, height)?;
+        let mut encoded =
+            encoder.encode_indexed(palette, indices, width, height, self.pixel_format);
+
+        // The color palette is stored in a separate file, so only the indices are kept
+        if self.data_flags.intersects(DataFlags::ExternalPalette) {
+            encoded.drain(..colors * size_of::<u16>());
+        }
+
+        let mut result = Vec::new();
+        self.write_header(width, height, encoded.len(), &mut result)?;
+        result.write_all(&encoded)?;
+
+        Ok(result)
+    }
+
+    /// Encodes the given DDS file into a GVR texture.
+    ///
+    /// If the DDS file holds BC1 (DXT1) blocks and the encoder uses [`DataFormat::Dxt1`], the
+    /// blocks are copied over as is, only reordering them into the tiled layout of the GameCube,
+    /// unless [`Self::with_resize()`] or [`Self::with_premultiplied_alpha()`] change the pixels.
+    /// This keeps the quality of the encoder that made the DDS file, as the blocks are never
+    /// compressed again. With [`Self::with_mipmaps()`], the mipmaps of the DDS file are copied
+    /// over as well, if it has all of them. Otherwise, the mipmaps are encoded from the full size
+    /// image like they normally are.
+    ///
+    /// The blocks are flipped by [`Self::with_flip_v()`] without decoding them as well, unless the
+    /// height of the image, or of a mipmap that's copied over, is above 4 and not a multiple of 4.
+    /// The rows of pixels would have to move across blocks to flip those, so they're decoded.
+    ///
+    /// Any other combination is decoded to an image, and goes through the same encoding as
+    /// [`Self::encode_image()`]. Besides BC1 blocks, uncompressed 24-bit and 32-bit RGB pixels are
+    /// supported.
+    ///
+    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    ///
+    /// # Errors
+    ///
+    /// If `dds_bytes` isn't a valid DDS file, or it uses a format that isn't supported, a
+    /// [`TextureEncodeError::Encode`] is returned. If the dimensions in its header are too large
+    /// for the size of its pixels to be represented, a [`TextureEncodeError::DimensionsTooLarge`]
+    /// is returned. Otherwise, the same errors as with [`Self::encode_image()`] apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::error::TextureEncodeError;
+    /// use gvrtex::formats::{DataFormat, TextureType};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image =
+    ///     RgbaImage::from_fn(24, 16, |x, y| Rgba([x as u8 * 10, y as u8 * 15, 0x80, 0xFF]));
+    ///
+    /// // The BC1 blocks of a DXT1 texture survive a round trip through a DDS file unchanged
+    /// let mut encoder = TextureEncoder::new(TextureType::Gcix, DataFormat::Dxt1)?;
+    /// let gvr = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    /// let dds = TextureDecoder::new_from_buffer(gvr.clone()).to_dds_bytes()?;
+    /// assert_eq!(&dds[84..88], b"DXT1");
+    /// assert_eq!(encoder.encode_dds(&dds)?, gvr);
+    ///
+    /// // Mipmaps are copied over as well. Blocks that only pad small mipmaps out to a full tile
+    /// // aren't stored in DDS files, so the round trip is checked from the DDS side.
+    /// let mut encoder = TextureEncoder::new(TextureType::Gcix, DataFormat::Dxt1)?.with_mipmaps()?;
+    /// let gvr = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    /// let dds = TextureDecoder::new_from_buffer(gvr).to_dds_bytes()?;
+    /// let gvr = encoder.encode_dds(&dds)?;
+    /// assert_eq!(TextureDecoder::new_from_buffer(gvr).to_dds_bytes()?, dds);
+    ///
+    /// // The 4x6 mipmap of a 8x12 image can't be flipped within its blocks, so the flipped
+    /// // texture is encoded from the pixels instead
+    /// let small = RgbaImage::from_fn(8, 12, |x, y| {
+    ///     Rgba([x as u8 * 0x20, y as u8 * 0x15, 0, 0xFF])
+    /// });
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Dxt1)?.with_mipmaps()?;
+    /// let gvr = encoder.encode_image(DynamicImage::ImageRgba8(small))?;
+    /// let dds = TextureDecoder::new_from_buffer(gvr.clone()).to_dds_bytes()?;
+    /// let mut decoder = TextureDecoder::new_from_buffer(gvr);
+    /// decoder.decode()?;
+    /// let mut encoder = encoder.with_flip_v();
+    /// let from_pixels = encoder.encode_image(decoder.into_decoded()?.into())?;
+    /// assert_eq!(encoder.encode_dds(&dds)?, from_pixels);
+    ///
+    /// // Other formats go through uncompressed pixels
+    /// let mut encoder = TextureEncoder::new(TextureType::Gcix, DataFormat::Argb8888)?;
+    /// let gvr = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    /// let dds = TextureDecoder::new_from_buffer(gvr.clone()).to_dds_bytes()?;
+    /// assert_eq!(dds.len(), 128 + 24 * 16 * 4);
+    /// assert_eq!(encoder.encode_dds(&dds)?, gvr);
+    ///
+    /// // A header claiming more pixels than fit in memory is rejected before anything is allocated
+    /// let mut header = dds[..128].to_vec();
+    /// header[12..16].copy_from_slice(&0x8000_0000u32.to_le_bytes());
+    /// header[16..20].copy_from_slice(&0x8000_0000u32.to_le_bytes());
+    /// assert!(matches!(
+    ///     encoder.encode_dds(&header),
+    ///     Err(TextureEncodeError::DimensionsTooLarge(0x8000_0000, 0x8000_0000))
+    /// ));
+    /// header[12..16].copy_from_slice(&16u32.to_le_bytes());
+    /// assert!(matches!(
+    ///     encoder.encode_dds(&header),
+    ///     Err(TextureEncodeError::Encode(_))
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encode_dds(&mut self, dds_bytes: &[u8]) -> Result<Vec<u8>, TextureEncodeError> {
+        let dds = dds::parse_dds(dds_bytes)?;
+        let blocks = match dds.data {
+            dds::DdsData::Bc1(blocks) => blocks,
+            dds::DdsData::Rgba(image) => return self.encode_image(image.into()),
+        };
+
+        let levels = dds::bc1_levels(blocks, dds.width, dds.height, dds.mipmap_count);
+        let (base_blocks, width, height) = levels[0];
+        // Resizing and unpremultiplying change the pixels, so the blocks can't be copied over.
+        // Neither can the blocks of levels that don't fill their last row of blocks be flipped
+        let changes_pixels = self.resize.is_some() || self.premultiplied_alpha;
+        let copied_levels = if self.data_flags.intersects(DataFlags::Mipmaps) {
+            &levels[..]
+        } else {
+            &levels[..1]
+        };
+        let flips_partial_blocks = self.flip_v
+            && copied_levels
+                .iter()
+                .any(|&(_, _, level_height)| !dds::can_flip_blocks(level_height));
+        if self.data_format != DataFormat::Dxt1
+            || self.custom_encoder.is_some()
+            || changes_pixels
+            || flips_partial_blocks
+        {
+            let image = dds::decode_bc1(base_blocks, width, height)?;
+            return self.encode_image(image.into());
+        }
+
+        let encoder = create_new_encoder(
+            self.data_format,
+            self.alpha_threshold,
+            self.padding_byte,
+            self.dxt_layout,
+        );
+        encoder.validate_dimensions(width, height)?;
+        let mut encoded = dds::bc1_to_gvr(base_blocks, width, height, self.flip_v, self.dxt_layout);
+
+        if self.data_flags.intersects(DataFlags::Mipmaps) {
+            // The mipmaps of GVR textures go all the way down to 1x1
+            if levels.len() as u32 > width.max(height).ilog2() && self.mipmap_images.is_none() {
+                for &(mipmap_blocks, mipmap_width, mipmap_height) in &levels[1..] {
+                    encoded.append(&mut dds::bc1_to_gvr(
+                        mipmap_blocks,
+                        mipmap_width,
+                        mipmap_height,
+                        self.flip_v,
+                        self.dxt_layout,
+                    ));
+                }
+            } else {
+                let mut image = dds::decode_bc1(base_blocks, width, height)?;
+                if self.flip_v {
+                    imageops::flip_vertical_in_place(&mut image);
+                }
+                let (mut encoded_mipmaps, _) = self.encode_mipmaps(&image, &*encoder)?;
+                encoded.append(&mut encoded_mipmaps);
+            }
+        }
+
+        let mut result = Vec::new();
+        self.write_header(width, height, encoded.len(), &mut result)?;
+        result.write_all(&encoded)?;
+
+        Ok(result)
+    }
+
+    /// Encodes the given `image` into a GVR texture with the given `header`, written as is.
+    ///
+    /// This is an expert API for producing unusual textures on purpose, such as for testing how
+    /// a game's loader deals with them. Unlike the other encoding methods, nothing in the
+    /// `header` is checked against anything else, so the resulting texture can easily be
+    /// invalid. The only field that's changed is [`GvrHeader::data_len`], which is set to the
+    /// length of the encoded data. To write a different length, write the header again over the
+    /// start of the result with [`GvrHeader::write()`].
+    ///
+    /// The `image` is encoded in the data format of the `header`, using the pixel format of the
+    /// `header` for the color palette of palettized formats. The palette is left out if the
+    /// [`DataFlags::ExternalPalette`] flag is set. No mipmaps are encoded, regardless of the
+    /// flags. The settings of the encoder affecting the pixel data, such as
+    /// [`Self::with_flip_v()`], [`Self::with_channel_swizzle()`] and
+    /// [`Self::with_color_dithering()`], still apply, while the formats, flags and other header
+    /// fields the encoder was set up with are ignored. The image is resized with
+    /// [`Self::with_resize()`] as well, but the dimensions in the `header` are left as they are.
+    ///
+    /// This method returns an in-memory representation of the file as a [`Vec`] of bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+    /// use gvrtex::{GvrHeader, TextureEncoder};
+    /// use image::imageops::FilterType;
+    /// use image::{Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // A texture claiming to have mipmaps, without any mipmaps in it
+    /// let header = GvrHeader {
+    ///     texture_type: TextureType::Gbix,
+    ///     global_index: 0,
+    ///     index_padding: vec![0; 4],
+    ///     pixel_format: PixelFormat::RGB5A3,
+    ///     data_format: DataFormat::Rgb565,
+    ///     flags: DataFlags::Mipmaps,
+    ///     width: 8,
+    ///     height: 8,
+    ///     data_len: 0,
+    ///     reserved: 0xBEEF,
+    /// };
+    ///
+    /// let encoder = TextureEncoder::new_gbix(DataFormat::Argb8888)?;
+    /// let encoded = encoder.encode_with_header(header.clone(), &RgbaImage::new(8, 8))?;
+    ///
+    /// let written = GvrHeader::read(&mut encoded.as_slice())?;
+    /// assert_eq!(written, GvrHeader { data_len: 8 * 8 * 2, ..header });
+    /// assert_eq!(encoded.len(), 0x20 + 8 * 8 * 2);
+    ///
+    /// // The channel swizzle applies to ARGB8888 headers
+    /// let data_format = DataFormat::Argb8888;
+    /// let header = GvrHeader { data_format, flags: DataFlags::None, ..written };
+    /// let image = RgbaImage::from_pixel(8, 8, Rgba([0x10, 0x20, 0x30, 0x40]));
+    /// let encoder = encoder.with_channel_swizzle([3, 0, 1, 2])?;
+    /// let encoded = encoder.encode_with_header(header.clone(), &image)?;
+    /// let decoded = gvrtex::decode_bytes(&encoded)?;
+    /// assert_eq!(decoded.get_pixel(0, 0), &Rgba([0x40, 0x10, 0x20, 0x30]));
+    ///
+    /// // So does dithering, for RGB565 and RGB5A3 headers
+    /// let header = GvrHeader { data_format: DataFormat::Rgb565, ..header };
+    /// let image = RgbaImage::from_pixel(8, 8, Rgba([0x84, 0x84, 0x84, 0xFF]));
+    /// let encoder = TextureEncoder::new_gbix(DataFormat::Argb8888)?.with_color_dithering();
+    /// let decoded = gvrtex::decode_bytes(&encoder.encode_with_header(header.clone(), &image)?)?;
+    /// assert!(decoded.pixels().any(|p| p != decoded.get_pixel(0, 0)));
+    ///
+    /// // The image is resized, while the header keeps its dimensions
+    /// let encoder = TextureEncoder::new_gbix(DataFormat::Argb8888)?
+    ///     .with_resize(16, 16, FilterType::Nearest);
+    /// let encoded = encoder.encode_with_header(header, &image)?;
+    /// assert_eq!(GvrHeader::read(&mut encoded.as_slice())?.width, 8);
+    /// assert_eq!(encoded.len(), 0x20 + 16 * 16 * 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the dimensions of the `image` aren't supported by the data format of the `header`, a
+    /// [`TextureEncodeError::InvalidDimensions`] is returned. If the encoded data is too long for
+    /// its length to be stored in the header, a [`TextureEncodeError::DimensionsTooLarge`] is
+    /// returned. Otherwise, the same errors as with [`Self::encode_image()`] apply.
+    pub fn encode_with_header(
+        &self,
+        mut header: GvrHeader,
+        image: &RgbaImage,
+    ) -> Result<Vec<u8>, TextureEncodeError> {
+        let mut image = match self.resize {
+            Some((width, height, filter)) => imageops::resize(image, width, height, filter),
+            None => image.clone(),
+        };
+        self.unpremultiply_alpha(&mut image);
+        if self.flip_v {
+            imageops::flip_vertical_in_place(&mut image);
+        }
+        if let Some(swizzle) = self.channel_swizzle {
+            if header.data_format == DataFormat::Argb8888 {
+                swizzle_channels(&mut image, swizzle);
+            }
+        }
+        let image = &*self.dithered_as(&image, header.data_format);
+
+        let encoded = if header.data_format.requires_palette() {
+            let encoder = create_new_encoder_with_palette(header.data_format);
+            encoder.validate_input(image)?;
+            let mut encoded = encoder.encode(image, header.pixel_format)?;
+
+            if header.flags.intersects(DataFlags::ExternalPalette) {
+                let palette_len = palette_size(header.data_format) as usize * size_of::<u16>();
+                encoded.drain(..palette_len);
+            }
+            encoded
+        } else {
+            let encoder = create_new_encoder(
+                header.data_format,
+                self.alpha_threshold,
+                self.padding_byte,
+                self.dxt_layout,
+            );
+            encoder.validate_input(image)?;
+            encoder.encode(image)
+        };
+
+        // The GVRT header stores the length of the data plus the 8 bytes of header after it
+        header.data_len = u32::try_from(encoded.len())
+            .ok()
+            .filter(|len| len.checked_add(8).is_some())
+            .ok_or(TextureEncodeError::DimensionsTooLarge(
+                image.width(),
+                image.height(),
+            ))?;
+
+        let mut result = Vec::new();
+        header.write(&mut result)?;
+        result.write_all(&encoded)?;
+
+        Ok(result)
+    }
+
+    /// Turns the colors of the `image` into straight alpha, if the encoder was set up with
+    /// [`Self::with_premultiplied_alpha()`].
+    fn unpremultiply_alpha(&self, image: &mut RgbaImage) {
+        if self.premultiplied_alpha {
+            for p in image.pixels_mut() {
+                p.0 = pixel::unpremultiply_alpha(p.0);
+            }
+        }
+    }
+
+    fn encode_internal(
+        &mut self,
+        img: DynamicImage,
+    ) -> Result<(Vec<u8>, EncodeInfo), TextureEncodeError> {
+        let rgba_img = self.prepare_image(img)?;
+        self.encode_prepared(&rgba_img)
+    }
+
+    /// Encodes an image that has already gone through [`Self::prepare_image()`].
+    fn encode_prepared(
+        &self,
+        rgba_img: &RgbaImage,
+    ) -> Result<(Vec<u8>, EncodeInfo), TextureEncodeError> {
+        let mut result = Vec::new();
+
+        let mut encoded;
+        let mut mipmap_levels = 0;
+        let mut palette_colors = None;
+        if self.data_flags.intersects(DataFlags::Palette) {
+            let encoder = create_new_encoder_with_palette(self.data_format);
+            encoder.validate_input(rgba_img)?;
+            encoded = encoder.encode(rgba_img, self.pixel_format)?;
+            palette_colors = Some(palette_size(self.data_format));
+
+            // The color palette is stored in a separate file, so only the indices are kept
+            if self.data_flags.intersects(DataFlags::ExternalPalette) {
+                let palette_len = palette_size(self.data_format) as usize * size_of::<u16>();
+                encoded.drain(..palette_len);
+            }
+        } else {
+            let built_in;
+            let encoder: &dyn GvrEncoder = match &self.custom_encoder {
+                Some((_, encoder)) => encoder.as_ref(),
+                None => {
+                    built_in = create_new_encoder(
+                        self.data_format,
+                        self.alpha_threshold,
+                        self.padding_byte,
+                        self.dxt_layout,
+                    );
+                    built_in.as_ref()
+                }
+            };
+            encoder.validate_input(rgba_img)?;
+            encoded = encoder.encode(&self.dithered(rgba_img));
+
+            if self.data_flags.intersects(DataFlags::Mipmaps) {
+                let (mut encoded_mipmaps, levels) = self.encode_mipmaps(rgba_img, encoder)?;
+                encoded.append(&mut encoded_mipmaps);
+                mipmap_levels = levels;
+            }
+        }
+
+        self.write_header(
+            rgba_img.width(),
+            rgba_img.height(),
+            encoded.len(),
+            &mut result,
+        )?;
+        result.write_all(&encoded)?;
+
+        let info = EncodeInfo {
+            data_format: self.data_format,
+            width: rgba_img.width(),
+            height: rgba_img.height(),
+            mipmap_levels,
+            palette_colors,
+            total_bytes: result.len(),
+        };
+
+        Ok((result, info))
+    }
+
+    /// Encodes the given in-memory `image` into a GVR texture like [`Self::encode_image()`],
+    /// writing the texture to `writer` as it's encoded instead of returning it.
+    ///
+    /// The image is encoded one row of blocks at a time, and each row is written out before the
+    /// next one is encoded, so the encoded texture is never held in memory as a whole. This keeps
+    /// the memory use down when encoding very big textures. Mipmaps are encoded in memory before
+    /// the image, as they're only a third of the size of the image data at most.
+    ///
+    /// Palettized data formats need a color palette made out of the whole image, and custom
+    /// encoders may not encode the image in rows of blocks, so their textures are encoded in
+    /// memory before being written out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::TextureEncoder;
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(256, 136, |x, y| {
+    ///     Rgba([x as u8, y as u8, (x ^ y) as u8, (x + y) as u8])
+    /// });
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// for data_format in DataFormat::ALL {
+    ///     let mut encoder = if data_format.requires_palette() {
+    ///         TextureEncoder::new_gcix_palettized(Default::default(), data_format)?
+    ///     } else if data_format.supports_mipmaps() {
+    ///         TextureEncoder::new_gcix(data_format)?.with_mipmaps()?
+    ///     } else {
+    ///         TextureEncoder::new_gcix(data_format)?
+    ///     };
+    ///
+    ///     let mut streamed = Vec::new();
+    ///     encoder.encode_image_to(image.clone(), &mut streamed)?;
+    ///     assert_eq!(streamed, encoder.encode_image(image.clone())?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Encoders created with [`Self::new_auto_lossless()`] pick the data format before streaming,
+    /// so an image with few enough colors is streamed as a palettized texture:
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, TextureType};
+    /// use gvrtex::pixel::decode_rgb565;
+    /// use gvrtex::{GvrHeader, TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // 128 colors that RGB565 stores exactly
+    /// let image = RgbaImage::from_fn(16, 8, |x, y| {
+    ///     Rgba(decode_rgb565((y * 16 + x) as u16 * 500))
+    /// });
+    /// let mut encoder = TextureEncoder::new_auto_lossless(TextureType::Gcix);
+    /// let mut streamed = Vec::new();
+    /// encoder.encode_image_to(DynamicImage::ImageRgba8(image.clone()), &mut streamed)?;
+    /// assert_eq!(GvrHeader::read(&mut streamed.as_slice())?.data_format, DataFormat::Index8);
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(streamed);
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.into_decoded()?, image);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If anything goes wrong in the encoding process or while writing to `writer`, a
+    /// [`TextureEncodeError`] is returned instead. Part of the texture may have been written to
+    /// `writer` by then.
+    pub fn encode_image_to<W: Write>(
+        &mut self,
+        image: DynamicImage,
+        writer: &mut W,
+    ) -> Result<(), TextureEncodeError> {
+        // The data format of encoders created with Self::new_auto_lossless() is only known once
+        // the image has been prepared
+        let rgba_img = self.prepare_image(image)?;
+        if self.data_flags.intersects(DataFlags::Palette) || self.custom_encoder.is_some() {
+            writer.write_all(&self.encode_prepared(&rgba_img)?.0)?;
+            return Ok(());
+        }
+
+        let (width, height) = rgba_img.dimensions();
+        let encoder = create_new_encoder(
+            self.data_format,
+            self.alpha_threshold,
+            self.padding_byte,
+            self.dxt_layout,
+        );
+        encoder.validate_input(&rgba_img)?;
+
+        let mipmaps = if self.data_flags.intersects(DataFlags::Mipmaps) {
+            self.encode_mipmaps(&rgba_img, &*encoder)?.0
+        } else {
+            Vec::new()
+        };
+        let data_len = self.data_format.image_size(width, height) + mipmaps.len();
+        self.write_header(width, height, data_len, writer)?;
+
+        // The image data of each row of blocks is laid out the same way as a texture of that
+        // height, so each row is encoded as its own image. The dithering carries over from one
+        // row to the next, so the whole image is dithered first
+        let dithered = self.dithered(&rgba_img);
+        let (_, block_height) = self.data_format.block_size();
+        for y in (0..height).step_by(block_height as usize) {
+            let band_height = block_height.min(height - y);
+            let band = imageops::crop_imm(&*dithered, 0, y, width, band_height).to_image();
+            writer.write_all(&encoder.encode(&band))?;
+        }
+        writer.write_all(&mipmaps)?;
+
+        Ok(())
+    }
+
+    /// Converts the given `img` into the image that gets encoded, applying the options of the
+    /// encoder that change the pixels of the image.
+    ///
+    /// For encoders created with [`Self::new_auto_lossless()`], this also picks their data format.
+    fn prepare_image(&mut self, img: DynamicImage) -> Result<RgbaImage, TextureEncodeError> {
+        let img = match self.resize {
+            Some((width, height, filter)) => img.resize_exact(width, height, filter),
+            None => img,
+        };
+        let is_16bit = matches!(
+            img,
+            DynamicImage::ImageLuma16(_)
+                | DynamicImage::ImageLumaA16(_)
+                | DynamicImage::ImageRgb16(_)
+                | DynamicImage::ImageRgba16(_)
+        );
+        let source_16bit = (self.round_16bit && is_16bit).then(|| {
+            let mut source = img.to_rgba16();
+            if self.premultiplied_alpha {
+                rounding::unpremultiply_alpha_16bit(&mut source);
+            }
+            source
+        });
+        let mut rgba_img = img.into_rgba8();
+        self.unpremultiply_alpha(&mut rgba_img);
+        if self.auto_lossless {
+            self.pick_lossless_formats(&rgba_img);
+        }
+        if let Some(source) = source_16bit {
+            rgba_img = rounding::to_rgba8_rounded(&source, self.data_format);
+        }
+        self.check_alpha(|| rgba_img.pixels().any(|p| p.0[3] != 0xFF))?;
+        if self.flip_v {
+            imageops::flip_vertical_in_place(&mut rgba_img);
+        }
+        if let Some(swizzle) = self.channel_swizzle {
+            if self.data_format == DataFormat::Argb8888 && self.custom_encoder.is_none() {
+                swizzle_channels(&mut rgba_img, swizzle);
+            }
+        }
+
+        Ok(rgba_img)
+    }
+
+    /// Returns `image` with its colors dithered if [`Self::with_color_dithering()`] applies to
+    /// the data format of the encoder.
+    fn dithered<'a>(&self, image: &'a RgbaImage) -> Cow<'a, RgbaImage> {
+        if self.custom_encoder.is_some() {
+            return Cow::Borrowed(image);
+        }
+        self.dithered_as(image, self.data_format)
+    }
+
+    /// Returns `image` with its colors dithered if [`Self::with_color_dithering()`] applies to
+    /// `data_format`, regardless of the data format and custom encoder of the encoder.
+    fn dithered_as<'a>(&self, image: &'a RgbaImage, data_format: DataFormat) -> Cow<'a, RgbaImage> {
+        if !self.color_dithering {
+            return Cow::Borrowed(image);
+        }
+
+        match data_format {
+            DataFormat::Rgb565 => Cow::Owned(dither(image, |p| {
+                pixel::decode_rgb565(pixel::encode_rgb565(p))
+            })),
+            DataFormat::Rgb5a3 => Cow::Owned(dither(image, |p| {
+                pixel::decode_rgb5a3(pixel::encode_rgb5a3(p))
+            })),
+            _ => Cow::Borrowed(image),
+        }
+    }
+
+    fn write_header<W: Write>(
+        &self,
+        width: u32,
+        height: u32,
+        data_len: usize,
+        writer: &mut W,
+    ) -> Result<(), TextureEncodeError> {
+        // The GVRT header stores the length of the data plus the 8 bytes of header after it
+        let too_large = || TextureEncodeError::DimensionsTooLarge(width, height);
+        let data_len = u32::try_from(data_len)
+            .ok()
+            .filter(|len| len.checked_add(8).is_some())
+            .ok_or_else(too_large)?;
+        let header = GvrHeader {
+            texture_type: self.texture_type,
+            global_index: self.global_index,
+            index_padding: vec![self.padding_byte; 4],
+            pixel_format: self.pixel_format,
+            data_format: self.data_format,
+            flags: self.data_flags,
+            width: width.try_into().map_err(|_| too_large())?,
+            height: height.try_into().map_err(|_| too_large())?,
+            data_len,
+            reserved: self.gvrt_reserved,
+        };
+        let format_value = match (self.raw_format_byte, &self.custom_encoder) {
+            (Some(format_value), _) => format_value,
+            (None, Some((format_value, _))) => *format_value,
+            (None, None) => self.data_format.into(),
+        };
+        header.write_with_format(writer, format_value)?;
+        Ok(())
+    }
+}
+
+/// How [`TextureEncoder`] generates mipmaps, set with [`TextureEncoder::with_mip_generation()`].
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MipGeneration {
+    /// Every mipmap is scaled down from the full size image.
+    ///
+    /// Every mipmap is filtered straight from the original pixels, but scaling down the full size
+    /// image over and over takes up most of the time spent encoding big textures.
+    #[default]
+    FromBase,
+    /// Every mipmap is scaled down from the previous mipmap, which is a lot faster for big
+    /// textures, as each mipmap only reads a quarter of the pixels the previous one did.
+    ///
+    /// The mipmaps are filtered a little differently from [`MipGeneration::FromBase`], as the
+    /// filtering of each mipmap carries over to the next one.
+    Cascade,
+}
+
+/// A summary of a texture encoded with [`TextureEncoder::encode_with_info()`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct EncodeInfo {
+    /// The data format the texture was encoded in.
+    pub data_format: DataFormat,
+    /// The width of the encoded texture.
+    pub width: u32,
+    /// The height of the encoded texture.
+    pub height: u32,
+    /// The amount of mipmaps encoded alongside the texture, not counting the texture itself.
+    pub mipmap_levels: u32,
+    /// The amount of colors in the color palette, if the texture was encoded with one.
+    pub palette_colors: Option<u32>,
+    /// The size of the whole encoded file in bytes, including the headers.
+    pub total_bytes: usize,
+}
+
+/// Statistics about the blocks of a texture encoded with
+/// [`TextureEncoder::encode_dxt_with_stats()`].
+///
+/// Every DXT1 block either holds 4 opaque colors, or 3 opaque colors and a transparent one. A lot
+/// of blocks that need the latter, or blocks that could only be given a single color, are a sign
+/// that another data format might fit the texture better.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DxtStats {
+    /// The amount of blocks encoded.
+    pub total_blocks: u32,
+    /// The amount of blocks with transparent pixels, which are encoded with only 3 colors.
+    pub alpha_blocks: u32,
+    /// The amount of blocks where both colors of the block ended up the same after being reduced
+    /// to RGB565, so the block could only hold a single color.
+    pub single_color_blocks: u32,
+}
+
+/// Provides all the functionality needed to decode a GVR texture file.
+///
+/// When the file is decoded using [`Self::decode()`], the image is not given to you from that
+/// method. You can retrieve it via [`Self::as_decoded()`] or [`Self::into_decoded()`], or if you
+/// don't want an in-memory representation of the file, you can immediately save the file via
+/// [`Self::save()`].
+///
+/// By default the whole texture file is kept in memory. If you'd rather have the pixel data be
+/// read incrementally from a file or some other stream, see [`Self::new_from_reader()`].
+///
+/// For examples, see the documentation on the root of the [`crate`]
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct TextureDecoder<R = Cursor<Vec<u8>>> {
+    reader: R,
+    offset: u64,
+    texture_type: Option<TextureType>,
+    data_format: Option<DataFormat>,
+    pixel_format: Option<PixelFormat>,
+    data_flags: Option<DataFlags>,
+    palette: Option<Vec<Rgba<u8>>>,
+    indices: Option<Vec<u8>>,
+    external_palette: Option<Vec<Rgba<u8>>>,
+    custom_decoders: HashMap<u8, Box<dyn GvrDecoder + Send + Sync>>,
+    flip_v: bool,
+    relaxed_magic: bool,
+    premultiplied_alpha: bool,
+    dxt_layout: DxtLayout,
+    channel_swizzle: Option<[usize; 4]>,
+    image: Option<RgbaImage>,
+}
+
+#[cfg(feature = "std")]
+impl TextureDecoder {
+    /// Instantiate a new [`TextureDecoder`], that can decode the file in the given `gvr_path`,
+    /// reading the file's contents.
+    ///
+    /// This function doesn't decode the file by itself, [`Self::decode()`] must be called.
+    ///
+    /// # Errors
+    ///
+    /// An IO error will be returned if the given `gvr_path` is invalid in any way.
+    pub fn new(gvr_path: &str) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            reader: Cursor::new(std::fs::read(gvr_path)?),
+            ..Default::default()
+        })
+    }
+
+    /// Instantiate a new [`TextureDecoder`], that can decode the texture in the given `buffer`.
+    ///
+    /// This function doesn't decode the file by itself, [`Self::decode()`] must be called.
+    ///
+    /// # Notes
+    ///
+    /// The start of the `buffer` should properly point to the start of the texture.
+    ///
+    /// The length of `buffer` should be large enough to contain the whole texture. It can be
+    /// bigger than the actual texture data, the `buffer` will only be read up until the full
+    /// length of the texture.
+    pub fn new_from_buffer(buffer: Vec<u8>) -> Self {
+        Self {
+            reader: Cursor::new(buffer),
+            ..Default::default()
+        }
+    }
+
+    /// Instantiate a new [`TextureDecoder`], that can decode a texture embedded somewhere inside
+    /// the given `buffer`, for example a texture stored inside of a larger archive file.
+    ///
+    /// The `offset` should point to the start of the texture in `buffer`, meaning the start of
+    /// the GCIX/GBIX header (or the GVRT header, if the texture has no GCIX/GBIX header). Any
+    /// data before `offset` or after the end of the texture is ignored.
+    ///
+    /// This function doesn't decode the file by itself, [`Self::decode()`] must be called.
+    pub fn new_at_offset(buffer: Vec<u8>, offset: u64) -> Self {
+        Self {
+            reader: Cursor::new(buffer),
+            offset,
+            ..Default::default()
+        }
+    }
+
+    /// Instantiate a new [`TextureDecoder`], that can decode a texture embedded somewhere inside
+    /// the file in the given `path`, reading the file's contents.
+    ///
+    /// See [`Self::new_at_offset()`] for how the `offset` is used.
+    ///
+    /// This function doesn't decode the file by itself, [`Self::decode()`] must be called.
+    ///
+    /// # Errors
+    ///
+    /// An IO error will be returned if the given `path` is invalid in any way.
+    pub fn new_from_path_at_offset(path: &str, offset: u64) -> Result<Self, std::io::Error> {
+        Ok(Self::new_at_offset(std::fs::read(path)?, offset))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + Seek> TextureDecoder<R> {
+    /// Instantiate a new [`TextureDecoder`], that decodes the texture from the given `reader`.
+    ///
+    /// Unlike [`Self::new()`], the file isn't read into memory up front. When [`Self::decode()`]
+    /// is called, only the header is read at first, after which the pixel data is consumed
+    /// block by block while the decoded image is being written. This keeps the peak memory usage
+    /// down to roughly the size of the decoded image, which matters for large textures.
+    ///
+    /// As the pixel data is read in small chunks, it's recommended to wrap unbuffered readers
+    /// (like [`std::fs::File`]) in a [`std::io::BufReader`].
+    ///
+    /// This function doesn't decode the file by itself, [`Self::decode()`] must be called.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use gvrtex::error::TextureDecodeError;
+    /// use gvrtex::TextureDecoder;
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// # fn main() -> Result<(), TextureDecodeError> {
+    /// # let gvr_path: &str = "";
+    /// let reader = BufReader::new(File::open(gvr_path)?);
+    /// let mut decoder = TextureDecoder::new_from_reader(reader);
+    /// decoder.decode()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Notes
+    ///
+    /// The texture should start at the very beginning of the stream held by `reader`. Any data
+    /// after the end of the texture is ignored.
+    pub fn new_from_reader(reader: R) -> Self {
+        Self {
+            reader,
+            offset: 0,
+            texture_type: None,
+            data_format: None,
+            pixel_format: None,
+            data_flags: None,
+            palette: None,
+            indices: None,
+            external_palette: None,
+            custom_decoders: HashMap::new(),
+            flip_v: false,
+            relaxed_magic: false,
+            premultiplied_alpha: false,
+            dxt_layout: DxtLayout::default(),
+            channel_swizzle: None,
+            image: None,
+        }
+    }
+
+    /// Decodes the given image from [`Self::new()`].
+    ///
+    /// With the `rayon` feature enabled, textures without a color palette are decoded on multiple
+    /// threads, a few rows of blocks at a time. The decoded image is the same either way.
+    ///
+    /// # Examples
+    ///
+    /// Decoding gives the same image as decoding the texture data with [`crate::decode_raw()`],
+    /// which always decodes on a single thread:
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use gvrtex::{GvrHeader, TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(72, 40, |x, y| {
+    ///     let value = (x * 7 + y * 13) as u8;
+    ///     Rgba([value, value.wrapping_mul(3), x as u8 * 3, (x + y) as u8 % 4 * 0x55])
+    /// });
+    ///
+    /// for data_format in DataFormat::ALL {
+    ///     let mut encoder = if data_format.requires_palette() {
+    ///         TextureEncoder::new_gcix_palettized(PixelFormat::RGB5A3, data_format)?
+    ///     } else {
+    ///         TextureEncoder::new_gcix(data_format)?
+    ///     };
+    ///     let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    ///
+    ///     let mut decoder = TextureDecoder::new_from_buffer(encoded.clone());
+    ///     decoder.decode()?;
+    ///
+    ///     let header = GvrHeader::read(&mut encoded.as_slice())?;
+    ///     let data = &encoded[header.size()..];
+    ///     let expected = gvrtex::decode_raw(data, 72, 40, data_format, Some(PixelFormat::RGB5A3))?;
+    ///     assert_eq!(decoder.into_decoded()?, expected, "{data_format}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Textures that are cut short give an error instead of a partially decoded image:
+    ///
+    /// ```
+    /// use gvrtex::error::TextureDecodeError;
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// for data_format in [DataFormat::Index4, DataFormat::Index8] {
+    ///     let mut encoder = TextureEncoder::new_gcix_palettized(PixelFormat::RGB5A3, data_format)?;
+    ///     let mut encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(8, 8)))?;
+    ///     encoded.pop();
+    ///
+    ///     let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    ///     let err = decoder.decode().unwrap_err();
+    ///     assert!(matches!(err, TextureDecodeError::SizeMismatch { .. }));
+    ///     assert!(decoder.as_decoded().is_none());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If something goes wrong while decoding, or the given file is not a valid GVR texture file,
+    /// a [`TextureDecodeError`] is returned.
+    pub fn decode(&mut self) -> Result<(), TextureDecodeError> {
+        self.image = Some(self.decode_internal(None)?.into_rgba8());
+        Ok(())
+    }
+
+    /// Returns the decoded image in the color type that best fits the data format of the texture,
+    /// consuming `self`.
+    ///
+    /// The intensity formats ([`DataFormat::Intensity4`] and [`DataFormat::Intensity8`]) are
+    /// returned as [`DynamicImage::ImageLuma8`], and the intensity formats with alpha
+    /// ([`DataFormat::IntensityA4`] and [`DataFormat::IntensityA8`]) as
+    /// [`DynamicImage::ImageLumaA8`]. Every other data format is returned as
+    /// [`DynamicImage::ImageRgba8`].
+    ///
+    /// If [`Self::decode()`] has ran successfully, the already decoded image is converted.
+    /// Otherwise the texture is decoded straight into the resulting color type, which avoids
+    /// creating an intermediate RGBA image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_pixel(8, 8, Rgba([0x12, 0x34, 0x56, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Argb8888)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.into_dynamic()?, DynamicImage::ImageRgba8(image));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the texture has to be decoded and something goes wrong while doing so, a
+    /// [`TextureDecodeError`] is returned.
+    pub fn into_dynamic(mut self) -> Result<DynamicImage, TextureDecodeError> {
+        let Some(image) = self.image.take() else {
+            return self.decode_internal(None);
+        };
+
+        let image = DynamicImage::ImageRgba8(image);
+        match self.data_format {
+            Some(DataFormat::Intensity4 | DataFormat::Intensity8) => {
+                Ok(DynamicImage::ImageLuma8(image.into_luma8()))
+            }
+            Some(DataFormat::IntensityA4 | DataFormat::IntensityA8) => {
+                Ok(DynamicImage::ImageLumaA8(image.into_luma_alpha8()))
+            }
+            _ => Ok(image),
+        }
+    }
+
+    /// Decodes only the given rectangular region of the texture, returning it as a new image.
+    ///
+    /// The region starts at the `x` and `y` coordinate, and spans `width` pixels to the right and
+    /// `height` pixels down. This is useful for extracting a single sprite out of a texture atlas.
+    ///
+    /// For [`DataFormat::Dxt1`] textures, only the blocks that overlap with the region are
+    /// decoded. Other data formats are decoded fully before cropping out the region.
+    ///
+    /// Unlike [`Self::decode()`], the result isn't saved in the decoder.
+    ///
+    /// # Errors
+    ///
+    /// If the region is empty or doesn't fit inside the texture, a
+    /// [`TextureDecodeError::InvalidRegion`] is returned. Otherwise, the same errors as with
+    /// [`Self::decode()`] apply.
+    pub fn decode_region(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, TextureDecodeError> {
+        Ok(self
+            .decode_internal(Some((x, y, width, height)))?
+            .into_rgba8())
+    }
+
+    /// Decodes every texture stored back to back starting from the texture of this decoder, as
+    /// some game files pack several textures one after the other.
+    ///
+    /// Each texture is found right after the end of the previous one, using the length its GVRT
+    /// header states. Decoding stops at the end of the file, or as soon as the bytes after a
+    /// texture don't start with a GCIX, GBIX or GVRT magic string.
+    ///
+    /// Unlike [`Self::decode()`], the results aren't saved in the decoder, and the information
+    /// about the texture of this decoder (such as [`Self::palette()`]) is left as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let first = RgbaImage::from_pixel(8, 8, Rgba([0xFF, 0x00, 0x00, 0xFF]));
+    /// let second = RgbaImage::from_pixel(16, 4, Rgba([0x00, 0x00, 0xFF, 0xFF]));
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+    /// let mut archive = encoder.encode_image(DynamicImage::ImageRgba8(first.clone()))?;
+    /// let mut encoder = TextureEncoder::new_gbix(DataFormat::Argb8888)?;
+    /// archive.extend(encoder.encode_image(DynamicImage::ImageRgba8(second.clone()))?);
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(archive.clone());
+    /// assert_eq!(decoder.decode_all()?, [first.clone(), second]);
+    ///
+    /// // Anything after the last texture that isn't a texture is ignored
+    /// archive.extend([0; 16]);
+    /// let mut decoder = TextureDecoder::new_from_buffer(archive.clone());
+    /// assert_eq!(decoder.decode_all()?.len(), 2);
+    ///
+    /// // But a texture cut short isn't
+    /// archive.truncate(archive.len() - 17);
+    /// let mut decoder = TextureDecoder::new_from_buffer(archive);
+    /// assert!(decoder.decode_all().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If any of the textures is invalid or cut short, a [`TextureDecodeError`] is returned.
+    pub fn decode_all(&mut self) -> Result<Vec<RgbaImage>, TextureDecodeError> {
+        let start = self.offset;
+        let state = (
+            self.texture_type,
+            self.data_format,
+            self.pixel_format,
+            self.data_flags,
+            self.palette.take(),
+            self.indices.take(),
+        );
 
-        let data_format: DataFormat = DataFormat::try_from(self.cursor.read_u8()?)?;
+        let result = self.decode_all_from(start);
 
-        if data_flags.intersects(DataFlags::ExternalPalette) {
-            unimplemented!();
+        self.offset = start;
+        (
+            self.texture_type,
+            self.data_format,
+            self.pixel_format,
+            self.data_flags,
+            self.palette,
+            self.indices,
+        ) = state;
+        result
+    }
+
+    /// Decodes every texture stored back to back starting from `offset`, see
+    /// [`Self::decode_all()`].
+    fn decode_all_from(&mut self, mut offset: u64) -> Result<Vec<RgbaImage>, TextureDecodeError> {
+        let mut images = Vec::new();
+        loop {
+            self.reader.seek(SeekFrom::Start(offset))?;
+            let mut magic = [0; 4];
+            match self.reader.read_exact(&mut magic) {
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                result => result?,
+            }
+            if !matches!(
+                &header::normalize_magic(magic, self.relaxed_magic),
+                b"GCIX" | b"GBIX" | b"GVRT"
+            ) {
+                break;
+            }
+
+            self.offset = offset;
+            images.push(self.decode_internal(None)?.into_rgba8());
+
+            self.reader.seek(SeekFrom::Start(offset))?;
+            let (header, _) = GvrHeader::read_with_custom(
+                &mut self.reader,
+                |value| self.custom_decoders.contains_key(&value),
+                self.relaxed_magic,
+            )?;
+            offset += (header.size() + header.data_len as usize) as u64;
         }
+        Ok(images)
+    }
 
-        // Check if data format is matching if a palette is included
-        if data_flags.intersects(DataFlags::InternalPalette)
-            && matches!(data_format, DataFormat::Index4 | DataFormat::Index8).not()
-        {
-            return Err(TextureDecodeError::InvalidFile);
+    /// Returns a copy of the texture data that follows the headers, without decoding it.
+    ///
+    /// This is as much data as the GVRT header states, including the color palette and mipmaps
+    /// if the texture has them. It's useful for comparing the encoded data byte by byte with the
+    /// output of other tools. [`Self::decode()`] doesn't need to be called first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8 * 32, y as u8 * 32, 0, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// let data = DataFormat::Rgb565.encoder().unwrap().encode(&image);
+    /// assert_eq!(decoder.raw_data()?, data);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the headers are invalid, or the file is shorter than the length of the data the GVRT
+    /// header states, a [`TextureDecodeError`] is returned.
+    pub fn raw_data(&mut self) -> Result<Vec<u8>, TextureDecodeError> {
+        self.reader.seek(SeekFrom::Start(self.offset))?;
+        let (header, _) = GvrHeader::read_with_custom(
+            &mut self.reader,
+            |value| self.custom_decoders.contains_key(&value),
+            self.relaxed_magic,
+        )?;
+
+        let data_start = self.reader.stream_position()?;
+        let data_end = self.reader.seek(SeekFrom::End(0))?;
+        header.check_available(data_end - data_start)?;
+        self.reader.seek(SeekFrom::Start(data_start))?;
+
+        let mut data = vec![0; header.data_len as usize];
+        self.reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Checks that the texture could be decoded, without decoding it.
+    ///
+    /// This reads the headers and runs the same checks [`Self::decode()`] does before decoding:
+    /// the magic values, the pixel and data formats, whether the data flags make sense for the
+    /// data format, and whether the file holds as much data as the GVRT header states and the
+    /// texture needs. Nothing is allocated for the texture data, so this is a cheap way of
+    /// sorting out broken files from a large set of textures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::error::TextureDecodeError;
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::layout::{GVRT_FORMATS_OFFSET, GVRT_OFFSET};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(8, 8)))?;
+    /// assert!(TextureDecoder::new_from_buffer(encoded.clone()).validate().is_ok());
+    ///
+    /// let mut bad_magic = encoded.clone();
+    /// bad_magic[..4].copy_from_slice(b"XXXX");
+    /// let result = TextureDecoder::new_from_buffer(bad_magic).validate();
+    /// assert!(matches!(result, Err(TextureDecodeError::BadMagic { .. })));
+    ///
+    /// // Only palette formats can have an internal palette
+    /// let mut bad_flags = encoded.clone();
+    /// bad_flags[GVRT_OFFSET + GVRT_FORMATS_OFFSET] |= 0x8;
+    /// let result = TextureDecoder::new_from_buffer(bad_flags).validate();
+    /// assert!(matches!(result, Err(TextureDecodeError::UnknownFlags { .. })));
+    ///
+    /// let truncated = encoded[..encoded.len() - 1].to_vec();
+    /// let result = TextureDecoder::new_from_buffer(truncated).validate();
+    /// assert!(matches!(result, Err(TextureDecodeError::SizeMismatch { .. })));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the texture can't be decoded, the [`TextureDecodeError`] [`Self::decode()`] would
+    /// return is returned.
+    pub fn validate(&mut self) -> Result<(), TextureDecodeError> {
+        self.reader.seek(SeekFrom::Start(self.offset))?;
+        let (header, format_value) = GvrHeader::read_with_custom(
+            &mut self.reader,
+            |value| self.custom_decoders.contains_key(&value),
+            self.relaxed_magic,
+        )?;
+        self.texture_type = Some(header.texture_type);
+
+        let data_start = self.reader.stream_position()?;
+        let data_end = self.reader.seek(SeekFrom::End(0))?;
+        if self.custom_decoders.contains_key(&format_value) {
+            header.check_available(data_end - data_start)
+        } else {
+            header.check_data_len(data_end - data_start)
+        }
+    }
+
+    fn decode_internal(
+        &mut self,
+        region: Option<(u32, u32, u32, u32)>,
+    ) -> Result<DynamicImage, TextureDecodeError> {
+        self.reader.seek(SeekFrom::Start(self.offset))?;
+        let (header, format_value) = GvrHeader::read_with_custom(
+            &mut self.reader,
+            |value| self.custom_decoders.contains_key(&value),
+            self.relaxed_magic,
+        )?;
+        let custom_decoder = self.custom_decoders.get(&format_value);
+        let data_len = u64::from(header.data_len);
+        let data_format = header.data_format;
+        let data_flags = header.flags;
+        let palette_format = header.pixel_format;
+
+        self.texture_type = Some(header.texture_type);
+        self.data_format = custom_decoder.is_none().then_some(data_format);
+        self.data_flags = Some(data_flags);
+
+        if data_flags.intersects(DataFlags::ExternalPalette) {
+            let colors = palette_size(data_format) as usize;
+            match &self.external_palette {
+                None => return Err(TextureDecodeError::MissingPalette),
+                Some(palette) if palette.len() != colors => {
+                    return Err(TextureDecodeError::PaletteLength(colors, palette.len()))
+                }
+                Some(_) => {}
+            }
         }
 
-        let width = self.cursor.read_u16::<BigEndian>()?;
-        let height = self.cursor.read_u16::<BigEndian>()?;
+        self.pixel_format = data_flags
+            .intersects(DataFlags::Palette)
+            .then_some(palette_format);
+
+        let width = header.width;
+        let height = header.height;
+
+        if let Some((x, y, region_width, region_height)) = region {
+            let fits_width = x
+                .checked_add(region_width)
+                .is_some_and(|w| w <= width.into());
+            let fits_height = y
+                .checked_add(region_height)
+                .is_some_and(|h| h <= height.into());
 
-        let mut data: Vec<u8> = Vec::with_capacity(data_len);
-        let read_size = self.cursor.read_to_end(&mut data)?;
-        if read_size != data_len {
-            return Err(TextureDecodeError::InvalidFile);
+            if region_width == 0 || region_height == 0 || !fits_width || !fits_height {
+                return Err(TextureDecodeError::InvalidRegion(
+                    x,
+                    y,
+                    region_width,
+                    region_height,
+                ));
+            }
+        }
+
+        let data_start = self.reader.stream_position()?;
+        let data_end = self.reader.seek(SeekFrom::End(0))?;
+        if custom_decoder.is_some() {
+            header.check_available(data_end - data_start)?;
+        } else {
+            header.check_data_len(data_end - data_start)?;
         }
+        self.reader.seek(SeekFrom::Start(data_start))?;
 
-        if data_flags.intersects(DataFlags::InternalPalette) {
+        let mut data = (&mut self.reader).take(data_len);
+
+        let mut image = if data_flags.intersects(DataFlags::Palette) {
             let decoder = create_new_decoder_with_palette(data_format);
-            self.image =
-                Some(decoder.decode(&data, width.into(), height.into(), palette_format)?);
+            let (palette, mut indices) = match &self.external_palette {
+                Some(palette) if data_flags.intersects(DataFlags::ExternalPalette) => (
+                    palette.clone(),
+                    decoder.decode_indices(&mut data, width.into(), height.into())?,
+                ),
+                _ => match header.palette_colors() {
+                    Some(colors) if colors < palette_size(data_format) as usize => {
+                        decode_indexed_short(
+                            &mut data,
+                            data_format,
+                            width.into(),
+                            height.into(),
+                            palette_format,
+                            colors,
+                        )?
+                    }
+                    _ => decoder.decode_indexed(
+                        &mut data,
+                        width.into(),
+                        height.into(),
+                        palette_format,
+                    )?,
+                },
+            };
+            if self.flip_v {
+                indices = flip_indices_vertical(&indices, width.into());
+            }
+            let image = expand_indexed(&palette, &indices, width.into(), height.into());
+
+            self.palette = Some(palette);
+            self.indices = Some(indices);
+
+            match region {
+                Some((x, y, region_width, region_height)) => DynamicImage::ImageRgba8(
+                    imageops::crop_imm(&image, x, y, region_width, region_height).to_image(),
+                ),
+                None => DynamicImage::ImageRgba8(image),
+            }
+        } else {
+            let built_in;
+            let decoder: &(dyn GvrDecoder + Sync) = match custom_decoder {
+                Some(decoder) => decoder.as_ref(),
+                None => {
+                    built_in = create_new_decoder(data_format, self.dxt_layout);
+                    built_in.as_ref()
+                }
+            };
+
+            // The region is given in the flipped image, so the rows to decode are mirrored
+            let region = region.map(|(x, y, region_width, region_height)| {
+                if self.flip_v {
+                    let y = u32::from(height) - y - region_height;
+                    (x, y, region_width, region_height)
+                } else {
+                    (x, y, region_width, region_height)
+                }
+            });
+
+            let mut image = match region {
+                Some(region) => DynamicImage::ImageRgba8(decoder.decode_region(
+                    &mut data,
+                    width.into(),
+                    height.into(),
+                    region,
+                )?),
+                // The layout of custom data formats is unknown, so they can't be split up
+                #[cfg(feature = "rayon")]
+                None if custom_decoder.is_none() => decode_dynamic_parallel(
+                    decoder,
+                    data_format,
+                    &mut data,
+                    width.into(),
+                    height.into(),
+                )?,
+                None => decoder.decode_dynamic(&mut data, width.into(), height.into())?,
+            };
+
+            if self.flip_v {
+                imageops::flip_vertical_in_place(&mut image);
+            }
+            if let (Some(swizzle), None, DynamicImage::ImageRgba8(image)) =
+                (self.channel_swizzle, custom_decoder, &mut image)
+            {
+                if data_format == DataFormat::Argb8888 {
+                    swizzle_channels(image, swizzle);
+                }
+            }
+            image
+        };
+
+        if self.premultiplied_alpha {
+            premultiply_alpha(&mut image);
+        }
+        Ok(image)
+    }
+
+    /// Converts the texture into a DDS file, returning the bytes of the file.
+    ///
+    /// For [`DataFormat::Dxt1`] textures, the compressed blocks are copied over into a DDS file
+    /// with the DXT1 format, only reordering them from the tiled layout of the GameCube into the
+    /// linear layout of DDS files. As the blocks are never decoded, no quality is lost. If the
+    /// texture has mipmaps, they're carried over as well. With [`Self::with_flip_v()`], the blocks
+    /// are flipped without decoding them too, unless the height of the texture or one of its
+    /// mipmaps is above 4 and not a multiple of 4. The rows of pixels would have to move across
+    /// blocks to flip those, so they're stored like the other data formats instead.
+    ///
+    /// Every other data format is decoded and stored as uncompressed 32-bit RGBA pixels, without
+    /// mipmaps.
+    ///
+    /// # Examples
+    ///
+    /// Flipping a 4x6 texture, whose rows can't be flipped within the blocks, stores its pixels
+    /// uncompressed after the 128 bytes of headers:
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFlags, DataFormat};
+    /// use gvrtex::{GvrHeader, TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Only mipmaps are this size, so the first mipmap of a 8x12 texture is taken out of it
+    /// let image = RgbaImage::from_fn(8, 12, |x, y| {
+    ///     Rgba([y as u8 * 0x15, x as u8 * 0x20, 0, 0xFF])
+    /// });
+    /// let gvr = TextureEncoder::new_gcix(DataFormat::Dxt1)?
+    ///     .with_mipmaps()?
+    ///     .encode_image(DynamicImage::ImageRgba8(image))?;
+    /// let mut header = GvrHeader::read(&mut gvr.as_slice())?;
+    /// let mipmap_start = header.size() + 64;
+    /// (header.width, header.height, header.data_len) = (4, 6, 32);
+    /// header.flags = DataFlags::empty();
+    /// let mut mipmap = Vec::new();
+    /// header.write(&mut mipmap)?;
+    /// mipmap.extend_from_slice(&gvr[mipmap_start..mipmap_start + 32]);
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(mipmap).with_flip_v();
+    /// let dds = decoder.to_dds_bytes()?;
+    /// decoder.decode()?;
+    /// let flipped = decoder.into_decoded()?;
+    /// assert_eq!(flipped.dimensions(), (4, 6));
+    /// assert_eq!(dds[128..], *flipped.as_raw());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If something goes wrong while reading the texture, or the given file is not a valid GVR
+    /// texture file, a [`TextureDecodeError`] is returned.
+    pub fn to_dds_bytes(&mut self) -> Result<Vec<u8>, TextureDecodeError> {
+        self.reader.seek(SeekFrom::Start(self.offset))?;
+        let (header, format_value) = GvrHeader::read_with_custom(
+            &mut self.reader,
+            |value| self.custom_decoders.contains_key(&value),
+            self.relaxed_magic,
+        )?;
+        let width = u32::from(header.width);
+        let height = u32::from(header.height);
+        let data_len = header.data_len as usize;
+
+        // The blocks of a level can't be flipped if it doesn't fill its last row of blocks, and
+        // every mipmap halves the height down to 1
+        let mipmaps = header.flags.intersects(DataFlags::Mipmaps);
+        let level_count = if mipmaps {
+            height.checked_ilog2().unwrap_or(0) + 1
         } else {
-            let decoder = create_new_decoder(data_format);
-            self.image = Some(decoder.decode(&data, width.into(), height.into())?);
+            1
+        };
+        let flips_partial_blocks =
+            self.flip_v && (0..level_count).any(|level| !dds::can_flip_blocks(height >> level));
+
+        let is_custom = self.custom_decoders.contains_key(&format_value);
+        if header.data_format != DataFormat::Dxt1 || is_custom || flips_partial_blocks {
+            let image = self.decode_internal(None)?.into_rgba8();
+            return Ok(dds::rgba_to_dds(&image));
         }
 
+        let data_start = self.reader.stream_position()?;
+        let data_end = self.reader.seek(SeekFrom::End(0))?;
+        header.check_data_len(data_end - data_start)?;
+        self.reader.seek(SeekFrom::Start(data_start))?;
+
+        let mut data = vec![0; data_len];
+        self.reader.read_exact(&mut data)?;
+
+        Ok(dds::dxt1_to_dds(
+            &data,
+            width,
+            height,
+            mipmaps,
+            self.flip_v,
+            self.dxt_layout,
+        ))
+    }
+
+    /// Converts the texture into a DDS file and saves it to the given `path`.
+    ///
+    /// See [`Self::to_dds_bytes()`] for how the texture is converted.
+    ///
+    /// # Errors
+    ///
+    /// If the texture can't be converted, or writing the file fails, a [`TextureDecodeError`] is
+    /// returned.
+    pub fn save_dds(&mut self, path: &str) -> Result<(), TextureDecodeError> {
+        let dds = self.to_dds_bytes()?;
+        std::fs::write(path, dds)?;
         Ok(())
     }
+}
+
+/// Returns the fraction of pixels in `image` whose alpha, once multiplied by `scale`, is at least
+/// `threshold`.
+#[cfg(feature = "std")]
+fn alpha_coverage(image: &RgbaImage, threshold: u8, scale: f32) -> f32 {
+    let pixel_count = image.width() as usize * image.height() as usize;
+    if pixel_count == 0 {
+        return 0.0;
+    }
+
+    let covered = image
+        .pixels()
+        .filter(|p| scale_alpha(p[3], scale) >= threshold)
+        .count();
+    covered as f32 / pixel_count as f32
+}
+
+/// Scales the alpha channel of `image` so that the fraction of its pixels with an alpha of at
+/// least `threshold` gets as close as possible to `coverage`.
+///
+/// The scale is found with a binary search, as the coverage only ever grows with the scale.
+#[cfg(feature = "std")]
+fn scale_alpha_to_coverage(image: &mut RgbaImage, threshold: u8, coverage: f32) {
+    const MAX_SCALE: f32 = 4.0;
+    const ITERATIONS: usize = 16;
+
+    let (mut low, mut high) = (0.0f32, MAX_SCALE);
+    let mut best = (
+        1.0f32,
+        (alpha_coverage(image, threshold, 1.0) - coverage).abs(),
+    );
+    for _ in 0..ITERATIONS {
+        let scale = (low + high) / 2.0;
+        let current = alpha_coverage(image, threshold, scale);
+        let error = (current - coverage).abs();
+        if error < best.1 {
+            best = (scale, error);
+        }
+
+        if current < coverage {
+            low = scale;
+        } else if current > coverage {
+            high = scale;
+        } else {
+            break;
+        }
+    }
+
+    let scale = best.0;
+    if scale != 1.0 {
+        for p in image.pixels_mut() {
+            p[3] = scale_alpha(p[3], scale);
+        }
+    }
+}
+
+/// Sharpens the colors of `image` with an unsharp mask, adding the difference between each
+/// pixel and a blurred copy of the image, multiplied by `amount`, back to the pixel.
+#[cfg(feature = "std")]
+fn sharpen(image: &RgbaImage, amount: f32) -> RgbaImage {
+    let blurred = imageops::blur(image, 1.0);
+    let mut sharpened = image.clone();
+    for (p, blurred) in sharpened.pixels_mut().zip(blurred.pixels()) {
+        for (c, blurred) in p.0[..3].iter_mut().zip(&blurred.0[..3]) {
+            let difference = f32::from(*c) - f32::from(*blurred);
+            *c = (f32::from(*c) + difference * amount)
+                .round()
+                .clamp(0.0, 255.0) as u8;
+        }
+    }
+    sharpened
+}
+
+/// Dithers the colors of `image` with Floyd-Steinberg dithering, where `quantize` returns the
+/// color that's stored in place of the given one.
+///
+/// The pixels are gone through row by row, and the difference between the color each pixel
+/// should have and the one that's stored is carried over to the pixels to the right and below it.
+/// The alpha channel of each pixel is kept as is.
+#[cfg(feature = "std")]
+fn dither(image: &RgbaImage, quantize: impl Fn([u8; 4]) -> [u8; 4]) -> RgbaImage {
+    let width = image.width() as usize;
+    let mut dithered = image.clone();
+
+    // The errors carried over to the current and the next row, with a pixel of room on both
+    // sides so the edges don't need special handling
+    let mut errors = vec![[0f32; 3]; width + 2];
+    let mut next_errors = vec![[0f32; 3]; width + 2];
+    for row in dithered.rows_mut() {
+        for (x, p) in (1..).zip(row) {
+            let mut wanted = [0f32; 3];
+            for c in 0..3 {
+                wanted[c] = (f32::from(p.0[c]) + errors[x][c]).clamp(0.0, 255.0);
+                p.0[c] = wanted[c].round() as u8;
+            }
+
+            let stored = quantize(p.0);
+            for c in 0..3 {
+                let error = wanted[c] - f32::from(stored[c]);
+                errors[x + 1][c] += error * 7.0 / 16.0;
+                next_errors[x - 1][c] += error * 3.0 / 16.0;
+                next_errors[x][c] += error * 5.0 / 16.0;
+                next_errors[x + 1][c] += error / 16.0;
+            }
+            p.0[..3].copy_from_slice(&stored[..3]);
+        }
+
+        std::mem::swap(&mut errors, &mut next_errors);
+        next_errors.fill([0.0; 3]);
+    }
+
+    dithered
+}
+
+/// Multiplies `alpha` by `scale`, clamping the result to 255.
+#[cfg(feature = "std")]
+fn scale_alpha(alpha: u8, scale: f32) -> u8 {
+    (f32::from(alpha) * scale).round().min(255.0) as u8
+}
+
+/// Checks if `swizzle` holds every channel of an RGBA pixel exactly once.
+#[cfg(feature = "std")]
+fn is_channel_permutation(swizzle: [usize; 4]) -> bool {
+    (0..4).all(|channel| swizzle.contains(&channel))
+}
+
+/// Reorders the channels of each pixel in `image`, taking channel `i` of each pixel from its
+/// channel `swizzle[i]`.
+#[cfg(feature = "std")]
+fn swizzle_channels(image: &mut RgbaImage, swizzle: [usize; 4]) {
+    for p in image.pixels_mut() {
+        p.0 = swizzle.map(|channel| p.0[channel]);
+    }
+}
+
+/// Multiplies the color of each pixel in the `image` by its alpha, keeping the color type of the
+/// image if it's [`DynamicImage::ImageRgba8`] or [`DynamicImage::ImageLumaA8`]. Other color types
+/// with alpha are converted to [`DynamicImage::ImageRgba8`].
+#[cfg(feature = "std")]
+fn premultiply_alpha(image: &mut DynamicImage) {
+    match image {
+        DynamicImage::ImageRgba8(image) => {
+            for p in image.pixels_mut() {
+                p.0 = pixel::premultiply_alpha(p.0);
+            }
+        }
+        DynamicImage::ImageLumaA8(image) => {
+            for p in image.pixels_mut() {
+                let [c, a] = p.0;
+                p.0 = [pixel::premultiply_alpha([c, c, c, a])[0], a];
+            }
+        }
+        image if image.color().has_alpha() => {
+            let mut rgba = image.to_rgba8();
+            for p in rgba.pixels_mut() {
+                p.0 = pixel::premultiply_alpha(p.0);
+            }
+            *image = DynamicImage::ImageRgba8(rgba);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> TextureDecoder<R> {
+    /// Sets the color palette to use for textures with the [`DataFlags::ExternalPalette`] flag.
+    ///
+    /// These textures only store the palette indices, with the color palette itself stored in a
+    /// separate file (usually a GVP file). The `palette` has to have exactly as many colors as
+    /// the data format of the texture allows for (16 for [`DataFormat::Index4`], 256 for
+    /// [`DataFormat::Index8`]).
+    ///
+    /// Textures that store their own color palette ignore this palette.
+    pub fn with_external_palette(mut self, palette: Vec<Rgba<u8>>) -> Self {
+        self.external_palette = Some(palette);
+        self
+    }
+
+    /// Registers a custom `decoder` for textures whose data format has the value `format_value`.
+    /// This is used for data formats this crate doesn't know about, see the [`codec`] module for
+    /// an example.
+    ///
+    /// The custom decoder is used instead of the built-in one, even if `format_value` is one of
+    /// the data formats in [`DataFormat`]. Textures decoded with a custom decoder can't use a
+    /// color palette.
+    pub fn with_custom_decoder(
+        mut self,
+        format_value: u8,
+        decoder: Box<dyn GvrDecoder + Send + Sync>,
+    ) -> Self {
+        self.custom_decoders.insert(format_value, decoder);
+        self
+    }
+
+    /// Flips the image vertically after it's decoded.
+    ///
+    /// This is the counterpart of [`TextureEncoder::with_flip_v()`], for textures that are stored
+    /// upside down. The flip also applies to [`Self::indices()`], and regions given to
+    /// [`Self::decode_region()`] are positioned in the flipped image.
+    pub fn with_flip_v(mut self) -> Self {
+        self.flip_v = true;
+        self
+    }
+
+    /// Accepts textures whose magic strings ("GCIX", "GBIX" and "GVRT") are written in a
+    /// different case, or have a NUL byte in place of their last character, as some tools write
+    /// them that way. By default, only the exact magic strings are accepted.
+    ///
+    /// Relaxing the check makes it more likely for data that isn't a GVR texture at all to be
+    /// decoded into garbage instead of being rejected with a [`TextureDecodeError::BadMagic`], so
+    /// only use this for files that are known to be textures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::error::TextureDecodeError;
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_pixel(8, 8, Rgba([0xFF, 0, 0, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+    /// let mut encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    /// encoded[0..4].copy_from_slice(b"gcix");
+    /// encoded[0x10..0x14].copy_from_slice(b"GVR\0");
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded.clone());
+    /// assert!(matches!(
+    ///     decoder.decode(),
+    ///     Err(TextureDecodeError::BadMagic { offset: 0, .. })
+    /// ));
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded.clone()).with_relaxed_magic();
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.into_decoded()?, image);
+    ///
+    /// // Magic strings that are entirely wrong are still rejected
+    /// encoded[0..4].copy_from_slice(b"ABCD");
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded).with_relaxed_magic();
+    /// assert!(matches!(
+    ///     decoder.decode(),
+    ///     Err(TextureDecodeError::BadMagic { offset: 0, found }) if &found == b"ABCD"
+    /// ));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_relaxed_magic(mut self) -> Self {
+        self.relaxed_magic = true;
+        self
+    }
+
+    /// Multiplies the color of each pixel by its alpha after the texture is decoded, giving an
+    /// image with premultiplied alpha instead of the straight alpha GVR textures store.
+    ///
+    /// This is the counterpart of [`TextureEncoder::with_premultiplied_alpha()`], and only
+    /// changes the pixels that aren't fully opaque. The colors in [`Self::palette()`] are left
+    /// with straight alpha.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, _| match x {
+    ///     0..4 => Rgba([0xFF, 0x80, 0x00, 0xFF]),
+    ///     _ => Rgba([0xFF, 0x80, 0x00, 0x80]),
+    /// });
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb5a3)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// let mut straight = TextureDecoder::new_from_buffer(encoded.clone());
+    /// straight.decode()?;
+    /// let straight = straight.into_decoded()?;
+    /// assert_eq!(straight.get_pixel(0, 0), &Rgba([0xFF, 0x84, 0x00, 0xFF]));
+    /// assert_eq!(straight.get_pixel(4, 0), &Rgba([0xFF, 0x88, 0x00, 0x92]));
+    ///
+    /// let mut premultiplied = TextureDecoder::new_from_buffer(encoded).with_premultiplied_alpha();
+    /// premultiplied.decode()?;
+    /// let premultiplied = premultiplied.into_decoded()?;
+    /// assert_eq!(premultiplied.get_pixel(0, 0), &Rgba([0xFF, 0x84, 0x00, 0xFF]));
+    /// assert_eq!(premultiplied.get_pixel(4, 0), &Rgba([0x92, 0x4E, 0x00, 0x92]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_premultiplied_alpha(mut self) -> Self {
+        self.premultiplied_alpha = true;
+        self
+    }
+
+    /// Sets the order the blocks of [`DataFormat::Dxt1`] textures are read in, see
+    /// [`DxtLayout`]. By default, they're read in the [`DxtLayout::GameCube`] order.
+    ///
+    /// This is the counterpart of [`TextureEncoder::with_dxt_layout()`]. Decoding a texture with
+    /// the wrong layout doesn't fail, but scrambles the blocks of the image. Other data formats
+    /// aren't affected.
+    pub fn with_dxt_layout(mut self, layout: DxtLayout) -> Self {
+        self.dxt_layout = layout;
+        self
+    }
+
+    /// Reorders the channels of [`DataFormat::Argb8888`] textures after they're decoded.
+    ///
+    /// Each entry of `swizzle` picks the stored channel (0 for red, 1 for green, 2 for blue and 3
+    /// for alpha) that ends up in the red, green, blue and alpha channel of the decoded image
+    /// respectively. This is the counterpart of [`TextureEncoder::with_channel_swizzle()`], and
+    /// undoes the swizzle of the encoder when given its inverse. Other data formats aren't
+    /// affected.
+    ///
+    /// # Errors
+    ///
+    /// If `swizzle` doesn't hold every channel from 0 to 3 exactly once, a
+    /// [`TextureDecodeError::InvalidSwizzle`] is returned.
+    pub fn with_channel_swizzle(mut self, swizzle: [usize; 4]) -> Result<Self, TextureDecodeError> {
+        if !is_channel_permutation(swizzle) {
+            return Err(TextureDecodeError::InvalidSwizzle(swizzle));
+        }
+        self.channel_swizzle = Some(swizzle);
+        Ok(self)
+    }
 
     /// Checks if the decode process has concluded successfully.
     pub fn is_decoded(&self) -> bool {
         self.image.is_some()
     }
 
+    /// Returns which header the texture starts with, once [`Self::decode()`] or
+    /// [`Self::validate()`] has read the headers.
+    ///
+    /// This is useful for keeping the header of a texture when encoding it again, see
+    /// [`TextureEncoder::new()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, TextureType};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut encoder = TextureEncoder::new_gbix(DataFormat::Rgb565)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(8, 8)))?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// assert_eq!(decoder.texture_type(), None);
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.texture_type(), Some(TextureType::Gbix));
+    ///
+    /// let mut encoder = TextureEncoder::new(TextureType::Gcix, DataFormat::Rgb565)?.without_outer_header();
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(RgbaImage::new(8, 8)))?;
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// decoder.validate()?;
+    /// assert_eq!(decoder.texture_type(), Some(TextureType::None));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn texture_type(&self) -> Option<TextureType> {
+        self.texture_type
+    }
+
+    /// Returns the pixel format of the color palette, if [`Self::decode()`] has ran successfully
+    /// on a palettized texture ([`DataFormat::Index4`] or [`DataFormat::Index8`]).
+    ///
+    /// For every other data format, the texture has no color palette, so [`None`] is returned.
+    pub fn pixel_format(&self) -> Option<PixelFormat> {
+        self.pixel_format
+    }
+
+    /// Returns the [`DataFlags`] stored in the header of the texture, if [`Self::decode()`] has
+    /// ran successfully.
+    pub fn flags(&self) -> Option<DataFlags> {
+        self.data_flags
+    }
+
+    /// Returns the color palette of the texture, if [`Self::decode()`] has ran successfully on a
+    /// palettized texture ([`DataFormat::Index4`] or [`DataFormat::Index8`]).
+    ///
+    /// The palette always has the full amount of colors the data format allows for (16 or 256),
+    /// even if the texture doesn't use all of them. Textures that store a shorter palette have it
+    /// filled out with transparent black, see [`GvrHeader::palette_colors()`].
+    pub fn palette(&self) -> Option<&[Rgba<u8>]> {
+        self.palette.as_deref()
+    }
+
+    /// Renders the color palette of the texture into a square grid with one pixel for each
+    /// color, if [`Self::decode()`] has ran successfully on a palettized texture
+    /// ([`DataFormat::Index4`] or [`DataFormat::Index8`]).
+    ///
+    /// The colors go from left to right, one row after the other, so a palette of 16 colors gives
+    /// a 4x4 image, and a palette of 256 colors a 16x16 one. This is handy for looking at the
+    /// palette of a texture while debugging, scaled up to a viewable size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::imageops::{self, FilterType};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(16, 16, |x, y| Rgba([x as u8 * 16, y as u8 * 16, 0, 0xFF]));
+    /// let image = DynamicImage::ImageRgba8(image);
+    ///
+    /// for (data_format, size) in [(DataFormat::Index4, 4), (DataFormat::Index8, 16)] {
+    ///     let mut encoder = TextureEncoder::new_gcix_palettized(PixelFormat::RGB565, data_format)?;
+    ///     let mut decoder = TextureDecoder::new_from_buffer(encoder.encode_image(image.clone())?);
+    ///     assert_eq!(decoder.palette_image(), None);
+    ///
+    ///     decoder.decode()?;
+    ///     let swatch = decoder.palette_image().unwrap();
+    ///     assert_eq!(swatch.dimensions(), (size, size));
+    ///     assert_eq!(swatch.get_pixel(1, 0), &decoder.palette().unwrap()[1]);
+    ///
+    ///     // Scaled up to 16x16 pixels for each color, to save it as a PNG for example
+    ///     let scaled = imageops::resize(&swatch, size * 16, size * 16, FilterType::Nearest);
+    ///     assert_eq!(scaled.get_pixel(16, 0), swatch.get_pixel(1, 0));
+    /// }
+    ///
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb565)?;
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoder.encode_image(image)?);
+    /// decoder.decode()?;
+    /// assert_eq!(decoder.palette_image(), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn palette_image(&self) -> Option<RgbaImage> {
+        let palette = self.palette.as_deref()?;
+        let columns = palette.len().isqrt().max(1);
+        let rows = palette.len().div_ceil(columns);
+
+        let mut image = RgbaImage::new(columns as u32, rows as u32);
+        for (p, color) in image.pixels_mut().zip(palette) {
+            *p = *color;
+        }
+        Some(image)
+    }
+
+    /// Returns the palette index of every pixel in the texture, if [`Self::decode()`] has ran
+    /// successfully on a palettized texture ([`DataFormat::Index4`] or [`DataFormat::Index8`]).
+    ///
+    /// The indices are in row-major order, meaning the index of the pixel at `x` and `y` is at
+    /// `y * width + x`. Each index refers to a color in [`Self::palette()`].
+    pub fn indices(&self) -> Option<&[u8]> {
+        self.indices.as_deref()
+    }
+
+    /// Iterates over the decoded image in blocks of `block_width` by `block_height` pixels,
+    /// in the same order the encoder goes through them.
+    ///
+    /// The blocks go from left to right, one row of blocks after the other, and each block holds
+    /// its pixels row by row, the same as [`iter::PixelBlockIteratorExt`] visits them. Blocks
+    /// that go past the right or bottom edge of the image repeat the pixels at the edge.
+    ///
+    /// If [`Self::decode()`] hasn't ran successfully, or either size of the blocks is 0, the
+    /// iterator is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([x as u8, y as u8, 0, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Argb8888)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// assert_eq!(decoder.blocks_iter(4, 4).count(), 0);
+    ///
+    /// decoder.decode()?;
+    /// let blocks: Vec<_> = decoder.blocks_iter(4, 4).collect();
+    /// assert_eq!(blocks.len(), 4);
+    /// assert!(blocks.iter().all(|block| block.len() == 16));
+    ///
+    /// // The second block is the top right one, starting at 4,0
+    /// assert_eq!(blocks[1][0], Rgba([4, 0, 0, 0xFF]));
+    /// assert_eq!(blocks[1][15], Rgba([7, 3, 0, 0xFF]));
+    ///
+    /// // Blocks that don't fit the image repeat its edge
+    /// let blocks: Vec<_> = decoder.blocks_iter(6, 6).collect();
+    /// assert_eq!(blocks.len(), 4);
+    /// assert_eq!(blocks[3][0], Rgba([6, 6, 0, 0xFF]));
+    /// assert_eq!(blocks[3][35], Rgba([7, 7, 0, 0xFF]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn blocks_iter(
+        &self,
+        block_width: u32,
+        block_height: u32,
+    ) -> impl Iterator<Item = Vec<Rgba<u8>>> + '_ {
+        let block_len = block_width as usize * block_height as usize;
+        let image = self.image.as_ref().filter(|_| block_len > 0);
+
+        let mut pixels = image
+            .into_iter()
+            .flat_map(move |image| {
+                // Iterating over whole blocks, as the iterator skips the pixels outside the image
+                let (width, height) = image.dimensions();
+                let padded_width = width.next_multiple_of(block_width);
+                let padded_height = height.next_multiple_of(block_height);
+                let block_size = (block_width, block_height);
+                PixelBlockIteratorExt::new(padded_width, padded_height, block_size).map(
+                    move |(block, _, x, y)| {
+                        (block, *image.get_pixel(x.min(width - 1), y.min(height - 1)))
+                    },
+                )
+            })
+            .peekable();
+
+        std::iter::from_fn(move || {
+            let (block, pixel) = pixels.next()?;
+            let mut result = Vec::with_capacity(block_len);
+            result.push(pixel);
+            while let Some((_, pixel)) = pixels.next_if(|&(next_block, _)| next_block == block) {
+                result.push(pixel);
+            }
+            Some(result)
+        })
+    }
+
     /// Borrows the decoded image, if [`Self::decode()`] has ran successfully.
     pub fn as_decoded(&self) -> &Option<RgbaImage> {
         &self.image
     }
 
+    /// Borrows the pixels of the decoded image as raw bytes, if [`Self::decode()`] has ran
+    /// successfully.
+    ///
+    /// The pixels are in row-major order, 4 bytes each in RGBA order, which is the layout most
+    /// graphics APIs expect when uploading a texture. Unlike [`Self::into_decoded()`], this
+    /// doesn't consume the decoder or copy the pixels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::DataFormat;
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(16, 8, |x, y| Rgba([x as u8, y as u8, 0, 0xFF]));
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Argb8888)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// assert_eq!(decoder.as_rgba_bytes(), None);
+    ///
+    /// decoder.decode()?;
+    /// let bytes = decoder.as_rgba_bytes().unwrap();
+    /// assert_eq!(bytes.len(), 16 * 8 * 4);
+    /// assert_eq!(bytes, image.as_raw().as_slice());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_rgba_bytes(&self) -> Option<&[u8]> {
+        self.image.as_ref().map(|image| image.as_raw().as_slice())
+    }
+
     /// Returns the decoded image, if [`Self::decode()`] has ran successfully, consuming `self`.
     ///
     /// # Errors
@@ -471,6 +3629,48 @@ impl TextureDecoder {
         }
     }
 
+    /// Encodes the decoded image into a new GVR texture with the given `encoder`, without saving
+    /// it to an intermediate image file first.
+    ///
+    /// This is useful for converting textures from one data format to another. Any settings of
+    /// the `encoder` apply, same as with [`TextureEncoder::encode_image()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gvrtex::formats::{DataFormat, PixelFormat};
+    /// use gvrtex::{TextureDecoder, TextureEncoder};
+    /// use image::{DynamicImage, Rgba, RgbaImage};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let image = RgbaImage::from_fn(8, 8, |x, _| match x % 2 {
+    ///     0 => Rgba([0xFF, 0x00, 0x00, 0xFF]),
+    ///     _ => Rgba([0x00, 0x00, 0xFF, 0xFF]),
+    /// });
+    /// let mut encoder = TextureEncoder::new_gcix(DataFormat::Rgb5a3)?;
+    /// let encoded = encoder.encode_image(DynamicImage::ImageRgba8(image))?;
+    ///
+    /// let mut decoder = TextureDecoder::new_from_buffer(encoded);
+    /// decoder.decode()?;
+    /// let mut encoder = TextureEncoder::new_gcix_palettized(PixelFormat::RGB5A3, DataFormat::Index8)?;
+    /// let reencoded = decoder.reencode(&mut encoder)?;
+    ///
+    /// let mut reencoded_decoder = TextureDecoder::new_from_buffer(reencoded);
+    /// reencoded_decoder.decode()?;
+    /// assert_eq!(reencoded_decoder.as_decoded(), decoder.as_decoded());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the image hasn't been decoded yet, a [`TextureDecodeError::Undecoded`] is returned. If
+    /// something goes wrong while encoding, a [`TextureDecodeError::Encode`] is returned.
+    pub fn reencode(&self, encoder: &mut TextureEncoder) -> Result<Vec<u8>, TextureDecodeError> {
+        let image = self.image.clone().ok_or(TextureDecodeError::Undecoded)?;
+        Ok(encoder.encode_image(DynamicImage::ImageRgba8(image))?)
+    }
+
     /// Saves the currently decoded image into a file, with a format of your choice.
     /// The format the file is saved in is derived from the file extension (.png, .jpg, etc.)
     /// in the given `path`.
@@ -488,30 +3688,4 @@ impl TextureDecoder {
         self.image.as_ref().unwrap().save(path)?;
         Ok(())
     }
-
-    fn read_string(&mut self, len: usize) -> Result<String, std::io::Error> {
-        let mut buf = vec![0; len];
-        self.cursor.read_exact(&mut buf)?;
-
-        let char_buf: Vec<char> = buf.into_iter().map(|e| e as char).collect();
-        let result: String = char_buf.into_iter().collect();
-        Ok(result)
-    }
-
-    /// This function checks if the magic strings "GCIX" and "GVRT" in the file match.
-    /// It doesn't check the actual validity of the data in the headers, that's done in
-    /// [`Self::decode()`]
-    fn is_valid_gvr(&mut self) -> Result<(), TextureDecodeError> {
-        let type_magic = self.read_string(4)?;
-        if type_magic != "GCIX" && type_magic != "GBIX" {
-            return Err(TextureDecodeError::InvalidFile);
-        }
-
-        self.cursor.seek(SeekFrom::Start(0x10))?;
-        let tex_magic = self.read_string(4)?;
-        if tex_magic != "GVRT" {
-            return Err(TextureDecodeError::InvalidFile);
-        }
-        Ok(())
-    }
 }
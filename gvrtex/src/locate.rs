@@ -0,0 +1,116 @@
+//! Contains the search for GVR textures embedded in larger buffers, such as memory dumps or
+//! archive formats that aren't otherwise supported.
+
+use crate::formats::TextureType;
+use crate::header::GvrHeader;
+use crate::layout::GVR_HEADER_SIZE;
+use crate::TextureDecoder;
+
+/// The location of a single GVR texture found inside a larger buffer with [`scan()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GvrLocation {
+    /// The offset of the start of the texture (the GCIX/GBIX header) in the buffer.
+    pub offset: usize,
+    /// The full length of the texture in bytes, including the headers.
+    pub len: usize,
+}
+
+/// Searches the given `buffer` for GVR textures, returning the location of each texture found, in
+/// the order they appear in.
+///
+/// This is useful for extracting textures out of memory dumps or archive formats that aren't
+/// otherwise supported. Only textures that are fully contained in `buffer` are returned.
+///
+/// To minimize false positives, every texture found has to have valid magic strings in both of its
+/// headers, along with valid flags and a valid data format.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+/// use gvrtex::{scan, GvrLocation, TextureEncoder};
+/// use image::{DynamicImage, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = DynamicImage::ImageRgba8(RgbaImage::new(8, 8));
+/// let first = TextureEncoder::new_gcix(DataFormat::Rgb565)?.encode_image(image.clone())?;
+/// let second = TextureEncoder::new_gbix(DataFormat::Argb8888)?.encode_image(image)?;
+///
+/// // Two textures among junk, including a magic string without a valid texture behind it
+/// let mut buffer = vec![0xAB; 13];
+/// buffer.extend_from_slice(b"GCIX\x08\0\0\0junk");
+/// buffer.extend_from_slice(&first);
+/// buffer.extend_from_slice(&[0xCD; 7]);
+/// let second_offset = buffer.len();
+/// buffer.extend_from_slice(&second);
+/// buffer.extend_from_slice(b"GBIX");
+///
+/// assert_eq!(
+///     scan(&buffer),
+///     [
+///         GvrLocation { offset: 25, len: first.len() },
+///         GvrLocation { offset: second_offset, len: second.len() },
+///     ]
+/// );
+///
+/// // Each texture can be decoded on its own
+/// let decoders = gvrtex::extract_all(&buffer);
+/// assert_eq!(decoders.len(), 2);
+/// for mut decoder in decoders {
+///     decoder.decode()?;
+///     assert_eq!(decoder.into_decoded()?.dimensions(), (8, 8));
+/// }
+///
+/// // A texture cut off by the end of the buffer isn't found
+/// assert_eq!(scan(&buffer[..second_offset + second.len() - 1]).len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn scan(buffer: &[u8]) -> Vec<GvrLocation> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+
+    while offset + GVR_HEADER_SIZE <= buffer.len() {
+        match texture_len_at(buffer, offset) {
+            Some(len) => {
+                result.push(GvrLocation { offset, len });
+                offset += len;
+            }
+            None => offset += 1,
+        }
+    }
+
+    result
+}
+
+/// Searches the given `buffer` for GVR textures with [`scan()`], returning a [`TextureDecoder`] for
+/// each of the textures found.
+///
+/// Each decoder holds a copy of just its own texture, so the `buffer` doesn't need to be kept
+/// around afterwards. None of the textures are decoded yet, [`TextureDecoder::decode()`] must be
+/// called on each of them.
+pub fn extract_all(buffer: &[u8]) -> Vec<TextureDecoder> {
+    scan(buffer)
+        .into_iter()
+        .map(|location| {
+            let texture = &buffer[location.offset..location.offset + location.len];
+            TextureDecoder::new_from_buffer(texture.to_vec())
+        })
+        .collect()
+}
+
+/// Checks if a valid GVR texture starts at the given `offset` in `buffer`, returning the full
+/// length of the texture if so.
+fn texture_len_at(buffer: &[u8], offset: usize) -> Option<usize> {
+    let header = GvrHeader::read(&mut &buffer[offset..]).ok()?;
+    if header.texture_type == TextureType::None {
+        return None;
+    }
+
+    let len = header.size().checked_add(header.data_len as usize)?;
+    if offset.checked_add(len)? > buffer.len() {
+        return None;
+    }
+
+    Some(len)
+}
@@ -0,0 +1,97 @@
+use crate::formats::DataFlags;
+use crate::header::GvrHeader;
+use crate::pixel_codecs::encode_palette;
+use crate::TextureDecodeError;
+use image::Rgba;
+
+/// Replaces the color palette of the given palettized GVR texture with `new_palette`, returning
+/// the modified texture file.
+///
+/// The new palette is encoded in the same [`crate::formats::PixelFormat`] as the original
+/// palette. Every other byte of the file, including the palette indices, is left untouched. This
+/// is useful for creating color variants of a texture, which many games do purely by swapping out
+/// the palette.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::error::TextureDecodeError;
+/// use gvrtex::formats::{DataFormat, PixelFormat};
+/// use gvrtex::{palette_swap, GvrHeader, TextureDecoder, TextureEncoder};
+/// use image::{DynamicImage, Rgba, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = RgbaImage::from_fn(8, 8, |x, y| Rgba([(x + y) as u8 * 0x10, 0, 0, 0xFF]));
+/// let gvr = TextureEncoder::new_gcix_palettized(PixelFormat::RGB565, DataFormat::Index4)?
+///     .encode_image(DynamicImage::ImageRgba8(image))?;
+///
+/// let mut decoder = TextureDecoder::new_from_buffer(gvr.clone());
+/// decoder.decode()?;
+/// let inverted: Vec<_> = decoder
+///     .palette()
+///     .unwrap()
+///     .iter()
+///     .map(|c| Rgba([0xFF - c.0[0], 0xFF - c.0[1], 0xFF - c.0[2], 0xFF]))
+///     .collect();
+/// let swapped = palette_swap(&gvr, &inverted)?;
+///
+/// // Only the 16 colors of the palette change, the header and the indices stay the same
+/// let palette_start = GvrHeader::read(&mut gvr.as_slice())?.size();
+/// let palette = palette_start..palette_start + 16 * 2;
+/// assert_eq!(swapped.len(), gvr.len());
+/// assert_ne!(swapped[palette.clone()], gvr[palette.clone()]);
+/// assert_eq!(swapped[..palette.start], gvr[..palette.start]);
+/// assert_eq!(swapped[palette.end..], gvr[palette.end..]);
+///
+/// let mut decoder = TextureDecoder::new_from_buffer(swapped);
+/// decoder.decode()?;
+/// assert_eq!(decoder.palette().unwrap(), inverted);
+///
+/// assert!(matches!(
+///     palette_swap(&gvr, &inverted[..15]),
+///     Err(TextureDecodeError::PaletteLength(16, 15))
+/// ));
+///
+/// let rgb565 = TextureEncoder::new_gcix(DataFormat::Rgb565)?
+///     .encode_image(DynamicImage::ImageRgba8(RgbaImage::new(8, 8)))?;
+/// assert!(matches!(
+///     palette_swap(&rgb565, &inverted),
+///     Err(TextureDecodeError::InvalidFile)
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If the texture isn't a valid GVR texture with an internal color palette (meaning it has to be
+/// a [`crate::formats::DataFormat::Index4`] or [`crate::formats::DataFormat::Index8`] texture), a
+/// [`TextureDecodeError::InvalidFile`] is returned. If `new_palette` doesn't have exactly as many
+/// colors as the palette of the texture (usually 16 or 256, see [`GvrHeader::palette_colors()`]),
+/// a [`TextureDecodeError::PaletteLength`] is returned. If the texture is cut short, a
+/// [`TextureDecodeError::SizeMismatch`] is returned.
+pub fn palette_swap(
+    gvr_bytes: &[u8],
+    new_palette: &[Rgba<u8>],
+) -> Result<Vec<u8>, TextureDecodeError> {
+    let header = GvrHeader::read(&mut &gvr_bytes[..])?;
+    if !header.flags.intersects(DataFlags::InternalPalette) {
+        return Err(TextureDecodeError::InvalidFile);
+    }
+
+    let palette_start = header.size();
+    header.check_data_len(gvr_bytes.len().saturating_sub(palette_start) as u64)?;
+
+    let colors = header.palette_colors().unwrap_or_default();
+    if new_palette.len() != colors {
+        return Err(TextureDecodeError::PaletteLength(colors, new_palette.len()));
+    }
+
+    let encoded = encode_palette(new_palette.iter().map(|c| c.0), header.pixel_format);
+    let palette_end = palette_start + encoded.len();
+
+    let mut result = gvr_bytes.to_vec();
+    result[palette_start..palette_end].copy_from_slice(&encoded);
+
+    Ok(result)
+}
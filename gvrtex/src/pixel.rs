@@ -0,0 +1,381 @@
+//! Contains the per-pixel conversions between RGBA colors and the pixel formats used in GVR
+//! textures.
+//!
+//! Unlike the rest of the crate, this module doesn't depend on the standard library or the
+//! [`image`](https://docs.rs/image) crate, so it can be used in `no_std` environments by disabling
+//! the default `std` feature. Colors are passed around as raw `[r, g, b, a]` arrays.
+//!
+//! Note that these functions only convert single pixels (or runs of pixels), they don't arrange
+//! the pixels into the blocks that the texture data is stored in.
+
+use alloc::vec::Vec;
+
+/// The lowest alpha value that [`encode_rgb5a3`] encodes as fully opaque.
+///
+/// Colors with transparency only get 3 bits of alpha in RGB5A3, so the alpha is rounded to the
+/// nearest of the 8 levels it can represent. Alpha values from this threshold upwards are closest
+/// to the fully opaque level, which is encoded as RGB555 instead to keep more color precision.
+pub const RGB5A3_OPAQUE_THRESHOLD: u8 = 0xED;
+
+/// Expands a channel with the given amount of `bits` to 8 bits, by repeating its bits until all
+/// 8 bits are filled. This maps 0 to 0 and the highest value to 0xFF, spreading the rest evenly
+/// in between, the same way the GameCube and Wii expand colors.
+const fn expand_bits(value: u8, bits: i32) -> u8 {
+    let mut expanded = 0;
+    let mut shift = 8 - bits;
+    while shift > -bits {
+        if shift >= 0 {
+            expanded |= value << shift;
+        } else {
+            expanded |= value >> -shift;
+        }
+        shift -= bits;
+    }
+    expanded
+}
+
+/// Builds the lookup table of every channel with the given amount of `bits` expanded to 8 bits.
+const fn expansion_table<const N: usize>(bits: i32) -> [u8; N] {
+    let mut table = [0; N];
+    let mut value = 0;
+    while value < N {
+        table[value] = expand_bits(value as u8, bits);
+        value += 1;
+    }
+    table
+}
+
+/// 3-bit channels expanded to 8 bits.
+pub(crate) const EXPAND_3: [u8; 8] = expansion_table(3);
+/// 4-bit channels expanded to 8 bits.
+pub(crate) const EXPAND_4: [u8; 16] = expansion_table(4);
+/// 5-bit channels expanded to 8 bits.
+pub(crate) const EXPAND_5: [u8; 32] = expansion_table(5);
+/// 6-bit channels expanded to 8 bits.
+pub(crate) const EXPAND_6: [u8; 64] = expansion_table(6);
+
+/// Encodes the given color into an RGB5A3 pixel.
+///
+/// Colors with an alpha value of at least [`RGB5A3_OPAQUE_THRESHOLD`] are stored as opaque RGB555,
+/// while colors with transparency are stored as ARGB3444.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::pixel::{decode_rgb5a3, encode_rgb5a3};
+///
+/// // Near-opaque colors keep their transparency, at the precision of 3 bits
+/// let pixel = encode_rgb5a3([0xFF, 0xFF, 0xFF, 230]);
+/// assert_eq!(pixel & 0x8000, 0);
+/// assert_eq!(decode_rgb5a3(pixel)[3], 219);
+///
+/// // Colors closer to fully opaque than to the next lower level are stored as opaque
+/// let pixel = encode_rgb5a3([0xFF, 0xFF, 0xFF, 240]);
+/// assert_eq!(decode_rgb5a3(pixel)[3], 0xFF);
+/// ```
+pub fn encode_rgb5a3(p: [u8; 4]) -> u16 {
+    let mut pixel: u16 = 0;
+    if p[3] < RGB5A3_OPAQUE_THRESHOLD {
+        // Argb3444, with the alpha rounded to the nearest 3-bit level
+        let alpha = (p[3] as u16 * 7 + 127) / 255;
+        pixel |= ((p[0] >> 4) as u16) << 8;
+        pixel |= ((p[1] >> 4) as u16) << 4;
+        pixel |= (p[2] >> 4) as u16;
+        pixel |= alpha << 12;
+    } else {
+        // Rgb555
+        pixel |= ((p[0] >> 3) as u16) << 10;
+        pixel |= ((p[1] >> 3) as u16) << 5;
+        pixel |= (p[2] >> 3) as u16;
+        pixel |= 0x8000;
+    }
+    pixel
+}
+
+/// Encodes the given color into an RGB565 pixel, dropping the alpha channel.
+pub fn encode_rgb565(p: [u8; 4]) -> u16 {
+    let mut pixel: u16 = 0x0000;
+    pixel |= ((p[0] >> 3) as u16) << 11;
+    pixel |= ((p[1] >> 2) as u16) << 5;
+    pixel |= (p[2] >> 3) as u16;
+    pixel
+}
+
+/// Returns the luminance of the given color, scaled up by 100.
+///
+/// Integer weights are used so that grayscale colors map back to their exact intensity.
+fn luminance_x100(p: [u8; 4]) -> u32 {
+    30 * p[0] as u32 + 59 * p[1] as u32 + 11 * p[2] as u32
+}
+
+/// Returns the 8-bit intensity (luminance) of the given color.
+pub fn encode_intensity8(p: [u8; 4]) -> u8 {
+    (luminance_x100(p) / 100) as u8
+}
+
+/// Returns the 4-bit intensity (luminance) of the given color.
+pub fn encode_intensity4(p: [u8; 4]) -> u8 {
+    ((luminance_x100(p) * 15 / (100 * 255)) as u8) & 0xF
+}
+
+/// Encodes the given color into an IA4 pixel, with the alpha in the upper 4 bits and the
+/// intensity in the lower 4 bits.
+pub fn encode_intensity_alpha4(p: [u8; 4]) -> u8 {
+    let alpha = ((p[3] as f32 * 15. / 255.) as u8) & 0xF;
+    encode_intensity4(p) | (alpha << 4)
+}
+
+/// Encodes the given color into an IA8 pixel, returning the intensity and alpha as a tuple.
+pub fn encode_intensity_alpha8(p: [u8; 4]) -> (u8, u8) {
+    (encode_intensity8(p), p[3])
+}
+
+/// Decodes the given RGB5A3 pixel into a color.
+///
+/// The decoders expand each channel to 8 bits by repeating its bits, like the GameCube and Wii
+/// do. For example, a 5-bit channel of `x` becomes `(x << 3) | (x >> 2)`, which is up to 1 higher
+/// than scaling the channel with `x * 255 / 31` and truncating the result.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::pixel::decode_rgb5a3;
+///
+/// // Rgb555, with channels of 0x1F, 0x04 and 0x00
+/// assert_eq!(decode_rgb5a3(0xFC80), [0xFF, 0x21, 0x00, 0xFF]);
+/// // Argb3444, with an alpha of 2 and channels of 0xF, 0x8 and 0x1
+/// assert_eq!(decode_rgb5a3(0x2F81), [0xFF, 0x88, 0x11, 0x49]);
+/// ```
+pub fn decode_rgb5a3(pixel: u16) -> [u8; 4] {
+    if (pixel & 0x8000) != 0 {
+        // Rgb555
+        let r = EXPAND_5[((pixel >> 10) & 0x1F) as usize];
+        let g = EXPAND_5[((pixel >> 5) & 0x1F) as usize];
+        let b = EXPAND_5[(pixel & 0x1F) as usize];
+        [r, g, b, 0xFF]
+    } else {
+        // Argb3444
+        let r = EXPAND_4[((pixel >> 8) & 0x0F) as usize];
+        let g = EXPAND_4[((pixel >> 4) & 0x0F) as usize];
+        let b = EXPAND_4[(pixel & 0x0F) as usize];
+        let a = EXPAND_3[((pixel >> 12) & 0x07) as usize];
+        [r, g, b, a]
+    }
+}
+
+/// Decodes the given RGB565 pixel into an opaque color.
+///
+/// The channels are expanded to 8 bits by repeating their bits, see [`decode_rgb5a3`].
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::pixel::decode_rgb565;
+///
+/// // Channels of 0x04, 0x21 and 0x1F
+/// assert_eq!(decode_rgb565(0x243F), [0x21, 0x86, 0xFF, 0xFF]);
+/// ```
+pub fn decode_rgb565(pixel: u16) -> [u8; 4] {
+    let r = EXPAND_5[((pixel >> 11) & 0x1F) as usize];
+    let g = EXPAND_6[((pixel >> 5) & 0x3F) as usize];
+    let b = EXPAND_5[(pixel & 0x1F) as usize];
+    [r, g, b, 0xFF]
+}
+
+/// Decodes the given 8-bit intensity into an opaque grayscale color.
+pub fn decode_intensity8(pixel: u8) -> [u8; 4] {
+    [pixel, pixel, pixel, 0xFF]
+}
+
+/// Decodes the given 4-bit intensity (stored in the lower 4 bits) into an opaque grayscale color.
+pub fn decode_intensity4(pixel: u8) -> [u8; 4] {
+    let c = EXPAND_4[(pixel & 0x0F) as usize];
+    [c, c, c, 0xFF]
+}
+
+/// Decodes the given IA4 pixel into a grayscale color.
+pub fn decode_intensity_alpha4(pixel: u8) -> [u8; 4] {
+    let c = EXPAND_4[(pixel & 0x0F) as usize];
+    let a = EXPAND_4[((pixel >> 4) & 0x0F) as usize];
+    [c, c, c, a]
+}
+
+/// Decodes the given IA8 intensity and alpha pair into a grayscale color.
+pub fn decode_intensity_alpha8(pixel: u8, alpha: u8) -> [u8; 4] {
+    [pixel, pixel, pixel, alpha]
+}
+
+/// Turns the given color with premultiplied alpha into one with straight alpha, by dividing its
+/// color channels by its alpha. Fully transparent colors are returned as they are, as their
+/// original color can't be recovered.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::pixel::unpremultiply_alpha;
+///
+/// assert_eq!(unpremultiply_alpha([0x40, 0x20, 0x00, 0x80]), [0x80, 0x40, 0x00, 0x80]);
+/// assert_eq!(unpremultiply_alpha([0x12, 0x34, 0x56, 0xFF]), [0x12, 0x34, 0x56, 0xFF]);
+/// assert_eq!(unpremultiply_alpha([0x12, 0x34, 0x56, 0x00]), [0x12, 0x34, 0x56, 0x00]);
+/// ```
+pub fn unpremultiply_alpha(p: [u8; 4]) -> [u8; 4] {
+    let alpha = p[3] as u32;
+    if alpha == 0 {
+        return p;
+    }
+
+    let unpremultiply = |c: u8| ((c as u32 * 0xFF + alpha / 2) / alpha).min(0xFF) as u8;
+    [
+        unpremultiply(p[0]),
+        unpremultiply(p[1]),
+        unpremultiply(p[2]),
+        p[3],
+    ]
+}
+
+/// Turns the given color with straight alpha into one with premultiplied alpha, by multiplying
+/// its color channels by its alpha. This is the opposite of [`unpremultiply_alpha`].
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::pixel::premultiply_alpha;
+///
+/// assert_eq!(premultiply_alpha([0x80, 0x40, 0x00, 0x80]), [0x40, 0x20, 0x00, 0x80]);
+/// assert_eq!(premultiply_alpha([0x12, 0x34, 0x56, 0xFF]), [0x12, 0x34, 0x56, 0xFF]);
+/// assert_eq!(premultiply_alpha([0x12, 0x34, 0x56, 0x00]), [0x00, 0x00, 0x00, 0x00]);
+/// ```
+pub fn premultiply_alpha(p: [u8; 4]) -> [u8; 4] {
+    let alpha = p[3] as u32;
+    let premultiply = |c: u8| ((c as u32 * alpha + 0x7F) / 0xFF) as u8;
+    [
+        premultiply(p[0]),
+        premultiply(p[1]),
+        premultiply(p[2]),
+        p[3],
+    ]
+}
+
+/// Encodes the given `pixels` into RGB565 with [`encode_rgb565`], appending them to `dest` as big
+/// endian bytes.
+///
+/// With the `simd` feature enabled on x86_64, 8 pixels are encoded at a time with SSE2. The
+/// result is the same either way.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::pixel::{encode_rgb565, encode_rgb565_pixels};
+///
+/// let mut state = 0x2545_F491_u32;
+/// let mut random_pixel = || {
+///     state ^= state << 13;
+///     state ^= state >> 17;
+///     state ^= state << 5;
+///     state.to_le_bytes()
+/// };
+///
+/// for len in [0, 1, 7, 8, 9, 16, 64, 1000] {
+///     let pixels: Vec<[u8; 4]> = (0..len).map(|_| random_pixel()).collect();
+///     let mut dest = Vec::new();
+///     encode_rgb565_pixels(&pixels, &mut dest);
+///
+///     let expected: Vec<u8> = pixels.iter().flat_map(|&p| encode_rgb565(p).to_be_bytes()).collect();
+///     assert_eq!(dest, expected);
+/// }
+/// ```
+pub fn encode_rgb565_pixels(pixels: &[[u8; 4]], dest: &mut Vec<u8>) {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    let pixels = {
+        let (vectorized, rest) = split_simd_lanes(pixels);
+        crate::pixel_simd::encode_rgb565(vectorized, dest);
+        rest
+    };
+
+    for &p in pixels {
+        dest.extend_from_slice(&encode_rgb565(p).to_be_bytes());
+    }
+}
+
+/// Encodes the given `pixels` into RGB5A3 with [`encode_rgb5a3`], appending them to `dest` as big
+/// endian bytes.
+///
+/// With the `simd` feature enabled on x86_64, 8 pixels are encoded at a time with SSE2. The
+/// result is the same either way.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::pixel::{encode_rgb5a3, encode_rgb5a3_pixels};
+///
+/// let mut state = 0x2545_F491_u32;
+/// let mut random_pixel = || {
+///     state ^= state << 13;
+///     state ^= state >> 17;
+///     state ^= state << 5;
+///     state.to_le_bytes()
+/// };
+///
+/// for len in [0, 1, 7, 8, 9, 16, 64, 1000] {
+///     let pixels: Vec<[u8; 4]> = (0..len).map(|_| random_pixel()).collect();
+///     let mut dest = Vec::new();
+///     encode_rgb5a3_pixels(&pixels, &mut dest);
+///
+///     let expected: Vec<u8> = pixels.iter().flat_map(|&p| encode_rgb5a3(p).to_be_bytes()).collect();
+///     assert_eq!(dest, expected);
+/// }
+///
+/// // Every alpha value, on both sides of the opaque threshold
+/// let pixels: Vec<[u8; 4]> = (0..=255).map(|a| [0x9C, 0x5A, 0x31, a]).collect();
+/// let mut dest = Vec::new();
+/// encode_rgb5a3_pixels(&pixels, &mut dest);
+/// let expected: Vec<u8> = pixels.iter().flat_map(|&p| encode_rgb5a3(p).to_be_bytes()).collect();
+/// assert_eq!(dest, expected);
+/// ```
+pub fn encode_rgb5a3_pixels(pixels: &[[u8; 4]], dest: &mut Vec<u8>) {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    let pixels = {
+        let (vectorized, rest) = split_simd_lanes(pixels);
+        crate::pixel_simd::encode_rgb5a3(vectorized, dest);
+        rest
+    };
+
+    for &p in pixels {
+        dest.extend_from_slice(&encode_rgb5a3(p).to_be_bytes());
+    }
+}
+
+/// Splits the given `pixels` into the ones that fill out whole SIMD vectors, and the rest.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+fn split_simd_lanes(pixels: &[[u8; 4]]) -> (&[[u8; 4]], &[[u8; 4]]) {
+    pixels.split_at(pixels.len() - pixels.len() % crate::pixel_simd::LANES)
+}
+
+/// Encodes a run of raw RGBA pixels (4 bytes per pixel) with the given 16-bit pixel `encode`
+/// function, such as [`encode_rgb565`], returning the pixels as big endian bytes.
+///
+/// Any trailing bytes that don't make up a full pixel are ignored.
+pub fn encode_pixels_u16(rgba: &[u8], encode: fn([u8; 4]) -> u16) -> Vec<u8> {
+    let mut result = Vec::with_capacity(rgba.len() / 2);
+
+    for p in rgba.chunks_exact(4) {
+        let pixel = encode([p[0], p[1], p[2], p[3]]);
+        result.extend_from_slice(&pixel.to_be_bytes());
+    }
+
+    result
+}
+
+/// Decodes a run of big endian 16-bit pixels with the given `decode` function, such as
+/// [`decode_rgb565`], returning the raw RGBA pixels (4 bytes per pixel).
+///
+/// Any trailing byte that doesn't make up a full pixel is ignored.
+pub fn decode_pixels_u16(data: &[u8], decode: fn(u16) -> [u8; 4]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len() * 2);
+
+    for p in data.chunks_exact(2) {
+        result.extend_from_slice(&decode(u16::from_be_bytes([p[0], p[1]])));
+    }
+
+    result
+}
@@ -2,15 +2,18 @@ use crate::{
     codec::{
         GvrBase, GvrDecoder, GvrDecoderPalette, GvrEncoder, GvrEncoderBase, GvrEncoderPalette,
     },
-    formats::{DataFormat, PixelFormat},
+    formats::{DataFormat, DxtLayout, PixelFormat},
     iter::{
         DecodeDxtBlockIterator, EncodeDxtBlockIterator, PixelBlockIterator, PixelBlockIteratorExt,
+        PixelBlockViewIterator,
     },
+    pixel, DxtStats,
 };
 use byteorder::{BigEndian, ReadBytesExt};
 use gvrtex_macros::{gvr_decoder_base, gvr_encoder_base};
-use image::{Pixel, Rgba, RgbaImage};
-use std::io::{Cursor, Seek};
+use image::{DynamicImage, GrayAlphaImage, GrayImage, Luma, LumaA, Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::io::Read;
 
 const INDEX4_PALETTE_SIZE: u32 = 16;
 const INDEX8_PALETTE_SIZE: u32 = 256;
@@ -34,8 +37,43 @@ fn as_imagequant_vec(
         .collect()
 }
 
-/// Uses [`imagequant`] to turn the given `image` into a color palette with each pixel mapped to an
-/// index into the palette.
+/// Builds a color palette out of the exact colors in the given `pixels`, in the order they first
+/// appear in, with each pixel mapped to an index into the palette.
+///
+/// If there are more than `max_colors` unique colors in the image, [`None`] is returned.
+fn exact_palette(
+    pixels: &[imagequant::RGBA],
+    max_colors: u32,
+) -> Option<(Vec<imagequant::RGBA>, Vec<u8>)> {
+    let mut palette = Vec::new();
+    let mut lookup = HashMap::new();
+    let mut indices = Vec::with_capacity(pixels.len());
+
+    for pixel in pixels {
+        let index = match lookup.get(pixel) {
+            Some(&index) => index,
+            None => {
+                if palette.len() == max_colors as usize {
+                    return None;
+                }
+
+                let index = palette.len() as u8;
+                palette.push(*pixel);
+                lookup.insert(*pixel, index);
+                index
+            }
+        };
+        indices.push(index);
+    }
+
+    Some((palette, indices))
+}
+
+/// Turns the given `image` into a color palette with each pixel mapped to an index into the
+/// palette.
+///
+/// If the image has at most `max_colors` unique colors, the palette is built out of those exact
+/// colors. Otherwise [`imagequant`] is used to quantize the image down to `max_colors` colors.
 ///
 /// `max_colors` determines how many colors the palette should consist of. If there isn't enough
 /// colors in the provided image (less than `max_colors`), the resulting palette gets padded with
@@ -45,14 +83,17 @@ fn palettize_image(
     max_colors: u32,
     palette_pixel_format: PixelFormat,
 ) -> Result<(Vec<imagequant::RGBA>, Vec<u8>), imagequant::Error> {
+    let pixels = as_imagequant_vec(image, palette_pixel_format);
+
+    if let Some((mut palette, indices)) = exact_palette(&pixels, max_colors) {
+        palette.resize(max_colors as usize, imagequant::RGBA::new(0, 0, 0, 0));
+        return Ok((palette, indices));
+    }
+
     let mut attr = imagequant::new();
     attr.set_max_colors(max_colors)?;
-    let mut imagequant_img = attr.new_image(
-        as_imagequant_vec(image, palette_pixel_format),
-        image.width() as usize,
-        image.height() as usize,
-        0.,
-    )?;
+    let mut imagequant_img =
+        attr.new_image(pixels, image.width() as usize, image.height() as usize, 0.)?;
 
     let mut quantized = attr.quantize(&mut imagequant_img)?;
     let (mut palette, indices) = quantized.remapped(&mut imagequant_img)?;
@@ -69,30 +110,28 @@ fn palettize_image(
     Ok((palette, indices))
 }
 
-/// Encodes the given `palette` into the suitable [`PixelFormat`], returning a [`Vec`] of bytes.
-fn encode_palette(palette: Vec<imagequant::RGBA>, palette_pixel_format: PixelFormat) -> Vec<u8> {
+/// Encodes the given `palette` of RGBA colors into the suitable [`PixelFormat`], returning a
+/// [`Vec`] of bytes.
+pub fn encode_palette(
+    palette: impl IntoIterator<Item = [u8; 4]>,
+    palette_pixel_format: PixelFormat,
+) -> Vec<u8> {
     let mut result: Vec<u8> = Vec::new();
 
     for color in palette {
         match palette_pixel_format {
             PixelFormat::RGB5A3 => {
-                let color_slice = [color.r, color.g, color.b, color.a];
-                let p = Rgba::from_slice(&color_slice);
-                let pixel = encode_pixel_rgb5a3(p);
+                let pixel = pixel::encode_rgb5a3(color);
                 result.push(((pixel >> 8) & 0xFF).try_into().unwrap());
                 result.push((pixel & 0xFF).try_into().unwrap());
             }
             PixelFormat::RGB565 => {
-                let color_slice = [color.r, color.g, color.b, color.a];
-                let p = Rgba::from_slice(&color_slice);
-                let pixel = encode_pixel_rgb565(p);
+                let pixel = pixel::encode_rgb565(color);
                 result.push(((pixel >> 8) & 0xFF).try_into().unwrap());
                 result.push((pixel & 0xFF).try_into().unwrap());
             }
             PixelFormat::IntensityA8 => {
-                let color_slice = [color.r, color.g, color.b, color.a];
-                let p = Rgba::from_slice(&color_slice);
-                let (pixel, alpha) = encode_pixel_intensity_alpha8(p);
+                let (pixel, alpha) = pixel::encode_intensity_alpha8(color);
                 result.push(alpha);
                 result.push(pixel);
             }
@@ -103,7 +142,7 @@ fn encode_palette(palette: Vec<imagequant::RGBA>, palette_pixel_format: PixelFor
 }
 
 fn decode_palette(
-    cursor: &mut Cursor<&[u8]>,
+    reader: &mut dyn Read,
     palette_pixel_format: PixelFormat,
     palette_size: u32,
 ) -> Result<Vec<Rgba<u8>>, std::io::Error> {
@@ -112,17 +151,17 @@ fn decode_palette(
     for _ in 0..palette_size {
         match palette_pixel_format {
             PixelFormat::IntensityA8 => {
-                let alpha = cursor.read_u8()?;
-                let pixel = cursor.read_u8()?;
-                result.push(decode_pixel_intensity_alpha8(pixel, alpha));
+                let alpha = reader.read_u8()?;
+                let pixel = reader.read_u8()?;
+                result.push(Rgba(pixel::decode_intensity_alpha8(pixel, alpha)));
             }
             PixelFormat::RGB565 => {
-                let color = cursor.read_u16::<BigEndian>()?;
-                result.push(decode_pixel_rgb565(color));
+                let color = reader.read_u16::<BigEndian>()?;
+                result.push(Rgba(pixel::decode_rgb565(color)));
             }
             PixelFormat::RGB5A3 => {
-                let color = cursor.read_u16::<BigEndian>()?;
-                result.push(decode_pixel_rgb5a3(color));
+                let color = reader.read_u16::<BigEndian>()?;
+                result.push(Rgba(pixel::decode_rgb5a3(color)));
             }
         }
     }
@@ -130,50 +169,43 @@ fn decode_palette(
     Ok(result)
 }
 
-////////////////////////
-// Encoding Functions //
-////////////////////////
+/// Decodes a color palette of the given amount of `colors` followed by the palette indices, for
+/// textures whose palette is shorter than what `data_format` allows for. The rest of the palette
+/// is filled with transparent black.
+pub fn decode_indexed_short(
+    reader: &mut dyn Read,
+    data_format: DataFormat,
+    width: u32,
+    height: u32,
+    palette_pixel_format: PixelFormat,
+    colors: usize,
+) -> Result<(Vec<Rgba<u8>>, Vec<u8>), std::io::Error> {
+    let mut palette = decode_palette(reader, palette_pixel_format, colors as u32)?;
+    palette.resize(palette_size(data_format) as usize, Rgba([0, 0, 0, 0]));
 
-fn encode_pixel_rgb5a3(p: &Rgba<u8>) -> u16 {
-    let mut pixel: u16 = 0;
-    if p.0[3] <= 0xDA {
-        // Argb3444
-        pixel |= ((p.0[0] >> 4) as u16) << 8;
-        pixel |= ((p.0[1] >> 4) as u16) << 4;
-        pixel |= (p.0[2] >> 4) as u16;
-        pixel |= ((p.0[3] >> 5) as u16) << 12;
-    } else {
-        // Rgb555
-        pixel |= ((p.0[0] >> 3) as u16) << 10;
-        pixel |= ((p.0[1] >> 3) as u16) << 5;
-        pixel |= (p.0[2] >> 3) as u16;
-        pixel |= 0x8000;
-    }
-    pixel
-}
+    let decoder = create_new_decoder_with_palette(data_format);
+    let indices = decoder.decode_indices(reader, width, height)?;
 
-fn encode_pixel_rgb565(p: &Rgba<u8>) -> u16 {
-    let mut pixel: u16 = 0x0000;
-    pixel |= ((p.0[0] >> 3) as u16) << 11;
-    pixel |= ((p.0[1] >> 2) as u16) << 5;
-    pixel |= (p.0[2] >> 3) as u16;
-    pixel
+    Ok((palette, indices))
 }
 
-fn encode_pixel_intensity_alpha8(p: &Rgba<u8>) -> (u8, u8) {
-    let pixel = (0.30 * p.0[0] as f32 + 0.59 * p.0[1] as f32 + 0.11 * p.0[2] as f32) as u8;
-    (pixel, p.0[3])
-}
+////////////////////////
+// Encoding Functions //
+////////////////////////
 
-fn compress_block_to_bc1(block: &[u8]) -> Vec<u8> {
+/// Compresses the given 4x4 `block` of BGRA pixels into a single BC1 block.
+///
+/// Pixels with an alpha value below `alpha_threshold` are treated as fully transparent, all other
+/// pixels are treated as fully opaque. The kind of block that was made is counted in `stats`.
+fn compress_block_to_bc1(block: &[u8; 64], alpha_threshold: u8, stats: &mut DxtStats) -> [u8; 8] {
     let mut dist: Option<i32> = None;
     let mut col_1 = 0;
     let mut col_2 = 0;
     let mut alpha = false;
-    let mut result = vec![0u8; 8];
+    let mut result = [0u8; 8];
 
-    for i in 0..15 {
-        if block[i * 4 + 3] < 16 {
+    for i in 0..16 {
+        if block[i * 4 + 3] < alpha_threshold {
             alpha = true;
         } else {
             for j in (i + 1)..16 {
@@ -188,33 +220,35 @@ fn compress_block_to_bc1(block: &[u8]) -> Vec<u8> {
         }
     }
 
-    let mut palette: Vec<Vec<u8>> = Vec::with_capacity(4);
+    // The last two colors are filled in once the first two are in their final order
+    let mut palette = [[0u8; 4]; 4];
 
     if dist.is_none() {
-        palette.push(vec![0, 0, 0, 0xff]);
-        palette.push(vec![0xff, 0xff, 0xff, 0xff]);
+        palette[0] = [0, 0, 0, 0xff];
+        palette[1] = [0xff, 0xff, 0xff, 0xff];
     } else {
         let color1_idx = col_1 * 4;
         let color2_idx = col_2 * 4;
 
-        palette.push(vec![
+        palette[0] = [
             block[color1_idx],
             block[color1_idx + 1],
             block[color1_idx + 2],
             0xff,
-        ]);
+        ];
 
-        palette.push(vec![
+        palette[1] = [
             block[color2_idx],
             block[color2_idx + 1],
             block[color2_idx + 2],
             0xff,
-        ]);
+        ];
 
         if palette[0][0] >> 3 == palette[1][0] >> 3
             && palette[0][1] >> 2 == palette[1][1] >> 2
             && palette[0][2] >> 3 == palette[1][2] >> 3
         {
+            stats.single_color_blocks += 1;
             if palette[0][0] >> 3 == 0 && palette[0][1] >> 2 == 0 && palette[0][2] >> 3 == 0 {
                 palette[1][0] = 0xff;
                 palette[1][1] = 0xff;
@@ -227,7 +261,10 @@ fn compress_block_to_bc1(block: &[u8]) -> Vec<u8> {
         }
     }
 
-    palette.resize(4, vec![]);
+    stats.total_blocks += 1;
+    if alpha {
+        stats.alpha_blocks += 1;
+    }
 
     result[0] = palette[0][2] & 0xf8 | palette[0][1] >> 5;
     result[1] = palette[0][1] << 3 & 0xe0 | palette[0][0] >> 3;
@@ -235,55 +272,54 @@ fn compress_block_to_bc1(block: &[u8]) -> Vec<u8> {
     result[3] = palette[1][1] << 3 & 0xe0 | palette[1][0] >> 3;
 
     if (result[0] > result[2] || (result[0] == result[2] && result[1] >= result[3])) == alpha {
-        result[4] = result[0];
-        result[5] = result[1];
-        result[0] = result[2];
-        result[1] = result[3];
-        result[2] = result[4];
-        result[3] = result[5];
+        result[..4].rotate_left(2);
 
-        palette[2] = palette[0].clone();
-        palette[0] = palette[1].clone();
-        palette[1] = palette[2].clone();
+        palette.swap(0, 1);
     }
 
     if !alpha {
-        palette[2] = vec![
+        palette[2] = [
             ((((palette[0][0] as u32) << 1) + palette[1][0] as u32) / 3) as u8,
             ((((palette[0][1] as u32) << 1) + palette[1][1] as u32) / 3) as u8,
             ((((palette[0][2] as u32) << 1) + palette[1][2] as u32) / 3) as u8,
             0xff,
         ];
 
-        palette[3] = vec![
+        palette[3] = [
             ((palette[0][0] as u32 + ((palette[1][0] as u32) << 1)) / 3) as u8,
             ((palette[0][1] as u32 + ((palette[1][1] as u32) << 1)) / 3) as u8,
             ((palette[0][2] as u32 + ((palette[1][2] as u32) << 1)) / 3) as u8,
             0xff,
         ];
     } else {
-        palette[2] = vec![
+        palette[2] = [
             ((palette[0][0] as u32 + palette[1][0] as u32) >> 1) as u8,
             ((palette[0][1] as u32 + palette[1][1] as u32) >> 1) as u8,
             ((palette[0][2] as u32 + palette[1][2] as u32) >> 1) as u8,
             0xff,
         ];
 
-        palette[3] = vec![0, 0, 0, 0];
+        palette[3] = [0, 0, 0, 0];
     }
 
-    for i in 0..(block.len() / 16) {
-        result[4 + i] = (least_distance_bc1(&palette, block, i * 16) << 6
-            | least_distance_bc1(&palette, block, i * 16 + 4) << 4
-            | least_distance_bc1(&palette, block, i * 16 + 8) << 2
-            | least_distance_bc1(&palette, block, i * 16 + 12)) as u8;
+    for i in 0..4 {
+        result[4 + i] = (least_distance_bc1(&palette, block, i * 16, alpha_threshold) << 6
+            | least_distance_bc1(&palette, block, i * 16 + 4, alpha_threshold) << 4
+            | least_distance_bc1(&palette, block, i * 16 + 8, alpha_threshold) << 2
+            | least_distance_bc1(&palette, block, i * 16 + 12, alpha_threshold))
+            as u8;
     }
 
     result
 }
 
-fn least_distance_bc1(palette: &[Vec<u8>], color: &[u8], offset: usize) -> usize {
-    if color[offset + 3] < 8 {
+fn least_distance_bc1(
+    palette: &[[u8; 4]; 4],
+    color: &[u8],
+    offset: usize,
+    alpha_threshold: u8,
+) -> usize {
+    if color[offset + 3] < alpha_threshold {
         return 3;
     }
 
@@ -321,46 +357,58 @@ fn distance_bc1(color_1: &[u8], offset_1: usize, color_2: &[u8], offset_2: usize
     temp
 }
 
-#[gvr_encoder_base(1, 1)]
-pub struct DXT1Encoder;
+/// Returns the length in bytes of the texture data of `image` in a data format with the given
+/// bits per pixel.
+///
+/// The math is done in `usize`, as the product of the dimensions can overflow `u32`.
+/// [`GvrEncoderBase::validate_input()`] rejects images that are too large for `usize`.
+fn data_len(image: &RgbaImage, bits_per_pixel: usize) -> usize {
+    image.width() as usize * image.height() as usize * bits_per_pixel / 8
+}
 
-impl GvrEncoder for DXT1Encoder {
-    fn encode(&self, image: &RgbaImage) -> Vec<u8> {
-        let width = image.width();
-        let height = image.height();
-        let dest_size = (width * height / 2).try_into().unwrap();
+#[gvr_encoder_base(4, 4)]
+pub struct DXT1Encoder {
+    pub alpha_threshold: u8,
+    pub padding_byte: u8,
+    pub layout: DxtLayout,
+}
+
+impl DXT1Encoder {
+    /// Encodes the given `image`, counting the kinds of blocks that were made in `stats`.
+    pub fn encode_with_stats(&self, image: &RgbaImage, stats: &mut DxtStats) -> Vec<u8> {
+        let dest_size = data_len(image, 4);
         let mut dest: Vec<u8> = Vec::with_capacity(dest_size);
 
-        for block in EncodeDxtBlockIterator::new(image) {
-            dest.append(&mut compress_block_to_bc1(&block));
+        for block in EncodeDxtBlockIterator::new(image).with_layout(self.layout) {
+            dest.extend_from_slice(&compress_block_to_bc1(&block, self.alpha_threshold, stats));
         }
 
         // Pad the data if needed
         if dest.len() < 32 {
-            dest.resize(32, 0);
+            dest.resize(32, self.padding_byte);
         }
 
         dest
     }
 }
 
+impl GvrEncoder for DXT1Encoder {
+    fn encode(&self, image: &RgbaImage) -> Vec<u8> {
+        self.encode_with_stats(image, &mut DxtStats::default())
+    }
+}
+
 #[gvr_encoder_base(4, 4)]
 pub struct RGB5A3Encoder;
 
 impl GvrEncoder for RGB5A3Encoder {
     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
-        let width = image.width();
-        let height = image.height();
-        let dest_size = (width * height * 2).try_into().unwrap();
+        let dest_size = data_len(image, 16);
         let mut dest: Vec<u8> = Vec::with_capacity(dest_size);
-        let block_size = self.get_block_size();
-
-        for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let p = image.get_pixel(x, y);
-            let pixel = encode_pixel_rgb5a3(p);
+        let block_size = Self::BLOCK_SIZE;
 
-            dest.push(((pixel >> 8) & 0xFF).try_into().unwrap());
-            dest.push((pixel & 0xFF).try_into().unwrap());
+        for block in PixelBlockViewIterator::new(image, block_size) {
+            pixel::encode_rgb5a3_pixels(block.pixels(), &mut dest);
         }
 
         dest
@@ -374,16 +422,15 @@ impl GvrEncoder for ARGB8888Encoder {
     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
         let width = image.width();
         let height = image.height();
-        let dest_size = (width * height * 4).try_into().unwrap();
+        let dest_size = data_len(image, 32);
         let mut dest = vec![0u8; dest_size];
-        let block_size = self.get_block_size();
+        let block_size = Self::BLOCK_SIZE;
 
         let mut dest_idx = 0;
 
         for (block, _, x, y) in PixelBlockIteratorExt::new(width, height, block_size) {
             let p = image.get_pixel(x, y);
-            let cur_idx = (block * 32) + dest_idx;
-            let cur_dest_idx = cur_idx as usize;
+            let cur_dest_idx = (block as usize * 32) + dest_idx;
 
             dest[cur_dest_idx] = p.0[3];
             dest[cur_dest_idx + 1] = p.0[0];
@@ -402,19 +449,12 @@ pub struct RGB565Encoder;
 
 impl GvrEncoder for RGB565Encoder {
     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
-        let width = image.width();
-        let height = image.height();
-        let dest_size = (width * height * 2).try_into().unwrap();
+        let dest_size = data_len(image, 16);
         let mut dest: Vec<u8> = Vec::with_capacity(dest_size);
-        let block_size = self.get_block_size();
+        let block_size = Self::BLOCK_SIZE;
 
-        for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let p = image.get_pixel(x, y);
-
-            let pixel = encode_pixel_rgb565(p);
-
-            dest.push(((pixel >> 8) & 0xFF).try_into().unwrap());
-            dest.push((pixel & 0xFF).try_into().unwrap());
+        for block in PixelBlockViewIterator::new(image, block_size) {
+            pixel::encode_rgb565_pixels(block.pixels(), &mut dest);
         }
 
         dest
@@ -426,22 +466,17 @@ pub struct IntensityA4Encoder;
 
 impl GvrEncoder for IntensityA4Encoder {
     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
-        let width = image.width();
-        let height = image.height();
-        let dest_size = (width * height).try_into().unwrap();
+        let dest_size = data_len(image, 8);
         let mut dest: Vec<u8> = Vec::with_capacity(dest_size);
-        let block_size = self.get_block_size();
-
-        for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let p = image.get_pixel(x, y);
-
-            let mut pixel: u8 = 0;
-            pixel |= (((0.30 * p.0[0] as f32 + 0.59 * p.0[1] as f32 + 0.11 * p.0[2] as f32) * 15.
-                / 255.) as u8)
-                & 0xF;
-            pixel |= (((p.0[3] as f32 * 15. / 255.) as u8) & 0xF) << 4;
-
-            dest.push(pixel);
+        let block_size = Self::BLOCK_SIZE;
+
+        for block in PixelBlockViewIterator::new(image, block_size) {
+            dest.extend(
+                block
+                    .pixels()
+                    .iter()
+                    .map(|&p| pixel::encode_intensity_alpha4(p)),
+            );
         }
 
         dest
@@ -453,19 +488,17 @@ pub struct IntensityA8Encoder;
 
 impl GvrEncoder for IntensityA8Encoder {
     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
-        let width = image.width();
-        let height = image.height();
-        let dest_size = (width * height * 2).try_into().unwrap();
+        let dest_size = data_len(image, 16);
         let mut dest: Vec<u8> = Vec::with_capacity(dest_size);
-        let block_size = self.get_block_size();
+        let block_size = Self::BLOCK_SIZE;
 
-        for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let p = image.get_pixel(x, y);
+        for block in PixelBlockViewIterator::new(image, block_size) {
+            for &p in block.pixels() {
+                let (pixel, alpha) = pixel::encode_intensity_alpha8(p);
 
-            let (pixel, alpha) = encode_pixel_intensity_alpha8(p);
-
-            dest.push(alpha);
-            dest.push(pixel);
+                dest.push(alpha);
+                dest.push(pixel);
+            }
         }
 
         dest
@@ -477,21 +510,19 @@ pub struct Intensity4Encoder;
 
 impl GvrEncoder for Intensity4Encoder {
     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
-        let width = image.width();
-        let height = image.height();
-        let dest_size = (width * height / 2).try_into().unwrap();
+        let dest_size = data_len(image, 4);
         let mut dest = vec![0u8; dest_size];
-        let block_size = self.get_block_size();
-
-        for (idx, (_, col, x, y)) in
-            PixelBlockIteratorExt::new(width, height, block_size).enumerate()
-        {
-            let p = image.get_pixel(x, y);
+        let block_size = Self::BLOCK_SIZE;
 
-            let pixel = ((0.30 * p.0[0] as f32 + 0.59 * p.0[1] as f32 + 0.11 * p.0[2] as f32) * 15.
-                / 255.) as u8;
+        let mut idx = 0;
+        for block in PixelBlockViewIterator::new(image, block_size) {
+            for (i, &p) in block.pixels().iter().enumerate() {
+                let col = i as u32 % block.width();
+                let pixel = pixel::encode_intensity4(p);
 
-            dest[idx / 2] |= (pixel & 0xF) << ((!col & 0x1) * 4);
+                dest[idx / 2] |= pixel << ((!col & 0x1) * 4);
+                idx += 1;
+            }
         }
 
         dest
@@ -503,18 +534,12 @@ pub struct Intensity8Encoder;
 
 impl GvrEncoder for Intensity8Encoder {
     fn encode(&self, image: &RgbaImage) -> Vec<u8> {
-        let width = image.width();
-        let height = image.height();
-        let dest_size = (width * height).try_into().unwrap();
+        let dest_size = data_len(image, 8);
         let mut dest: Vec<u8> = Vec::with_capacity(dest_size);
-        let block_size = self.get_block_size();
-
-        for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let p = image.get_pixel(x, y);
+        let block_size = Self::BLOCK_SIZE;
 
-            let pixel = (0.30 * p.0[0] as f32 + 0.59 * p.0[1] as f32 + 0.11 * p.0[2] as f32) as u8;
-
-            dest.push(pixel);
+        for block in PixelBlockViewIterator::new(image, block_size) {
+            dest.extend(block.pixels().iter().map(|&p| pixel::encode_intensity8(p)));
         }
 
         dest
@@ -530,19 +555,35 @@ impl GvrEncoderPalette for Index8PaletteEncoder {
         image: &RgbaImage,
         palette_pixel_format: PixelFormat,
     ) -> Result<Vec<u8>, imagequant::Error> {
-        let width = image.width();
-        let height = image.height();
-        let block_size = self.get_block_size();
-
         let (palette, indices) = palettize_image(image, INDEX8_PALETTE_SIZE, palette_pixel_format)?;
-        let mut result = encode_palette(palette, palette_pixel_format);
+        let palette: Vec<Rgba<u8>> = palette.iter().map(|c| Rgba([c.r, c.g, c.b, c.a])).collect();
+
+        Ok(self.encode_indexed(
+            &palette,
+            &indices,
+            image.width(),
+            image.height(),
+            palette_pixel_format,
+        ))
+    }
+
+    fn encode_indexed(
+        &self,
+        palette: &[Rgba<u8>],
+        indices: &[u8],
+        width: u32,
+        height: u32,
+        palette_pixel_format: PixelFormat,
+    ) -> Vec<u8> {
+        let block_size = Self::BLOCK_SIZE;
+        let mut result = encode_palette(palette.iter().map(|c| c.0), palette_pixel_format);
 
         for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let src_idx = y * width + x;
-            result.push(indices[src_idx as usize]);
+            let src_idx = y as usize * width as usize + x as usize;
+            result.push(indices[src_idx]);
         }
 
-        Ok(result)
+        result
     }
 }
 
@@ -555,30 +596,59 @@ impl GvrEncoderPalette for Index4PaletteEncoder {
         image: &RgbaImage,
         palette_pixel_format: PixelFormat,
     ) -> Result<Vec<u8>, imagequant::Error> {
-        let width = image.width();
-        let height = image.height();
-        let block_size = self.get_block_size();
-
         let (palette, indices) = palettize_image(image, INDEX4_PALETTE_SIZE, palette_pixel_format)?;
-        let mut result = encode_palette(palette, palette_pixel_format);
+        let palette: Vec<Rgba<u8>> = palette.iter().map(|c| Rgba([c.r, c.g, c.b, c.a])).collect();
+
+        Ok(self.encode_indexed(
+            &palette,
+            &indices,
+            image.width(),
+            image.height(),
+            palette_pixel_format,
+        ))
+    }
+
+    fn encode_indexed(
+        &self,
+        palette: &[Rgba<u8>],
+        indices: &[u8],
+        width: u32,
+        height: u32,
+        palette_pixel_format: PixelFormat,
+    ) -> Vec<u8> {
+        let block_size = Self::BLOCK_SIZE;
+        let mut result = encode_palette(palette.iter().map(|c| c.0), palette_pixel_format);
 
         // Resize vec to fill entire image data size (with palette)
         let cur_len = result.len();
-        result.resize(cur_len + (width * height / 2) as usize, 0);
+        result.resize(cur_len + width as usize * height as usize / 2, 0);
 
         for (dest_idx, (_, col, x, y)) in
             PixelBlockIteratorExt::new(width, height, block_size).enumerate()
         {
-            let src_idx = y * width + x;
-            result[cur_len + dest_idx / 2] |=
-                (indices[src_idx as usize] & 0xF) << ((!col & 0x1) * 4);
+            let src_idx = y as usize * width as usize + x as usize;
+            result[cur_len + dest_idx / 2] |= (indices[src_idx] & 0xF) << ((!col & 0x1) * 4);
         }
 
-        Ok(result)
+        result
+    }
+}
+
+/// Returns the amount of colors in the color palette of the given palettized `data_format`.
+pub fn palette_size(data_format: DataFormat) -> u32 {
+    match data_format {
+        DataFormat::Index4 => INDEX4_PALETTE_SIZE,
+        DataFormat::Index8 => INDEX8_PALETTE_SIZE,
+        _ => unreachable!(),
     }
 }
 
-pub fn create_new_encoder(data_format: DataFormat) -> Box<dyn GvrEncoder> {
+pub fn create_new_encoder(
+    data_format: DataFormat,
+    alpha_threshold: u8,
+    padding_byte: u8,
+    dxt_layout: DxtLayout,
+) -> Box<dyn GvrEncoder + Send + Sync> {
     match data_format {
         DataFormat::Rgb5a3 => Box::new(RGB5A3Encoder {}),
         DataFormat::Rgb565 => Box::new(RGB565Encoder {}),
@@ -587,12 +657,18 @@ pub fn create_new_encoder(data_format: DataFormat) -> Box<dyn GvrEncoder> {
         DataFormat::Intensity8 => Box::new(Intensity8Encoder {}),
         DataFormat::IntensityA4 => Box::new(IntensityA4Encoder {}),
         DataFormat::IntensityA8 => Box::new(IntensityA8Encoder {}),
-        DataFormat::Dxt1 => Box::new(DXT1Encoder {}),
+        DataFormat::Dxt1 => Box::new(DXT1Encoder {
+            alpha_threshold,
+            padding_byte,
+            layout: dxt_layout,
+        }),
         _ => unreachable!(),
     }
 }
 
-pub fn create_new_encoder_with_palette(data_format: DataFormat) -> Box<dyn GvrEncoderPalette> {
+pub fn create_new_encoder_with_palette(
+    data_format: DataFormat,
+) -> Box<dyn GvrEncoderPalette + Send + Sync> {
     match data_format {
         DataFormat::Index4 => Box::new(Index4PaletteEncoder {}),
         DataFormat::Index8 => Box::new(Index8PaletteEncoder {}),
@@ -604,46 +680,22 @@ pub fn create_new_encoder_with_palette(data_format: DataFormat) -> Box<dyn GvrEn
 // Decoding Functions //
 ////////////////////////
 
-fn decode_pixel_rgb5a3(pixel: u16) -> Rgba<u8> {
-    if (pixel & 0x8000) != 0 {
-        // Rgb555
-        let r = ((((pixel >> 10) & 0x1F) as f32) * 255. / 31.) as u8;
-        let g = ((((pixel >> 5) & 0x1F) as f32) * 255. / 31.) as u8;
-        let b = (((pixel & 0x1F) as f32) * 255. / 31.) as u8;
-        [r, g, b, 0xFF].into()
-    } else {
-        // Argb3444
-        let r = ((((pixel >> 8) & 0x0F) as f32) * 255. / 15.) as u8;
-        let g = ((((pixel >> 4) & 0x0F) as f32) * 255. / 15.) as u8;
-        let b = (((pixel & 0x0F) as f32) * 255. / 15.) as u8;
-        let a = ((((pixel >> 12) & 0x07) as f32) * 255. / 7.) as u8;
-        [r, g, b, a].into()
-    }
-}
-
-fn decode_pixel_rgb565(pixel: u16) -> Rgba<u8> {
-    let r = ((((pixel >> 11) & 0x1F) as f32) * 255. / 31.) as u8;
-    let g = ((((pixel >> 5) & 0x3F) as f32) * 255. / 63.) as u8;
-    let b = (((pixel & 0x1F) as f32) * 255. / 31.) as u8;
-    [r, g, b, 0xFF].into()
-}
-
-fn decode_pixel_intensity_alpha8(pixel: u8, alpha: u8) -> Rgba<u8> {
-    [pixel, pixel, pixel, alpha].into()
-}
-
 #[gvr_decoder_base(4, 4)]
 pub struct RGB5A3Decoder;
 
 impl GvrDecoder for RGB5A3Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
         let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let block_size = self.get_block_size();
+        let block_size = Self::BLOCK_SIZE;
 
         for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let pixel = cursor.read_u16::<BigEndian>()?;
-            image.put_pixel(x, y, decode_pixel_rgb5a3(pixel));
+            let pixel = reader.read_u16::<BigEndian>()?;
+            image.put_pixel(x, y, Rgba(pixel::decode_rgb5a3(pixel)));
         }
 
         Ok(image)
@@ -654,14 +706,18 @@ impl GvrDecoder for RGB5A3Decoder {
 pub struct RGB565Decoder;
 
 impl GvrDecoder for RGB565Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
         let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let block_size = self.get_block_size();
+        let block_size = Self::BLOCK_SIZE;
 
         for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let pixel = cursor.read_u16::<BigEndian>()?;
-            image.put_pixel(x, y, decode_pixel_rgb565(pixel));
+            let pixel = reader.read_u16::<BigEndian>()?;
+            image.put_pixel(x, y, Rgba(pixel::decode_rgb565(pixel)));
         }
 
         Ok(image)
@@ -672,23 +728,30 @@ impl GvrDecoder for RGB565Decoder {
 pub struct ARGB8888Decoder;
 
 impl GvrDecoder for ARGB8888Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
         let mut image = RgbaImage::new(width, height);
-        let block_size = self.get_block_size();
+        let block_size = Self::BLOCK_SIZE;
 
-        let mut src_idx = 0;
+        // Each block stores its AR pairs first, followed by its GB pairs
+        let mut block_data = [0u8; 64];
 
-        for (block, _, x, y) in PixelBlockIteratorExt::new(width, height, block_size) {
-            let cur_idx = (src_idx + block * 32) as usize;
+        for (idx, (x, y)) in PixelBlockIterator::new(width, height, block_size).enumerate() {
+            let cur_idx = (idx % 16) * 2;
+            if cur_idx == 0 {
+                reader.read_exact(&mut block_data)?;
+            }
 
-            let a = data[cur_idx];
-            let r = data[cur_idx + 1];
-            let g = data[cur_idx + 32];
-            let b = data[cur_idx + 33];
+            let a = block_data[cur_idx];
+            let r = block_data[cur_idx + 1];
+            let g = block_data[cur_idx + 32];
+            let b = block_data[cur_idx + 33];
 
             image.put_pixel(x, y, [r, g, b, a].into());
-
-            src_idx += 2;
         }
 
         Ok(image)
@@ -699,18 +762,31 @@ impl GvrDecoder for ARGB8888Decoder {
 pub struct IntensityA8Decoder;
 
 impl GvrDecoder for IntensityA8Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
-        let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let block_size = self.get_block_size();
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
+        Ok(self.decode_dynamic(reader, width, height)?.into_rgba8())
+    }
+
+    fn decode_dynamic(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<DynamicImage, std::io::Error> {
+        let mut image = GrayAlphaImage::new(width, height);
+        let block_size = Self::BLOCK_SIZE;
 
         for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let alpha = cursor.read_u8()?;
-            let pixel = cursor.read_u8()?;
-            image.put_pixel(x, y, decode_pixel_intensity_alpha8(pixel, alpha));
+            let alpha = reader.read_u8()?;
+            let pixel = reader.read_u8()?;
+            image.put_pixel(x, y, LumaA([pixel, alpha]));
         }
 
-        Ok(image)
+        Ok(DynamicImage::ImageLumaA8(image))
     }
 }
 
@@ -718,21 +794,31 @@ impl GvrDecoder for IntensityA8Decoder {
 pub struct IntensityA4Decoder;
 
 impl GvrDecoder for IntensityA4Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
-        let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let block_size = self.get_block_size();
-
-        for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let pixel = cursor.read_u8()?;
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
+        Ok(self.decode_dynamic(reader, width, height)?.into_rgba8())
+    }
 
-            let c = ((pixel & 0x0F) as f32 * 255. / 15.) as u8;
-            let a = (((pixel >> 4) & 0x0F) as f32 * 255. / 15.) as u8;
+    fn decode_dynamic(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<DynamicImage, std::io::Error> {
+        let mut image = GrayAlphaImage::new(width, height);
+        let block_size = Self::BLOCK_SIZE;
 
-            image.put_pixel(x, y, [c, c, c, a].into());
+        for (x, y) in PixelBlockIterator::new(width, height, block_size) {
+            let pixel = reader.read_u8()?;
+            let [c, _, _, a] = pixel::decode_intensity_alpha4(pixel);
+            image.put_pixel(x, y, LumaA([c, a]));
         }
 
-        Ok(image)
+        Ok(DynamicImage::ImageLumaA8(image))
     }
 }
 
@@ -740,17 +826,30 @@ impl GvrDecoder for IntensityA4Decoder {
 pub struct Intensity8Decoder;
 
 impl GvrDecoder for Intensity8Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
-        let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let block_size = self.get_block_size();
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
+        Ok(self.decode_dynamic(reader, width, height)?.into_rgba8())
+    }
+
+    fn decode_dynamic(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<DynamicImage, std::io::Error> {
+        let mut image = GrayImage::new(width, height);
+        let block_size = Self::BLOCK_SIZE;
 
         for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let c = cursor.read_u8()?;
-            image.put_pixel(x, y, [c, c, c, 0xFF].into());
+            let pixel = reader.read_u8()?;
+            image.put_pixel(x, y, Luma([pixel]));
         }
 
-        Ok(image)
+        Ok(DynamicImage::ImageLuma8(image))
     }
 }
 
@@ -758,19 +857,38 @@ impl GvrDecoder for Intensity8Decoder {
 pub struct Intensity4Decoder;
 
 impl GvrDecoder for Intensity4Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
-        let mut image = RgbaImage::new(width, height);
-        let block_size = self.get_block_size();
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
+        Ok(self.decode_dynamic(reader, width, height)?.into_rgba8())
+    }
+
+    fn decode_dynamic(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<DynamicImage, std::io::Error> {
+        let mut image = GrayImage::new(width, height);
+        let block_size = Self::BLOCK_SIZE;
+
+        let mut pixels = 0;
 
         for (idx, (_, col, x, y)) in
             PixelBlockIteratorExt::new(width, height, block_size).enumerate()
         {
-            let pixel = (data[idx / 2] >> ((!col & 0x1) * 4)) & 0x0F;
-            let c = (pixel as f32 * 255. / 15.) as u8;
-            image.put_pixel(x, y, [c, c, c, 0xFF].into());
+            if idx % 2 == 0 {
+                pixels = reader.read_u8()?;
+            }
+
+            let [c, ..] = pixel::decode_intensity4(pixels >> ((!col & 0x1) * 4));
+            image.put_pixel(x, y, Luma([c]));
         }
 
-        Ok(image)
+        Ok(DynamicImage::ImageLuma8(image))
     }
 }
 
@@ -778,25 +896,33 @@ impl GvrDecoder for Intensity4Decoder {
 pub struct Index8PaletteDecoder;
 
 impl GvrDecoderPalette for Index8PaletteDecoder {
-    fn decode(
+    fn decode_indexed(
         &self,
-        data: &[u8],
+        reader: &mut dyn Read,
         width: u32,
         height: u32,
         palette_pixel_format: PixelFormat,
-    ) -> Result<RgbaImage, std::io::Error> {
-        let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let block_size = self.get_block_size();
+    ) -> Result<(Vec<Rgba<u8>>, Vec<u8>), std::io::Error> {
+        let palette = decode_palette(reader, palette_pixel_format, INDEX8_PALETTE_SIZE)?;
+        let indices = self.decode_indices(reader, width, height)?;
 
-        let palette = decode_palette(&mut cursor, palette_pixel_format, INDEX8_PALETTE_SIZE)?;
+        Ok((palette, indices))
+    }
+
+    fn decode_indices(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        let mut indices = vec![0u8; (width * height) as usize];
+        let block_size = Self::BLOCK_SIZE;
 
         for (x, y) in PixelBlockIterator::new(width, height, block_size) {
-            let palette_idx = cursor.read_u8()?;
-            image.put_pixel(x, y, palette[palette_idx as usize]);
+            indices[(y * width + x) as usize] = reader.read_u8()?;
         }
 
-        Ok(image)
+        Ok(indices)
     }
 }
 
@@ -804,95 +930,217 @@ impl GvrDecoderPalette for Index8PaletteDecoder {
 pub struct Index4PaletteDecoder;
 
 impl GvrDecoderPalette for Index4PaletteDecoder {
-    fn decode(
+    fn decode_indexed(
         &self,
-        data: &[u8],
+        reader: &mut dyn Read,
         width: u32,
         height: u32,
         palette_pixel_format: PixelFormat,
-    ) -> Result<RgbaImage, std::io::Error> {
-        let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let block_size = self.get_block_size();
+    ) -> Result<(Vec<Rgba<u8>>, Vec<u8>), std::io::Error> {
+        let palette = decode_palette(reader, palette_pixel_format, INDEX4_PALETTE_SIZE)?;
+        let indices = self.decode_indices(reader, width, height)?;
+
+        Ok((palette, indices))
+    }
 
-        let palette = decode_palette(&mut cursor, palette_pixel_format, INDEX4_PALETTE_SIZE)?;
-        const PALETTE_SIZE_BYTES: usize = INDEX4_PALETTE_SIZE as usize * size_of::<u16>();
+    fn decode_indices(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, std::io::Error> {
+        let mut indices = vec![0u8; (width * height) as usize];
+        let block_size = Self::BLOCK_SIZE;
+        let mut packed_indices = 0;
 
         for (idx, (_, col, x, y)) in
             PixelBlockIteratorExt::new(width, height, block_size).enumerate()
         {
-            let palette_idx =
-                (data[PALETTE_SIZE_BYTES + (idx / 2)] >> ((col % 2 == 0) as u8 * 4)) & 0x0F;
-            image.put_pixel(x, y, palette[palette_idx as usize]);
+            if idx % 2 == 0 {
+                packed_indices = reader.read_u8()?;
+            }
+
+            indices[(y * width + x) as usize] =
+                (packed_indices >> ((col % 2 == 0) as u8 * 4)) & 0x0F;
         }
 
-        Ok(image)
+        Ok(indices)
     }
 }
 
+/// Reads a single BC1 block from the `reader`, returning the color palette of the block alongside
+/// the rows of 2-bit indices into said palette.
+fn read_block_bc1(reader: &mut dyn Read) -> Result<([Rgba<u8>; 4], [u8; 4]), std::io::Error> {
+    let mut colors: [Rgba<u8>; 4] = [[0, 0, 0, 0].into(); 4];
+    let mut indices = [0u8; 4];
+
+    let encoded_1 = reader.read_u16::<BigEndian>()?;
+    let encoded_2 = reader.read_u16::<BigEndian>()?;
+
+    colors[0] = Rgba(pixel::decode_rgb565(encoded_1));
+    colors[1] = Rgba(pixel::decode_rgb565(encoded_2));
+
+    if encoded_1 > encoded_2 {
+        colors[2] = [
+            ((colors[0].0[0] as u32 * 2 + colors[1].0[0] as u32) / 3) as u8,
+            ((colors[0].0[1] as u32 * 2 + colors[1].0[1] as u32) / 3) as u8,
+            ((colors[0].0[2] as u32 * 2 + colors[1].0[2] as u32) / 3) as u8,
+            0xFF,
+        ]
+        .into();
+
+        colors[3] = [
+            ((colors[1].0[0] as u32 * 2 + colors[0].0[0] as u32) / 3) as u8,
+            ((colors[1].0[1] as u32 * 2 + colors[0].0[1] as u32) / 3) as u8,
+            ((colors[1].0[2] as u32 * 2 + colors[0].0[2] as u32) / 3) as u8,
+            0xFF,
+        ]
+        .into();
+    } else {
+        colors[2] = [
+            ((colors[0].0[0] as u32 + colors[1].0[0] as u32) / 2) as u8,
+            ((colors[0].0[1] as u32 + colors[1].0[1] as u32) / 2) as u8,
+            ((colors[0].0[2] as u32 + colors[1].0[2] as u32) / 2) as u8,
+            0xFF,
+        ]
+        .into();
+
+        colors[3] = [0, 0, 0, 0].into();
+    }
+
+    reader.read_exact(&mut indices)?;
+
+    Ok((colors, indices))
+}
+
 #[gvr_decoder_base(1, 1)]
-pub struct DXT1Decoder;
+pub struct DXT1Decoder {
+    pub layout: DxtLayout,
+}
 
 impl GvrDecoder for DXT1Decoder {
-    fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<RgbaImage, std::io::Error> {
+    fn decode(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, std::io::Error> {
         let mut image = RgbaImage::new(width, height);
-        let mut cursor = Cursor::new(data);
-        let mut src_idx = 0;
-        let colors: &mut [Rgba<u8>] = &mut [[0, 0, 0, 0].into(); 4];
-
-        for (x, y) in DecodeDxtBlockIterator::new(width, height) {
-            cursor.seek(std::io::SeekFrom::Start(src_idx))?;
-            let encoded_1 = cursor.read_u16::<BigEndian>()?;
-            let encoded_2 = cursor.read_u16::<BigEndian>()?;
-
-            colors[0] = decode_pixel_rgb565(encoded_1);
-            colors[1] = decode_pixel_rgb565(encoded_2);
-
-            if encoded_1 > encoded_2 {
-                colors[2] = [
-                    ((colors[0].0[0] as u32 * 2 + colors[1].0[0] as u32) / 3) as u8,
-                    ((colors[0].0[1] as u32 * 2 + colors[1].0[1] as u32) / 3) as u8,
-                    ((colors[0].0[2] as u32 * 2 + colors[1].0[2] as u32) / 3) as u8,
-                    0xFF,
-                ]
-                .into();
-
-                colors[3] = [
-                    ((colors[1].0[0] as u32 * 2 + colors[0].0[0] as u32) / 3) as u8,
-                    ((colors[1].0[1] as u32 * 2 + colors[0].0[1] as u32) / 3) as u8,
-                    ((colors[1].0[2] as u32 * 2 + colors[0].0[2] as u32) / 3) as u8,
-                    0xFF,
-                ]
-                .into();
-            } else {
-                colors[2] = [
-                    ((colors[0].0[0] as u32 + colors[1].0[0] as u32) / 2) as u8,
-                    ((colors[0].0[1] as u32 + colors[1].0[1] as u32) / 2) as u8,
-                    ((colors[0].0[2] as u32 + colors[1].0[2] as u32) / 2) as u8,
-                    0xFF,
-                ]
-                .into();
-
-                colors[3] = [0, 0, 0, 0].into();
-            }
 
-            src_idx += 4;
+        for (x, y) in DecodeDxtBlockIterator::new(width, height).with_layout(self.layout) {
+            let (colors, indices) = read_block_bc1(reader)?;
 
             for y2 in (0..4).take_while(|i| y + i < height) {
                 for x2 in (0..4).take_while(|i| x + i < width) {
-                    let color_idx = (data[(src_idx + y2 as u64) as usize] >> (6 - x2 * 2)) & 0x3;
+                    let color_idx = (indices[y2 as usize] >> (6 - x2 * 2)) & 0x3;
                     image.put_pixel(x + x2, y + y2, colors[color_idx as usize]);
                 }
             }
+        }
+
+        Ok(image)
+    }
+
+    /// Only decodes the blocks that overlap with the given `region`, skipping over the rest.
+    fn decode_region(
+        &self,
+        reader: &mut dyn Read,
+        width: u32,
+        height: u32,
+        region: (u32, u32, u32, u32),
+    ) -> Result<RgbaImage, std::io::Error> {
+        let (region_x, region_y, region_width, region_height) = region;
+        let mut image = RgbaImage::new(region_width, region_height);
+        let mut skipped = [0u8; 8];
+
+        for (x, y) in DecodeDxtBlockIterator::new(width, height).with_layout(self.layout) {
+            // Both layouts store the blocks in rows of 8x8 tiles, so nothing after this row can
+            // overlap
+            if y >= region_y + region_height && y % 8 == 0 {
+                break;
+            }
+
+            if x + 4 <= region_x
+                || x >= region_x + region_width
+                || y + 4 <= region_y
+                || y >= region_y + region_height
+            {
+                reader.read_exact(&mut skipped)?;
+                continue;
+            }
 
-            src_idx += 4;
+            let (colors, indices) = read_block_bc1(reader)?;
+
+            for y2 in (0..4).filter(|i| (region_y..region_y + region_height).contains(&(y + i))) {
+                for x2 in (0..4).filter(|i| (region_x..region_x + region_width).contains(&(x + i)))
+                {
+                    let color_idx = (indices[y2 as usize] >> (6 - x2 * 2)) & 0x3;
+                    image.put_pixel(
+                        x + x2 - region_x,
+                        y + y2 - region_y,
+                        colors[color_idx as usize],
+                    );
+                }
+            }
         }
 
         Ok(image)
     }
 }
 
-pub fn create_new_decoder(data_format: DataFormat) -> Box<dyn GvrDecoder> {
+/// Decodes the image like `decoder.decode_dynamic()` does, splitting it up into rows of blocks
+/// that are decoded on multiple threads. The image data of each row of blocks is laid out the
+/// same way as a texture of that height, so each row is decoded as its own image.
+///
+/// Images whose width isn't a multiple of the block width are decoded on a single thread, as
+/// some decoders skip the data of the pixels past the right edge of the image.
+#[cfg(feature = "rayon")]
+pub fn decode_dynamic_parallel(
+    decoder: &(dyn GvrDecoder + Sync),
+    data_format: DataFormat,
+    reader: &mut dyn Read,
+    width: u32,
+    height: u32,
+) -> Result<DynamicImage, std::io::Error> {
+    use rayon::prelude::*;
+
+    let (block_width, block_height) = data_format.block_size();
+    if width == 0 || !width.is_multiple_of(block_width) || height <= block_height {
+        return decoder.decode_dynamic(reader, width, height);
+    }
+
+    let mut data = vec![0; data_format.image_size(width, height)];
+    reader.read_exact(&mut data)?;
+
+    let band_len = data_format.image_size(width, block_height);
+    let bands = data
+        .par_chunks(band_len)
+        .enumerate()
+        .map(|(i, mut band)| {
+            let band_height = block_height.min(height - i as u32 * block_height);
+            decoder.decode_dynamic(&mut band, width, band_height)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pixels = bands
+        .iter()
+        .flat_map(|band| band.as_bytes())
+        .copied()
+        .collect();
+    let image = match &bands[0] {
+        DynamicImage::ImageLuma8(_) => GrayImage::from_raw(width, height, pixels).map(Into::into),
+        DynamicImage::ImageLumaA8(_) => {
+            GrayAlphaImage::from_raw(width, height, pixels).map(Into::into)
+        }
+        _ => RgbaImage::from_raw(width, height, pixels).map(Into::into),
+    };
+    Ok(image.expect("the rows of blocks should add up to the whole image"))
+}
+
+pub fn create_new_decoder(
+    data_format: DataFormat,
+    dxt_layout: DxtLayout,
+) -> Box<dyn GvrDecoder + Send + Sync> {
     match data_format {
         DataFormat::Rgb5a3 => Box::new(RGB5A3Decoder {}),
         DataFormat::Rgb565 => Box::new(RGB565Decoder {}),
@@ -901,12 +1149,14 @@ pub fn create_new_decoder(data_format: DataFormat) -> Box<dyn GvrDecoder> {
         DataFormat::Intensity8 => Box::new(Intensity8Decoder {}),
         DataFormat::IntensityA4 => Box::new(IntensityA4Decoder {}),
         DataFormat::IntensityA8 => Box::new(IntensityA8Decoder {}),
-        DataFormat::Dxt1 => Box::new(DXT1Decoder {}),
+        DataFormat::Dxt1 => Box::new(DXT1Decoder { layout: dxt_layout }),
         _ => unreachable!(),
     }
 }
 
-pub fn create_new_decoder_with_palette(data_format: DataFormat) -> Box<dyn GvrDecoderPalette> {
+pub fn create_new_decoder_with_palette(
+    data_format: DataFormat,
+) -> Box<dyn GvrDecoderPalette + Send + Sync> {
     match data_format {
         DataFormat::Index4 => Box::new(Index4PaletteDecoder {}),
         DataFormat::Index8 => Box::new(Index8PaletteDecoder {}),
@@ -0,0 +1,110 @@
+//! SSE2 versions of the runs of pixel conversions in [`crate::pixel`], which go through 8 pixels
+//! at a time. SSE2 is always available on x86_64, so no runtime detection is needed.
+//!
+//! Every pixel is handled as a 32-bit lane holding `r | g << 8 | b << 16 | a << 24`, the same
+//! layout the `[r, g, b, a]` arrays have in memory.
+
+use crate::pixel::RGB5A3_OPAQUE_THRESHOLD;
+use alloc::vec::Vec;
+use core::arch::x86_64::*;
+
+/// The amount of pixels converted per iteration.
+pub(crate) const LANES: usize = 8;
+
+/// Encodes the given pixels into RGB565 like [`crate::pixel::encode_rgb565`] does, appending
+/// them to `dest` as big endian bytes. The amount of pixels has to be a multiple of [`LANES`].
+pub(crate) fn encode_rgb565(pixels: &[[u8; 4]], dest: &mut Vec<u8>) {
+    debug_assert_eq!(pixels.len() % LANES, 0);
+
+    for chunk in pixels.chunks_exact(LANES) {
+        // SAFETY: SSE2 is part of x86_64, and the loads and stores are unaligned
+        unsafe {
+            let (low, high) = load(chunk);
+            store(encode_rgb565_lanes(low), encode_rgb565_lanes(high), dest);
+        }
+    }
+}
+
+/// Encodes the given pixels into RGB5A3 like [`crate::pixel::encode_rgb5a3`] does, appending
+/// them to `dest` as big endian bytes. The amount of pixels has to be a multiple of [`LANES`].
+pub(crate) fn encode_rgb5a3(pixels: &[[u8; 4]], dest: &mut Vec<u8>) {
+    debug_assert_eq!(pixels.len() % LANES, 0);
+
+    for chunk in pixels.chunks_exact(LANES) {
+        // SAFETY: SSE2 is part of x86_64, and the loads and stores are unaligned
+        unsafe {
+            let (low, high) = load(chunk);
+            store(encode_rgb5a3_lanes(low), encode_rgb5a3_lanes(high), dest);
+        }
+    }
+}
+
+/// Loads 8 pixels into two vectors of 4 lanes.
+#[inline(always)]
+unsafe fn load(chunk: &[[u8; 4]]) -> (__m128i, __m128i) {
+    let ptr = chunk.as_ptr() as *const __m128i;
+    (_mm_loadu_si128(ptr), _mm_loadu_si128(ptr.add(1)))
+}
+
+/// Narrows the 16-bit pixels in the lanes of `low` and `high` down to 8 pixels, appending them to
+/// `dest` as big endian bytes.
+#[inline(always)]
+unsafe fn store(low: __m128i, high: __m128i, dest: &mut Vec<u8>) {
+    // Packing saturates signed values, so the pixels are sign extended first to keep them intact
+    let low = _mm_srai_epi32(_mm_slli_epi32(low, 16), 16);
+    let high = _mm_srai_epi32(_mm_slli_epi32(high, 16), 16);
+    let packed = _mm_packs_epi32(low, high);
+    let swapped = _mm_or_si128(_mm_slli_epi16(packed, 8), _mm_srli_epi16(packed, 8));
+
+    let mut bytes = [0u8; 16];
+    _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, swapped);
+    dest.extend_from_slice(&bytes);
+}
+
+/// Takes `bits` bits of each lane of `v`, starting from the bit `shift`.
+#[inline(always)]
+unsafe fn field(v: __m128i, shift: i32, bits: i32) -> __m128i {
+    let shifted = _mm_srl_epi32(v, _mm_cvtsi32_si128(shift));
+    _mm_and_si128(shifted, _mm_set1_epi32((1 << bits) - 1))
+}
+
+/// Moves each lane of `v` `shift` bits to the left.
+#[inline(always)]
+unsafe fn shl(v: __m128i, shift: i32) -> __m128i {
+    _mm_sll_epi32(v, _mm_cvtsi32_si128(shift))
+}
+
+#[inline(always)]
+unsafe fn encode_rgb565_lanes(v: __m128i) -> __m128i {
+    let r = field(v, 3, 5);
+    let g = field(v, 8 + 2, 6);
+    let b = field(v, 16 + 3, 5);
+    _mm_or_si128(_mm_or_si128(shl(r, 11), shl(g, 5)), b)
+}
+
+#[inline(always)]
+unsafe fn encode_rgb5a3_lanes(v: __m128i) -> __m128i {
+    let a = field(v, 24, 8);
+    let opaque_mask = _mm_cmpgt_epi32(a, _mm_set1_epi32(i32::from(RGB5A3_OPAQUE_THRESHOLD) - 1));
+
+    // Rgb555
+    let opaque = _mm_or_si128(
+        _mm_or_si128(shl(field(v, 3, 5), 10), shl(field(v, 8 + 3, 5), 5)),
+        _mm_or_si128(field(v, 16 + 3, 5), _mm_set1_epi32(0x8000)),
+    );
+
+    // Argb3444, with (a * 7 + 127) / 255 worked out as (x + 1 + (x >> 8)) >> 8, which is exact
+    // for every x below 0xFFFF
+    let x = _mm_add_epi32(_mm_sub_epi32(shl(a, 3), a), _mm_set1_epi32(127));
+    let x = _mm_add_epi32(_mm_add_epi32(x, _mm_set1_epi32(1)), _mm_srli_epi32(x, 8));
+    let alpha = _mm_srli_epi32(x, 8);
+    let translucent = _mm_or_si128(
+        _mm_or_si128(shl(alpha, 12), shl(field(v, 4, 4), 8)),
+        _mm_or_si128(shl(field(v, 8 + 4, 4), 4), field(v, 16 + 4, 4)),
+    );
+
+    _mm_or_si128(
+        _mm_and_si128(opaque_mask, opaque),
+        _mm_andnot_si128(opaque_mask, translucent),
+    )
+}
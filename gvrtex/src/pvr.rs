@@ -0,0 +1,467 @@
+//! Contains conversions between images and PVR textures, the texture format used in Dreamcast
+//! games.
+//!
+//! GVR textures descend from PVR textures, which is where the GBIX header comes from, and many
+//! games were released with the same assets in both formats. This module supports the common
+//! PVR formats, which is enough to convert textures between the two.
+//!
+//! # Examples
+//!
+//! Converting a PVR texture into a GVR texture:
+//!
+//! ```no_run
+//! use gvrtex::formats::DataFormat;
+//! use gvrtex::pvr::decode_pvr;
+//! use gvrtex::TextureEncoder;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let pvr_bytes: &[u8] = &[];
+//! let image = decode_pvr(pvr_bytes)?;
+//! let mut encoder = TextureEncoder::new_gbix(DataFormat::Rgb5a3)?;
+//! let gvr_bytes = encoder.encode_image(image.into())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Converting a GVR texture into a PVR texture:
+//!
+//! ```no_run
+//! use gvrtex::decode_bytes;
+//! use gvrtex::pvr::{encode_pvr, PvrPixelFormat};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let gvr_bytes: &[u8] = &[];
+//! let image = decode_bytes(gvr_bytes)?;
+//! let pvr_bytes = encode_pvr(&image, PvrPixelFormat::Argb4444, 0)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{TextureDecodeError, TextureEncodeError};
+use crate::pixel;
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use image::{Rgba, RgbaImage};
+
+/// Stores the pixels in a twiddled order, with the texture being square.
+const SQUARE_TWIDDLED: u8 = 0x01;
+/// Same as [`SQUARE_TWIDDLED`], but with mipmaps.
+const SQUARE_TWIDDLED_MIPMAPS: u8 = 0x02;
+/// Stores the texture as 2x2 blocks of pixels, each referring to a codebook of blocks.
+const VQ: u8 = 0x03;
+/// Same as [`VQ`], but with mipmaps.
+const VQ_MIPMAPS: u8 = 0x04;
+/// Stores the pixels row by row.
+const RECTANGLE: u8 = 0x09;
+/// Stores the pixels in a twiddled order, with the texture split into squares.
+const RECTANGLE_TWIDDLED: u8 = 0x0D;
+/// Same as [`VQ`], but the codebook has fewer blocks for small textures.
+const SMALL_VQ: u8 = 0x10;
+/// Same as [`SMALL_VQ`], but with mipmaps.
+const SMALL_VQ_MIPMAPS: u8 = 0x11;
+/// Same as [`SQUARE_TWIDDLED_MIPMAPS`], used by some games instead.
+const SQUARE_TWIDDLED_MIPMAPS_ALT: u8 = 0x12;
+
+/// The format the pixels of a PVR texture are stored in. Every format stores 16 bits per pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PvrPixelFormat {
+    /// Stores 15-bit color values with a single bit of alpha. Pixels with an alpha value below
+    /// 128 are encoded as fully transparent.
+    Argb1555 = 0x00,
+    /// Stores 16-bit color values, but does not save an alpha channel.
+    Rgb565 = 0x01,
+    /// Stores 12-bit color values with 4 bits of alpha.
+    Argb4444 = 0x02,
+}
+
+impl TryFrom<u8> for PvrPixelFormat {
+    type Error = TextureDecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(Self::Argb1555),
+            0x01 => Ok(Self::Rgb565),
+            0x02 => Ok(Self::Argb4444),
+            _ => Err(TextureDecodeError::UnsupportedFormat(value.into())),
+        }
+    }
+}
+
+impl PvrPixelFormat {
+    fn decode(self, pixel: u16) -> Rgba<u8> {
+        match self {
+            Self::Argb1555 => {
+                let r = pixel::EXPAND_5[((pixel >> 10) & 0x1F) as usize];
+                let g = pixel::EXPAND_5[((pixel >> 5) & 0x1F) as usize];
+                let b = pixel::EXPAND_5[(pixel & 0x1F) as usize];
+                let a = if pixel & 0x8000 != 0 { 0xFF } else { 0 };
+                Rgba([r, g, b, a])
+            }
+            Self::Rgb565 => Rgba(pixel::decode_rgb565(pixel)),
+            Self::Argb4444 => {
+                let r = pixel::EXPAND_4[((pixel >> 8) & 0x0F) as usize];
+                let g = pixel::EXPAND_4[((pixel >> 4) & 0x0F) as usize];
+                let b = pixel::EXPAND_4[(pixel & 0x0F) as usize];
+                let a = pixel::EXPAND_4[((pixel >> 12) & 0x0F) as usize];
+                Rgba([r, g, b, a])
+            }
+        }
+    }
+
+    fn encode(self, p: Rgba<u8>) -> u16 {
+        match self {
+            Self::Argb1555 => {
+                let mut pixel = ((p[0] >> 3) as u16) << 10;
+                pixel |= ((p[1] >> 3) as u16) << 5;
+                pixel |= (p[2] >> 3) as u16;
+                if p[3] >= 0x80 {
+                    pixel |= 0x8000;
+                }
+                pixel
+            }
+            Self::Rgb565 => pixel::encode_rgb565(p.0),
+            Self::Argb4444 => {
+                let mut pixel = ((p[3] >> 4) as u16) << 12;
+                pixel |= ((p[0] >> 4) as u16) << 8;
+                pixel |= ((p[1] >> 4) as u16) << 4;
+                pixel |= (p[2] >> 4) as u16;
+                pixel
+            }
+        }
+    }
+}
+
+/// Returns the position of the pixel at `x` and `y` in a square texture stored in the twiddled
+/// order.
+///
+/// The twiddled order interleaves the bits of both coordinates, with the bits of `y` in the even
+/// positions and the bits of `x` in the odd positions.
+fn twiddle(x: u32, y: u32) -> usize {
+    let mut result = 0;
+
+    for bit in 0..16 {
+        result |= (((y >> bit) & 1) as usize) << (bit * 2);
+        result |= (((x >> bit) & 1) as usize) << (bit * 2 + 1);
+    }
+
+    result
+}
+
+/// Returns the position of the pixel at `x` and `y` in a texture stored in the twiddled order.
+///
+/// Rectangular textures are split into squares of the shorter side, which are stored one after
+/// the other, each of them being twiddled.
+fn twiddle_rectangle(x: u32, y: u32, width: u32, height: u32) -> usize {
+    let size = width.min(height);
+    let square = if width > height { x / size } else { y / size };
+
+    square as usize * (size * size) as usize + twiddle(x % size, y % size)
+}
+
+/// Returns the amount of blocks in the codebook of a VQ texture with the given `data_format` and
+/// `width`.
+fn codebook_size(data_format: u8, width: u32) -> usize {
+    match (data_format, width) {
+        (SMALL_VQ, ..=16) | (SMALL_VQ_MIPMAPS, ..=16) => 16,
+        (SMALL_VQ, 32) => 32,
+        (SMALL_VQ, 64) => 128,
+        (SMALL_VQ_MIPMAPS, 32) => 64,
+        _ => 256,
+    }
+}
+
+/// Returns the offset of the full size texture in the data of a mipmapped texture, as the
+/// mipmaps are stored first, from the smallest to the largest.
+fn mipmap_offset(data_format: u8, width: u32) -> usize {
+    let mut offset = 0;
+    let mut size = 1;
+
+    while size < width {
+        offset += match data_format {
+            // Each index refers to a 2x2 block, the 1x1 mipmap takes up a full index
+            VQ_MIPMAPS | SMALL_VQ_MIPMAPS => ((size * size / 4) as usize).max(1),
+            // The 1x1 mipmap takes up as much space as a 2x1 mipmap would
+            _ => ((size * size) as usize).max(2) * size_of::<u16>(),
+        };
+        size *= 2;
+    }
+
+    offset
+}
+
+/// Decodes the PVR texture held in `pvr_bytes` into an image.
+///
+/// Textures in the [`PvrPixelFormat`] formats are supported, stored either in the twiddled or
+/// the rectangular (row by row) layout, or compressed with VQ. Only the full size texture is
+/// decoded, any mipmaps are skipped.
+///
+/// # Notes
+///
+/// The start of `pvr_bytes` should point to the start of the texture, which can either be the
+/// GBIX header or the PVRT header.
+///
+/// # Errors
+///
+/// If `pvr_bytes` doesn't hold a valid PVR texture, a [`TextureDecodeError::InvalidFile`] is
+/// returned. If the texture uses a pixel format or data format that isn't supported (like the
+/// palettized formats), a [`TextureDecodeError::UnsupportedFormat`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::error::TextureDecodeError;
+/// use gvrtex::pvr::decode_pvr;
+/// use image::Rgba;
+///
+/// // A PVRT header with RGB565 pixels, without a GBIX header in front of it
+/// fn pvr(data_format: u8, width: u16, height: u16, pixels: &[u16], indices: &[u8]) -> Vec<u8> {
+///     let mut data: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+///     data.extend_from_slice(indices);
+///     let mut pvr = b"PVRT".to_vec();
+///     pvr.extend_from_slice(&(data.len() as u32 + 8).to_le_bytes());
+///     pvr.extend_from_slice(&[0x01, data_format, 0, 0]);
+///     pvr.extend_from_slice(&width.to_le_bytes());
+///     pvr.extend_from_slice(&height.to_le_bytes());
+///     pvr.extend_from_slice(&data);
+///     pvr
+/// }
+///
+/// let (red, green, blue, white) = (0xF800, 0x07E0, 0x001F, 0xFFFF);
+/// let colors = [[0xFF, 0, 0, 0xFF], [0, 0xFF, 0, 0xFF], [0, 0, 0xFF, 0xFF], [0xFF; 4]];
+/// let [r, g, b, w] = colors.map(Rgba);
+///
+/// // The rectangular layout stores the pixels row by row
+/// let pixels = [red, green, blue, white, white, blue, green, red];
+/// let image = decode_pvr(&pvr(0x09, 4, 2, &pixels, &[]))?;
+/// assert_eq!(image.pixels().copied().collect::<Vec<_>>(), [r, g, b, w, w, b, g, r]);
+///
+/// // Mipmaps come first, from the smallest one, with the 1x1 mipmap taking up 2 pixels
+/// let image = decode_pvr(&pvr(0x02, 2, 2, &[0, 0, red, green, blue, white], &[]))?;
+/// assert_eq!(image.pixels().copied().collect::<Vec<_>>(), [r, b, g, w]);
+///
+/// // VQ textures index into a codebook of 2x2 blocks, which are twiddled just like the indices.
+/// // Small VQ textures of 16x16 pixels or less have a codebook of 16 blocks instead of 256.
+/// for (data_format, codebook_size) in [(0x03, 256), (0x10, 16)] {
+///     let mut codebook = vec![red; codebook_size * 4];
+///     codebook[4..8].copy_from_slice(&[red, green, blue, white]);
+///     let image = decode_pvr(&pvr(data_format, 4, 4, &codebook, &[1, 0, 0, 0]))?;
+///     assert_eq!(
+///         [image[(0, 0)], image[(0, 1)], image[(1, 0)], image[(1, 1)], image[(3, 3)]],
+///         [r, g, b, w, r]
+///     );
+/// }
+///
+/// // Indices past the end of the codebook are rejected
+/// let codebook = vec![red; 16 * 4];
+/// assert!(matches!(
+///     decode_pvr(&pvr(0x10, 4, 4, &codebook, &[16, 0, 0, 0])),
+///     Err(TextureDecodeError::InvalidFile)
+/// ));
+/// # Ok::<(), TextureDecodeError>(())
+/// ```
+pub fn decode_pvr(pvr_bytes: &[u8]) -> Result<RgbaImage, TextureDecodeError> {
+    // The GBIX header is optional, and its length varies between games
+    let pvrt_offset = match pvr_bytes.get(..4) {
+        Some(b"GBIX" | b"GCIX") => match pvr_bytes.get(4..8) {
+            Some(len) => 8 + LittleEndian::read_u32(len) as usize,
+            None => return Err(TextureDecodeError::InvalidFile),
+        },
+        Some(b"PVRT") => 0,
+        _ => return Err(TextureDecodeError::InvalidFile),
+    };
+
+    let Some(header) = pvr_bytes.get(pvrt_offset..pvrt_offset + 0x10) else {
+        return Err(TextureDecodeError::InvalidFile);
+    };
+    if &header[..4] != b"PVRT" {
+        return Err(TextureDecodeError::InvalidFile);
+    }
+
+    let Some(data_len) = LittleEndian::read_u32(&header[0x4..0x8]).checked_sub(8) else {
+        return Err(TextureDecodeError::InvalidFile);
+    };
+    let pixel_format = PvrPixelFormat::try_from(header[0x8])?;
+    let data_format = header[0x9];
+    let width = u32::from(LittleEndian::read_u16(&header[0xC..0xE]));
+    let height = u32::from(LittleEndian::read_u16(&header[0xE..0x10]));
+
+    let data_start = pvrt_offset + 0x10;
+    let Some(data) = pvr_bytes.get(data_start..data_start + data_len as usize) else {
+        return Err(TextureDecodeError::InvalidFile);
+    };
+
+    let is_square_format = !matches!(data_format, RECTANGLE | RECTANGLE_TWIDDLED);
+    if width == 0 || height == 0 || (is_square_format && width != height) {
+        return Err(TextureDecodeError::InvalidFile);
+    }
+
+    let read_pixel = |offset: usize| -> Result<Rgba<u8>, TextureDecodeError> {
+        match data.get(offset..offset + 2) {
+            Some(bytes) => Ok(pixel_format.decode(LittleEndian::read_u16(bytes))),
+            None => Err(TextureDecodeError::InvalidFile),
+        }
+    };
+
+    let mut image = RgbaImage::new(width, height);
+
+    match data_format {
+        SQUARE_TWIDDLED
+        | SQUARE_TWIDDLED_MIPMAPS
+        | SQUARE_TWIDDLED_MIPMAPS_ALT
+        | RECTANGLE_TWIDDLED => {
+            let offset = match data_format {
+                SQUARE_TWIDDLED_MIPMAPS | SQUARE_TWIDDLED_MIPMAPS_ALT => {
+                    mipmap_offset(data_format, width)
+                }
+                _ => 0,
+            };
+
+            for (x, y, pixel) in image.enumerate_pixels_mut() {
+                *pixel = read_pixel(offset + twiddle_rectangle(x, y, width, height) * 2)?;
+            }
+        }
+        RECTANGLE => {
+            for (x, y, pixel) in image.enumerate_pixels_mut() {
+                *pixel = read_pixel((y * width + x) as usize * 2)?;
+            }
+        }
+        VQ | VQ_MIPMAPS | SMALL_VQ | SMALL_VQ_MIPMAPS => {
+            // Each block in the codebook holds 2x2 pixels, which are twiddled as well
+            let codebook_len = codebook_size(data_format, width) * 4 * 2;
+            let offset = match data_format {
+                VQ_MIPMAPS | SMALL_VQ_MIPMAPS => codebook_len + mipmap_offset(data_format, width),
+                _ => codebook_len,
+            };
+
+            for (x, y, pixel) in image.enumerate_pixels_mut() {
+                let Some(&block) = data.get(offset + twiddle(x / 2, y / 2)) else {
+                    return Err(TextureDecodeError::InvalidFile);
+                };
+                let block_offset = block as usize * 4 * 2;
+                if block_offset >= codebook_len {
+                    return Err(TextureDecodeError::InvalidFile);
+                }
+
+                *pixel = read_pixel(block_offset + twiddle(x % 2, y % 2) * 2)?;
+            }
+        }
+        _ => return Err(TextureDecodeError::UnsupportedFormat(data_format.into())),
+    }
+
+    Ok(image)
+}
+
+/// Encodes the given `image` into a PVR texture with the given `pixel_format`, starting with a
+/// GBIX header holding `global_index`.
+///
+/// The pixels are stored in the twiddled layout, which is what the Dreamcast renders the fastest.
+/// No mipmaps are encoded.
+///
+/// # Errors
+///
+/// The twiddled layout needs both of the dimensions of `image` to be powers of 2, so if they
+/// aren't, a [`TextureEncodeError::NotPowerOfTwo`] is returned. If either of them doesn't fit in
+/// the 16 bits the header stores them in, a [`TextureEncodeError::DimensionsTooLarge`] is
+/// returned.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::error::TextureEncodeError;
+/// use gvrtex::pvr::{decode_pvr, encode_pvr, PvrPixelFormat};
+/// use image::{Rgba, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Fully saturated colors survive every pixel format, in square and rectangular textures alike
+/// let formats = [PvrPixelFormat::Argb1555, PvrPixelFormat::Rgb565, PvrPixelFormat::Argb4444];
+/// for pixel_format in formats {
+///     for (width, height) in [(8, 8), (16, 4), (2, 32)] {
+///         let image = RgbaImage::from_fn(width, height, |x, y| {
+///             let [r, g, b] = [x % 2, y % 2, (x / 2 + y) % 2].map(|c| c as u8 * 0xFF);
+///             Rgba([r, g, b, 0xFF])
+///         });
+///         let pvr = encode_pvr(&image, pixel_format, 3)?;
+///         assert_eq!(&pvr[..4], b"GBIX");
+///         assert_eq!(pvr[0x18], pixel_format as u8);
+///         assert_eq!(decode_pvr(&pvr)?, image);
+///     }
+/// }
+///
+/// // Everything else is rounded to the precision of the pixel format
+/// let image = RgbaImage::from_pixel(4, 4, Rgba([0x84, 0x84, 0x84, 0x7F]));
+/// let decoded = |format| decode_pvr(&encode_pvr(&image, format, 0).unwrap()).unwrap()[(0, 0)];
+/// assert_eq!(decoded(PvrPixelFormat::Argb1555), Rgba([0x84, 0x84, 0x84, 0x00]));
+/// assert_eq!(decoded(PvrPixelFormat::Rgb565), Rgba([0x84, 0x86, 0x84, 0xFF]));
+/// assert_eq!(decoded(PvrPixelFormat::Argb4444), Rgba([0x88, 0x88, 0x88, 0x77]));
+///
+/// // Square textures are twiddled as a whole, rectangular ones as squares of the shorter side
+/// let (r, g, b, w) = (0xFC00, 0x83E0, 0x801F, 0xFFFF);
+/// let colors = [[0xFF, 0, 0, 0xFF], [0, 0xFF, 0, 0xFF], [0, 0, 0xFF, 0xFF], [0xFF; 4]];
+/// let pixels = |pvr: &[u8]| -> Vec<u16> {
+///     pvr[0x20..].chunks(2).map(|p| u16::from_le_bytes([p[0], p[1]])).collect()
+/// };
+/// let image = RgbaImage::from_fn(2, 2, |x, y| Rgba(colors[(y * 2 + x) as usize]));
+/// let pvr = encode_pvr(&image, PvrPixelFormat::Argb1555, 0)?;
+/// assert_eq!(pvr[0x19], 0x01);
+/// assert_eq!(pixels(&pvr), [r, b, g, w]);
+/// let image = RgbaImage::from_fn(4, 2, |x, y| Rgba(colors[(y * 2 + x % 2) as usize]));
+/// let pvr = encode_pvr(&image, PvrPixelFormat::Argb1555, 0)?;
+/// assert_eq!(pvr[0x19], 0x0D);
+/// assert_eq!(pixels(&pvr), [r, b, g, w, r, b, g, w]);
+///
+/// // The header can't hold dimensions above 65535
+/// assert!(matches!(
+///     encode_pvr(&RgbaImage::new(65536, 1), PvrPixelFormat::Rgb565, 0),
+///     Err(TextureEncodeError::DimensionsTooLarge(65536, 1))
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+pub fn encode_pvr(
+    image: &RgbaImage,
+    pixel_format: PvrPixelFormat,
+    global_index: u32,
+) -> Result<Vec<u8>, TextureEncodeError> {
+    let width = image.width();
+    let height = image.height();
+    if !width.is_power_of_two() || !height.is_power_of_two() {
+        return Err(TextureEncodeError::NotPowerOfTwo(width, height));
+    }
+
+    // The header stores the dimensions as u16, and the length of the data plus the rest of the
+    // header as u32
+    let too_large = || TextureEncodeError::DimensionsTooLarge(width, height);
+    let header_width = u16::try_from(width).map_err(|_| too_large())?;
+    let header_height = u16::try_from(height).map_err(|_| too_large())?;
+    let data_len = width as usize * height as usize * 2;
+    let header_data_len = u32::try_from(data_len + 8).map_err(|_| too_large())?;
+
+    let data_format = if width == height {
+        SQUARE_TWIDDLED
+    } else {
+        RECTANGLE_TWIDDLED
+    };
+
+    let mut data = vec![0u8; data_len];
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let offset = twiddle_rectangle(x, y, width, height) * 2;
+        LittleEndian::write_u16(&mut data[offset..offset + 2], pixel_format.encode(*pixel));
+    }
+
+    let mut result = Vec::with_capacity(0x20 + data.len());
+    result.extend_from_slice(b"GBIX");
+    result.write_u32::<LittleEndian>(8)?;
+    result.write_u32::<LittleEndian>(global_index)?;
+    result.write_u32::<LittleEndian>(0)?; // padding
+
+    result.extend_from_slice(b"PVRT");
+    result.write_u32::<LittleEndian>(header_data_len)?;
+    result.write_u8(pixel_format as u8)?;
+    result.write_u8(data_format)?;
+    result.write_u16::<LittleEndian>(0)?; // padding
+    result.write_u16::<LittleEndian>(header_width)?;
+    result.write_u16::<LittleEndian>(header_height)?;
+    result.extend_from_slice(&data);
+
+    Ok(result)
+}
@@ -0,0 +1,80 @@
+use crate::formats::DataFormat;
+use crate::pixel::RGB5A3_OPAQUE_THRESHOLD;
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+/// Reduces the given image with 16 bits per channel to 8 bits per channel, with each channel
+/// rounded straight to the bit depth `data_format` stores it in.
+///
+/// The encoders truncate the 8-bit channels down to the bit depth of the data format, so going
+/// through 8 bits first rounds twice, which can end up a whole level off. Each of the returned
+/// 8-bit values is one the encoder turns back into the rounded level exactly.
+pub(crate) fn to_rgba8_rounded(
+    image: &ImageBuffer<Rgba<u16>, Vec<u16>>,
+    data_format: DataFormat,
+) -> RgbaImage {
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let pixel = match data_format {
+            DataFormat::Intensity4 | DataFormat::IntensityA4 => {
+                let intensity = round_channel(luminance(r, g, b), 4);
+                [intensity, intensity, intensity, round_channel(a, 4)]
+            }
+            DataFormat::Intensity8 | DataFormat::IntensityA8 => {
+                let intensity = round_channel(luminance(r, g, b), 8);
+                [intensity, intensity, intensity, round_channel(a, 8)]
+            }
+            DataFormat::Rgb565 => [
+                round_channel(r, 5),
+                round_channel(g, 6),
+                round_channel(b, 5),
+                round_channel(a, 8),
+            ],
+            DataFormat::Rgb5a3 => {
+                // Opaque colors are stored as RGB555, the rest as ARGB3444
+                let alpha = round_channel(a, 8);
+                let bits = if alpha >= RGB5A3_OPAQUE_THRESHOLD {
+                    5
+                } else {
+                    4
+                };
+                [
+                    round_channel(r, bits),
+                    round_channel(g, bits),
+                    round_channel(b, bits),
+                    alpha,
+                ]
+            }
+            _ => [r, g, b, a].map(|channel| round_channel(channel, 8)),
+        };
+        Rgba(pixel)
+    })
+}
+
+/// Rounds the given 16-bit channel to the nearest level of a channel with `bits` bits, returning
+/// the 8-bit value the encoders turn into that level.
+fn round_channel(value: u16, bits: u32) -> u8 {
+    let max = (1 << bits) - 1;
+    let level = (u32::from(value) * max + 0x7FFF) / 0xFFFF;
+    (level * 0xFF / max) as u8
+}
+
+/// Returns the 16-bit luminance of the given color, with the same weights as the intensity
+/// encoders.
+fn luminance(r: u16, g: u16, b: u16) -> u16 {
+    ((30 * u32::from(r) + 59 * u32::from(g) + 11 * u32::from(b)) / 100) as u16
+}
+
+/// Turns the colors of the given image with 16 bits per channel from premultiplied alpha into
+/// straight alpha, leaving fully transparent colors as they are, like
+/// [`crate::pixel::unpremultiply_alpha()`] does for 8-bit colors.
+pub(crate) fn unpremultiply_alpha_16bit(image: &mut ImageBuffer<Rgba<u16>, Vec<u16>>) {
+    for p in image.pixels_mut() {
+        let alpha = u32::from(p.0[3]);
+        if alpha == 0 {
+            continue;
+        }
+        for c in &mut p.0[..3] {
+            *c = ((u32::from(*c) * 0xFFFF + alpha / 2) / alpha).min(0xFFFF) as u16;
+        }
+    }
+}
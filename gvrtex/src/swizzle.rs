@@ -0,0 +1,214 @@
+use crate::formats::DataFormat;
+
+/// Describes how the data of an image is split into units, the smallest pieces of data the
+/// swizzling moves around, and how the units are grouped into the blocks of the data format.
+struct Layout {
+    /// The length of a unit in bytes.
+    unit_len: usize,
+    /// The amount of units in a row of the image.
+    units_x: usize,
+    /// The amount of rows of units in the image.
+    units_y: usize,
+    /// The width of a block, in units.
+    block_width: usize,
+    /// The height of a block, in units.
+    block_height: usize,
+}
+
+impl Layout {
+    fn new(width: u32, height: u32, format: DataFormat) -> Self {
+        let (width, height) = (width as usize, height as usize);
+        let (block_width, block_height) = format.block_size();
+        let (block_width, block_height) = (block_width as usize, block_height as usize);
+
+        match format {
+            // Two pixels share a byte
+            DataFormat::Intensity4 | DataFormat::Index4 => Self {
+                unit_len: 1,
+                units_x: width.div_ceil(2),
+                units_y: height,
+                block_width: block_width / 2,
+                block_height,
+            },
+            // Each unit is a compressed 4x4 block, with four of them making up an 8x8 tile
+            DataFormat::Dxt1 => Self {
+                unit_len: 8,
+                units_x: width.div_ceil(4),
+                units_y: height.div_ceil(4),
+                block_width: 2,
+                block_height: 2,
+            },
+            _ => Self {
+                unit_len: format.bits_per_pixel() as usize / 8,
+                units_x: width,
+                units_y: height,
+                block_width,
+                block_height,
+            },
+        }
+    }
+
+    fn linear_len(&self) -> usize {
+        self.units_x * self.units_y * self.unit_len
+    }
+
+    /// Calls `f` with the offset of each piece of data in the linear data, its offset in the
+    /// swizzled data, and its length.
+    fn for_each_piece(&self, format: DataFormat, mut f: impl FnMut(usize, usize, usize)) {
+        let blocks_x = self.units_x.div_ceil(self.block_width);
+        let block_units = self.block_width * self.block_height;
+
+        for y in 0..self.units_y {
+            for x in 0..self.units_x {
+                let linear = (y * self.units_x + x) * self.unit_len;
+                let block = (y / self.block_height) * blocks_x + x / self.block_width;
+                let unit = (y % self.block_height) * self.block_width + x % self.block_width;
+
+                if format == DataFormat::Argb8888 {
+                    // Each block stores the alpha and red of its pixels, followed by the green
+                    // and blue
+                    let swizzled = block * block_units * 4 + unit * 2;
+                    f(linear, swizzled, 2);
+                    f(linear + 2, swizzled + block_units * 2, 2);
+                } else {
+                    let swizzled = (block * block_units + unit) * self.unit_len;
+                    f(linear, swizzled, self.unit_len);
+                }
+            }
+        }
+    }
+}
+
+/// Reorders the given `data` of an image from row-major order into the blocks of the given data
+/// `format`, without changing any of the values.
+///
+/// This is only the reordering part of encoding a texture, for when the data has already been
+/// converted to the data format, such as DXT1 (BC1) compressed data taken from another texture.
+/// The layout of `data` depends on the format:
+///
+/// * Each pixel takes up [`DataFormat::bits_per_pixel()`] bits, and each row starts on a new
+///   byte. Two pixels of [`DataFormat::Intensity4`] and [`DataFormat::Index4`] share a byte.
+/// * The pixels of [`DataFormat::Argb8888`] are stored as the alpha, red, green and blue bytes.
+///   These are split into an alpha and red, and a green and blue half of each block.
+/// * [`DataFormat::Dxt1`] data consists of row-major compressed 4x4 blocks of 8 bytes, which are
+///   grouped into 8x8 tiles. The bytes of the blocks are kept as is, so the byte order of the
+///   colors and the order of the indices in the blocks have to be those of GameCube textures
+///   already.
+///
+/// The color palette of [`DataFormat::Index4`] and [`DataFormat::Index8`] isn't part of the
+/// data. The result is padded to full blocks on the right and bottom edges with zeros.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+/// use gvrtex::pixel;
+/// use image::{Rgba, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = RgbaImage::from_fn(12, 8, |x, y| Rgba([x as u8 * 20, y as u8 * 30, 0x80, 0xFF]));
+/// let linear = pixel::encode_pixels_u16(image.as_raw(), pixel::encode_rgb565);
+///
+/// let swizzled = gvrtex::swizzle(&linear, 12, 8, DataFormat::Rgb565);
+/// assert_eq!(swizzled, gvrtex::encode_raw(&image, DataFormat::Rgb565, None)?);
+/// assert_eq!(gvrtex::deswizzle(&swizzled, 12, 8, DataFormat::Rgb565), linear);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Deswizzling always undoes swizzling, for any format and dimensions:
+///
+/// ```
+/// use gvrtex::formats::DataFormat;
+///
+/// let mut state = 0x1234_5678_u32;
+/// let mut random_bytes = |len| -> Vec<u8> {
+///     (0..len)
+///         .map(|_| {
+///             state ^= state << 13;
+///             state ^= state >> 17;
+///             state ^= state << 5;
+///             state as u8
+///         })
+///         .collect()
+/// };
+///
+/// for format in DataFormat::ALL {
+///     for width in 1..=20u32 {
+///         for height in 1..=20u32 {
+///             let len = match format {
+///                 DataFormat::Dxt1 => width.div_ceil(4) * height.div_ceil(4) * 8,
+///                 _ => (width * format.bits_per_pixel()).div_ceil(8) * height,
+///             };
+///             let linear = random_bytes(len as usize);
+///
+///             let swizzled = gvrtex::swizzle(&linear, width, height, format);
+///             assert_eq!(swizzled.len(), format.data_size(width, height, 0) - palette_len(format));
+///             assert_eq!(gvrtex::deswizzle(&swizzled, width, height, format), linear);
+///
+///             // Swizzling undoes deswizzling as well when there's no padding
+///             let (block_width, block_height) = format.block_size();
+///             if width % block_width == 0 && height % block_height == 0 {
+///                 let swizzled = random_bytes(swizzled.len());
+///                 let linear = gvrtex::deswizzle(&swizzled, width, height, format);
+///                 assert_eq!(gvrtex::swizzle(&linear, width, height, format), swizzled);
+///             }
+///         }
+///     }
+/// }
+///
+/// fn palette_len(format: DataFormat) -> usize {
+///     match format {
+///         DataFormat::Index4 => 16 * 2,
+///         DataFormat::Index8 => 256 * 2,
+///         _ => 0,
+///     }
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if `data` is shorter than an image of the given dimensions in the given `format`.
+pub fn swizzle(data: &[u8], width: u32, height: u32, format: DataFormat) -> Vec<u8> {
+    let layout = Layout::new(width, height, format);
+    assert!(
+        data.len() >= layout.linear_len(),
+        "the data is {} bytes long, but the image needs {} bytes",
+        data.len(),
+        layout.linear_len()
+    );
+
+    let mut swizzled = vec![0; format.image_size(width, height)];
+    layout.for_each_piece(format, |linear, swizzled_offset, len| {
+        swizzled[swizzled_offset..swizzled_offset + len]
+            .copy_from_slice(&data[linear..linear + len]);
+    });
+    swizzled
+}
+
+/// Reorders the given `data` of an image from the blocks of the given data `format` into
+/// row-major order, without changing any of the values. This undoes [`swizzle()`], see it for
+/// the layout of the returned data.
+///
+/// The padding of the blocks on the right and bottom edges is left out of the result.
+///
+/// # Panics
+///
+/// Panics if `data` is shorter than an image of the given dimensions in the given `format`,
+/// padded to full blocks.
+pub fn deswizzle(data: &[u8], width: u32, height: u32, format: DataFormat) -> Vec<u8> {
+    let layout = Layout::new(width, height, format);
+    let swizzled_len = format.image_size(width, height);
+    assert!(
+        data.len() >= swizzled_len,
+        "the data is {} bytes long, but the image needs {} bytes",
+        data.len(),
+        swizzled_len
+    );
+
+    let mut linear = vec![0; layout.linear_len()];
+    layout.for_each_piece(format, |linear_offset, swizzled, len| {
+        linear[linear_offset..linear_offset + len].copy_from_slice(&data[swizzled..swizzled + len]);
+    });
+    linear
+}
@@ -0,0 +1,402 @@
+//! Contains conversions between GVR textures and TPL textures, the official texture file format
+//! for GameCube/Wii games.
+//!
+//! The image data of a GVR texture is stored exactly the same way as it is in a TPL texture, so
+//! these conversions only rewrite the headers around the image data, the pixels themselves are
+//! never re-encoded.
+
+use crate::bytes::split_texture;
+use crate::error::TextureDecodeError;
+use crate::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+use crate::header::GvrHeader;
+use crate::pixel_codecs::palette_size;
+use crate::TextureEncoder;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read};
+
+/// The magic number every TPL file starts with.
+const TPL_MAGIC: u32 = 0x0020AF30;
+/// The format code of the C14X2 format, which only exists in TPL textures.
+const TPL_FORMAT_C14X2: u32 = 0x0A;
+/// The size of the header of a single texture in a TPL file.
+const TPL_IMAGE_HEADER_SIZE: usize = 0x24;
+/// The size of the header of a single color palette in a TPL file.
+const TPL_PALETTE_HEADER_SIZE: usize = 0xC;
+/// The texture filter mode for linear filtering.
+const GX_LINEAR: u32 = 1;
+/// The texture filter mode for linear filtering, blending linearly between mipmaps.
+const GX_LIN_MIP_LIN: u32 = 5;
+
+/// Converts the texture at `texture_index` in the given TPL file into a GVR texture, starting
+/// with the header given in `texture_type`.
+///
+/// The image data (and the color palette, for [`DataFormat::Index4`] and [`DataFormat::Index8`]
+/// textures) is copied over as is. Palettes with fewer colors than the data format allows for are
+/// padded with zeroes. Only the full size image is converted, any mipmaps in the TPL file are left
+/// out.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::error::TextureDecodeError;
+/// use gvrtex::formats::{DataFormat, PixelFormat, TextureType};
+/// use gvrtex::tpl::{gvr_to_tpl, tpl_to_gvr};
+/// use gvrtex::TextureEncoder;
+/// use image::{DynamicImage, Rgba, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = RgbaImage::from_fn(16, 8, |x, y| Rgba([x as u8 * 16, y as u8 * 32, 0x40, 0xFF]));
+///
+/// // Neither the image data nor the color palette change on the way through a TPL file
+/// let encoders = [
+///     TextureEncoder::new_gcix(DataFormat::Rgb5a3)?,
+///     TextureEncoder::new_gcix_palettized(PixelFormat::RGB565, DataFormat::Index4)?,
+///     TextureEncoder::new_gcix_palettized(PixelFormat::RGB5A3, DataFormat::Index8)?,
+/// ];
+/// for mut encoder in encoders {
+///     let gvr = encoder.encode_image(DynamicImage::ImageRgba8(image.clone()))?;
+///     let tpl = gvr_to_tpl(&gvr)?;
+///     let converted = tpl_to_gvr(&tpl, 0, TextureType::Gcix)?;
+///     assert_eq!(converted, gvr);
+///     assert_eq!(gvr_to_tpl(&converted)?, tpl);
+/// }
+///
+/// // C14X2 textures can't be stored in GVR textures
+/// let gvr = TextureEncoder::new_gcix(DataFormat::Rgb565)?.encode_image(image.into())?;
+/// let mut tpl = gvr_to_tpl(&gvr)?;
+/// tpl[0x18..0x1C].copy_from_slice(&0x0Au32.to_be_bytes());
+/// assert!(matches!(
+///     tpl_to_gvr(&tpl, 0, TextureType::Gcix),
+///     Err(TextureDecodeError::UnsupportedFormat(0x0A))
+/// ));
+///
+/// // A 56 byte file claiming to hold a 65535x65535 ARGB8888 texture is rejected before the image
+/// // data is allocated
+/// let mut tpl = gvr_to_tpl(&gvr)?;
+/// tpl[0x14..0x18].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+/// tpl[0x18..0x1C].copy_from_slice(&0x06u32.to_be_bytes());
+/// tpl.truncate(0x38);
+/// assert!(matches!(
+///     tpl_to_gvr(&tpl, 0, TextureType::Gcix),
+///     Err(TextureDecodeError::InvalidFile)
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If `tpl_bytes` isn't a valid TPL file, it doesn't have a texture at `texture_index`, or it's
+/// too short to hold the image data and color palette its headers state, a
+/// [`TextureDecodeError::InvalidFile`] is returned. If the texture uses the C14X2 format, which
+/// has no equivalent in GVR textures, a [`TextureDecodeError::UnsupportedFormat`] is returned.
+pub fn tpl_to_gvr(
+    tpl_bytes: &[u8],
+    texture_index: usize,
+    texture_type: TextureType,
+) -> Result<Vec<u8>, TextureDecodeError> {
+    let mut reader = Cursor::new(tpl_bytes);
+
+    if reader.read_u32::<BigEndian>()? != TPL_MAGIC {
+        return Err(TextureDecodeError::InvalidFile);
+    }
+
+    let texture_count = reader.read_u32::<BigEndian>()? as usize;
+    let table_offset = reader.read_u32::<BigEndian>()?;
+    if texture_index >= texture_count {
+        return Err(TextureDecodeError::InvalidFile);
+    }
+
+    // Each entry in the table consists of the offsets to the image header and the palette header
+    reader.set_position(u64::from(table_offset) + texture_index as u64 * 8);
+    let image_header_offset = reader.read_u32::<BigEndian>()?;
+    let palette_header_offset = reader.read_u32::<BigEndian>()?;
+
+    reader.set_position(image_header_offset.into());
+    let height = reader.read_u16::<BigEndian>()?;
+    let width = reader.read_u16::<BigEndian>()?;
+    let format = reader.read_u32::<BigEndian>()?;
+    let image_data_offset = reader.read_u32::<BigEndian>()?;
+
+    if format == TPL_FORMAT_C14X2 {
+        return Err(TextureDecodeError::UnsupportedFormat(format));
+    }
+    let data_format = u8::try_from(format)
+        .map_err(|_| TextureDecodeError::InvalidFile)
+        .and_then(DataFormat::try_from)?;
+
+    let mut encoder = TextureEncoder {
+        texture_type,
+        data_format,
+        ..Default::default()
+    };
+    let mut data = Vec::new();
+
+    if data_format.requires_palette() {
+        if palette_header_offset == 0 {
+            return Err(TextureDecodeError::InvalidFile);
+        }
+
+        reader.set_position(palette_header_offset.into());
+        let colors = reader.read_u16::<BigEndian>()? as usize;
+        reader.read_u16::<BigEndian>()?; // unpacked flag and padding
+        let palette_format = reader.read_u32::<BigEndian>()?;
+        let palette_data_offset = reader.read_u32::<BigEndian>()?;
+
+        let max_colors = palette_size(data_format) as usize;
+        if colors > max_colors {
+            return Err(TextureDecodeError::InvalidFile);
+        }
+        encoder.pixel_format = u8::try_from(palette_format)
+            .map_err(|_| TextureDecodeError::InvalidFile)
+            .and_then(PixelFormat::try_from)?;
+        encoder.data_flags = DataFlags::InternalPalette;
+
+        if !holds_range(tpl_bytes, palette_data_offset, colors * size_of::<u16>()) {
+            return Err(TextureDecodeError::InvalidFile);
+        }
+        data.resize(max_colors * size_of::<u16>(), 0);
+        reader.set_position(palette_data_offset.into());
+        reader.read_exact(&mut data[..colors * size_of::<u16>()])?;
+    }
+
+    // The dimensions come straight from the file, so they're checked against its length before
+    // allocating anything for the image data
+    let image_len = data_format.image_size(width.into(), height.into());
+    if !holds_range(tpl_bytes, image_data_offset, image_len) {
+        return Err(TextureDecodeError::InvalidFile);
+    }
+    let palette_len = data.len();
+    data.resize(palette_len + image_len, 0);
+    reader.set_position(image_data_offset.into());
+    reader.read_exact(&mut data[palette_len..])?;
+
+    let mut result = Vec::new();
+    encoder.write_header(width.into(), height.into(), data.len(), &mut result)?;
+    result.extend_from_slice(&data);
+
+    Ok(result)
+}
+
+/// Returns whether `bytes` holds `len` bytes starting at `offset`.
+fn holds_range(bytes: &[u8], offset: u32, len: usize) -> bool {
+    (offset as usize)
+        .checked_add(len)
+        .is_some_and(|end| end <= bytes.len())
+}
+
+/// A single GVR texture split into the parts that make up a texture in a TPL file.
+struct TplEntry<'a> {
+    header: GvrHeader,
+    palette: Option<&'a [u8]>,
+    image: &'a [u8],
+    mipmap_levels: u8,
+}
+
+impl<'a> TplEntry<'a> {
+    fn new(gvr_bytes: &'a [u8]) -> Result<Self, TextureDecodeError> {
+        let (header, mut data) = split_texture(gvr_bytes)?;
+        if header.flags.intersects(DataFlags::ExternalPalette) {
+            return Err(TextureDecodeError::MissingPalette);
+        }
+
+        let mut palette = None;
+        if let Some(colors) = header.palette_colors() {
+            let palette_len = colors * size_of::<u16>();
+            let (palette_data, image_data) = data.split_at(palette_len);
+            palette = Some(palette_data);
+            data = image_data;
+        }
+
+        let mut width = u32::from(header.width);
+        let mut height = u32::from(header.height);
+        let mut image_len = header.data_format.image_size(width, height);
+        if data.len() < image_len {
+            return Err(TextureDecodeError::InvalidFile);
+        }
+
+        // Every mipmap halves the dimensions of the previous one, all the way down to 1x1
+        let mut mipmap_levels = 0;
+        if header.flags.intersects(DataFlags::Mipmaps) {
+            while width > 1 || height > 1 {
+                width = (width / 2).max(1);
+                height = (height / 2).max(1);
+
+                let mipmap_len = header.data_format.image_size(width, height);
+                if image_len + mipmap_len > data.len() {
+                    break;
+                }
+                image_len += mipmap_len;
+                mipmap_levels += 1;
+            }
+        }
+
+        Ok(Self {
+            header,
+            palette,
+            image: &data[..image_len],
+            mipmap_levels,
+        })
+    }
+}
+
+/// Rounds the given `offset` up to the next multiple of 32, which is the alignment the texture
+/// data in TPL files has.
+fn align_32(offset: usize) -> usize {
+    offset.next_multiple_of(32)
+}
+
+/// Converts the given GVR texture into a TPL file holding just that texture.
+///
+/// See [`gvrs_to_tpl()`] for the details.
+///
+/// # Errors
+///
+/// See [`gvrs_to_tpl()`].
+pub fn gvr_to_tpl(gvr_bytes: &[u8]) -> Result<Vec<u8>, TextureDecodeError> {
+    gvrs_to_tpl(&[gvr_bytes])
+}
+
+/// Converts the given GVR textures into a single TPL file, with the textures stored in the same
+/// order as they're given in.
+///
+/// The image data (including any mipmaps) and the color palettes are copied over as is. Each
+/// texture uses linear filtering and clamps its texture coordinates. The LOD range of mipmapped
+/// textures covers all of their mipmaps.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::error::TextureDecodeError;
+/// use gvrtex::formats::{DataFlags, DataFormat, PixelFormat, TextureType};
+/// use gvrtex::tpl::{gvrs_to_tpl, tpl_to_gvr};
+/// use gvrtex::TextureEncoder;
+/// use image::{DynamicImage, RgbaImage};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let image = DynamicImage::ImageRgba8(RgbaImage::new(16, 16));
+/// let plain = TextureEncoder::new_gcix(DataFormat::Rgb565)?.encode_image(image.clone())?;
+/// let mipmapped = TextureEncoder::new_gcix(DataFormat::Rgb565)?
+///     .with_mipmaps()?
+///     .encode_image(image)?;
+///
+/// let tpl = gvrs_to_tpl(&[&plain, &mipmapped])?;
+/// assert_eq!(tpl[4..8], 2u32.to_be_bytes());
+///
+/// // The image headers follow the table of 2 entries, holding the min and max LOD at 0x21
+/// let lods = |header: usize| (tpl[header + 0x21], tpl[header + 0x22]);
+/// assert_eq!(lods(0x1C), (0, 0));
+/// assert_eq!(lods(0x1C + 0x24), (0, 4));
+///
+/// // The mipmaps are left out when converting back
+/// assert_eq!(tpl_to_gvr(&tpl, 0, TextureType::Gcix)?, plain);
+/// assert_eq!(tpl_to_gvr(&tpl, 1, TextureType::Gcix)?.len(), plain.len());
+/// assert!(matches!(
+///     tpl_to_gvr(&tpl, 2, TextureType::Gcix),
+///     Err(TextureDecodeError::InvalidFile)
+/// ));
+///
+/// // TPL files hold the color palette, so it can't be left out of the texture
+/// let external = TextureEncoder::new_gcix_palettized(PixelFormat::RGB565, DataFormat::Index4)?
+///     .with_data_flags(DataFlags::ExternalPalette)?
+///     .encode_image(DynamicImage::ImageRgba8(RgbaImage::new(8, 8)))?;
+/// assert!(matches!(
+///     gvrs_to_tpl(&[&plain, &external]),
+///     Err(TextureDecodeError::MissingPalette)
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If any of the textures isn't a valid GVR texture, a [`TextureDecodeError::InvalidFile`] is
+/// returned. Textures with the [`DataFlags::ExternalPalette`] flag don't hold their color palette,
+/// which TPL files need, so a [`TextureDecodeError::MissingPalette`] is returned for those.
+pub fn gvrs_to_tpl(gvr_textures: &[&[u8]]) -> Result<Vec<u8>, TextureDecodeError> {
+    let entries = gvr_textures
+        .iter()
+        .map(|texture| TplEntry::new(texture))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Work out where every header and all the data will be placed in the file
+    let table_offset = 0xC;
+    let mut offset = table_offset + entries.len() * 8;
+    let mut header_offsets = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        let image_header_offset = offset;
+        offset += TPL_IMAGE_HEADER_SIZE;
+
+        let palette_header_offset = entry.palette.map(|_| {
+            offset += TPL_PALETTE_HEADER_SIZE;
+            offset - TPL_PALETTE_HEADER_SIZE
+        });
+        header_offsets.push((image_header_offset, palette_header_offset));
+    }
+
+    let mut data_offsets = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        let image_offset = align_32(offset);
+        offset = image_offset + entry.image.len();
+
+        let palette_offset = entry.palette.map(|palette| {
+            let palette_offset = align_32(offset);
+            offset = palette_offset + palette.len();
+            palette_offset
+        });
+        data_offsets.push((image_offset, palette_offset));
+    }
+
+    let mut result = Vec::with_capacity(offset);
+    result.write_u32::<BigEndian>(TPL_MAGIC)?;
+    result.write_u32::<BigEndian>(entries.len() as u32)?;
+    result.write_u32::<BigEndian>(table_offset as u32)?;
+
+    for &(image_header_offset, palette_header_offset) in &header_offsets {
+        result.write_u32::<BigEndian>(image_header_offset as u32)?;
+        result.write_u32::<BigEndian>(palette_header_offset.unwrap_or(0) as u32)?;
+    }
+
+    for (entry, &(image_offset, palette_offset)) in entries.iter().zip(&data_offsets) {
+        let min_filter = if entry.mipmap_levels > 0 {
+            GX_LIN_MIP_LIN
+        } else {
+            GX_LINEAR
+        };
+
+        result.write_u16::<BigEndian>(entry.header.height)?;
+        result.write_u16::<BigEndian>(entry.header.width)?;
+        result.write_u32::<BigEndian>(u8::from(entry.header.data_format).into())?;
+        result.write_u32::<BigEndian>(image_offset as u32)?;
+        result.write_u32::<BigEndian>(0)?; // wrap s (clamp)
+        result.write_u32::<BigEndian>(0)?; // wrap t (clamp)
+        result.write_u32::<BigEndian>(min_filter)?;
+        result.write_u32::<BigEndian>(GX_LINEAR)?; // mag filter
+        result.write_f32::<BigEndian>(0.)?; // lod bias
+        result.write_u8(0)?; // edge lod
+        result.write_u8(0)?; // min lod
+        result.write_u8(entry.mipmap_levels)?; // max lod
+        result.write_u8(0)?; // unpacked
+
+        if let (Some(palette), Some(palette_offset)) = (entry.palette, palette_offset) {
+            let colors = palette.len() / size_of::<u16>();
+            result.write_u16::<BigEndian>(colors as u16)?;
+            result.write_u8(0)?; // unpacked
+            result.write_u8(0)?; // padding
+            result.write_u32::<BigEndian>(u8::from(entry.header.pixel_format).into())?;
+            result.write_u32::<BigEndian>(palette_offset as u32)?;
+        }
+    }
+
+    for (entry, &(image_offset, palette_offset)) in entries.iter().zip(&data_offsets) {
+        result.resize(image_offset, 0);
+        result.extend_from_slice(entry.image);
+
+        if let (Some(palette), Some(palette_offset)) = (entry.palette, palette_offset) {
+            result.resize(palette_offset, 0);
+            result.extend_from_slice(palette);
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,392 @@
+//! Bindings for JavaScript through `wasm-bindgen`, enabled with the `wasm` feature.
+//!
+//! Built for `wasm32-unknown-unknown` (for example with `wasm-pack build --features wasm`), this
+//! exports [`decode_gvr()`] and [`encode_gvr()`] to JavaScript. Byte slices are passed in and
+//! returned as `Uint8Array`s, and errors are thrown as JavaScript `Error`s.
+//!
+//! ```js
+//! import { decode_gvr, encode_gvr } from "gvrtex";
+//!
+//! const gvr = encode_gvr(rgba, 64, 64, '{"format": "rgb5a3", "texture_type": "gbix"}');
+//! const texture = decode_gvr(gvr);
+//! const image = new ImageData(new Uint8ClampedArray(texture.pixels), texture.width);
+//! ```
+//!
+//! # Examples
+//!
+//! The functions can be called from Rust as well, as long as they don't fail:
+//!
+//! ```
+//! use gvrtex::wasm::{decode_gvr, encode_gvr};
+//!
+//! let rgba: Vec<u8> = (0..8 * 8).flat_map(|i| [i as u8 * 4, 0x80, 0, 0xFF]).collect();
+//! let options = r#"{"format": "argb8888", "texture_type": "gbix", "global_index": 7}"#;
+//! let gvr = encode_gvr(&rgba, 8, 8, options).unwrap();
+//! assert_eq!(&gvr[..4], b"GBIX");
+//!
+//! let texture = decode_gvr(&gvr).unwrap();
+//! assert_eq!((texture.width(), texture.height()), (8, 8));
+//! assert_eq!(texture.pixels(), rgba);
+//!
+//! let options = r#"{ "format": "index8", "palette_format": "rgb565", "flip_v": true }"#;
+//! let gvr = encode_gvr(&rgba, 8, 8, options).unwrap();
+//! assert_eq!(decode_gvr(&gvr).unwrap().height(), 8);
+//! assert_eq!(&encode_gvr(&rgba, 8, 8, "").unwrap()[..4], b"GCIX");
+//! ```
+
+use crate::error::ParseFormatError;
+use crate::formats::{DataFormat, PixelFormat, TextureType};
+use crate::{TextureDecoder, TextureEncoder};
+use image::{DynamicImage, RgbaImage};
+use std::error::Error;
+use std::fmt;
+use std::io::Cursor;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+use wasm_bindgen::prelude::*;
+
+/// A texture decoded by [`decode_gvr()`].
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct DecodedTexture {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl DecodedTexture {
+    /// The width of the texture in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the texture in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The pixels of the texture in row-major order, 4 bytes each in RGBA order. Each access
+    /// copies the pixels into a new `Uint8Array`.
+    #[wasm_bindgen(getter)]
+    pub fn pixels(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
+
+/// Decodes the GVR texture in `bytes` into RGBA pixels.
+///
+/// # Errors
+///
+/// If the texture can't be decoded, the [`crate::error::TextureDecodeError`] is thrown as an
+/// `Error`.
+#[wasm_bindgen]
+pub fn decode_gvr(bytes: &[u8]) -> Result<DecodedTexture, JsError> {
+    let mut decoder = TextureDecoder::new_from_reader(Cursor::new(bytes));
+    decoder.decode()?;
+    let image = decoder.into_decoded()?;
+
+    Ok(DecodedTexture {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+    })
+}
+
+/// Encodes `width` x `height` RGBA pixels into a GVR texture.
+///
+/// `options_json` is parsed into [`EncodeOptions`], whose documentation lists the keys it can
+/// hold.
+///
+/// # Errors
+///
+/// If the options are invalid, `rgba` doesn't hold `width` x `height` pixels, or the image can't
+/// be encoded, an `Error` is thrown.
+#[wasm_bindgen]
+pub fn encode_gvr(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    options_json: &str,
+) -> Result<Vec<u8>, JsError> {
+    let options: EncodeOptions = options_json.parse()?;
+    let image = RgbaImage::from_raw(width, height, rgba.to_vec()).ok_or_else(|| {
+        JsError::new(&format!(
+            "{} bytes of pixels don't make up a {width}x{height} image",
+            rgba.len()
+        ))
+    })?;
+
+    let mut encoder = if options.format.requires_palette() {
+        TextureEncoder::new_palettized(
+            options.texture_type,
+            options.palette_format,
+            options.format,
+        )?
+    } else {
+        TextureEncoder::new(options.texture_type, options.format)?
+    };
+    if let Some(global_index) = options.global_index {
+        encoder = encoder.with_global_index(global_index);
+    }
+    if options.mipmaps {
+        encoder = encoder.with_mipmaps()?;
+    }
+    if options.flip_v {
+        encoder = encoder.with_flip_v();
+    }
+
+    Ok(encoder.encode_image(DynamicImage::ImageRgba8(image))?)
+}
+
+/// The options of [`encode_gvr()`], parsed from a flat JSON object.
+///
+/// The object can hold any of the following keys, and an empty string stands for the defaults:
+///
+/// * `"format"`: the name of the data format, as parsed by [`DataFormat`]'s `FromStr`
+///   implementation. Defaults to `"rgb5a3"`.
+/// * `"palette_format"`: the name of the pixel format of the color palette, for the palettized
+///   data formats. Defaults to `"intensitya8"`.
+/// * `"texture_type"`: `"gcix"`, `"gbix"` or `"none"`. Defaults to `"gcix"`.
+/// * `"global_index"`: the global index stored in the GCIX/GBIX header.
+/// * `"mipmaps"`: whether to generate mipmaps.
+/// * `"flip_v"`: whether to flip the image vertically.
+///
+/// Strings can contain the `\"`, `\\` and `\/` escapes, and numbers have to be unsigned
+/// integers that fit a `u32`.
+///
+/// # Examples
+///
+/// ```
+/// use gvrtex::formats::{DataFormat, PixelFormat, TextureType};
+/// use gvrtex::wasm::EncodeOptions;
+///
+/// let options: EncodeOptions = r#"{
+///     "format": "index4",
+///     "palette_format": "rgb565",
+///     "texture_type": "GBIX",
+///     "global_index": 4294967295,
+///     "mipmaps": false,
+///     "flip_v": true
+/// }"#
+/// .parse()
+/// .unwrap();
+/// assert_eq!(options.format, DataFormat::Index4);
+/// assert_eq!(options.palette_format, PixelFormat::RGB565);
+/// assert_eq!(options.texture_type, TextureType::Gbix);
+/// assert_eq!(options.global_index, Some(u32::MAX));
+/// assert!(!options.mipmaps && options.flip_v);
+///
+/// assert_eq!("".parse::<EncodeOptions>().unwrap(), EncodeOptions::default());
+/// assert_eq!(" { } ".parse::<EncodeOptions>().unwrap(), EncodeOptions::default());
+///
+/// // Escaped quotes, backslashes and slashes end up in the parsed string
+/// let error = r#"{"texture_type": "g\"c\\i\/x"}"#.parse::<EncodeOptions>().unwrap_err();
+/// assert_eq!(error.to_string(), r#"unknown texture type "g"c\i/x""#);
+/// ```
+///
+/// Malformed JSON, unknown keys and values of the wrong type are rejected:
+///
+/// ```
+/// use gvrtex::wasm::EncodeOptions;
+///
+/// for (json, message) in [
+///     (r#""format": "rgb565""#, "expected '{' in the options"),
+///     (r#"{"format": "rgb565""#, "expected ',' or '}' in the options"),
+///     (r#"{"format" "rgb565"}"#, "expected ':' in the options"),
+///     (r#"{format: "rgb565"}"#, "expected '\"' in the options"),
+///     (r#"{"format": "rgb565",}"#, "expected '\"' in the options"),
+///     (r#"{"format": "rgb565"} {}"#, "unexpected data after the options"),
+///     (r#"{"format": "rgb565"#, "unterminated string in the options"),
+///     (r#"{"format": "rgb\u0035"}"#, "unsupported escape in the options"),
+///     (r#"{"mipmaps": yes}"#, "unexpected value yes in the options"),
+///     (r#"{"global_index": -1}"#, "expected a string, number or boolean in the options"),
+///     (r#"{"global_index": 4294967296}"#, "number 4294967296 is out of range in the options"),
+///     (r#"{"mipmaps": "true"}"#, r#"option "mipmaps" has the wrong type"#),
+///     (r#"{"global_index": true}"#, r#"option "global_index" has the wrong type"#),
+///     (r#"{"flip_h": true}"#, r#"unknown option "flip_h""#),
+///     (r#"{"texture_type": "gvrt"}"#, r#"unknown texture type "gvrt""#),
+/// ] {
+///     let error = json.parse::<EncodeOptions>().unwrap_err();
+///     assert_eq!(error.to_string(), message, "{json}");
+/// }
+///
+/// let error = r#"{"format": "rgb666"}"#.parse::<EncodeOptions>().unwrap_err();
+/// assert!(error.to_string().starts_with(r#"Unknown data format "rgb666""#));
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// The data format to encode the texture in.
+    pub format: DataFormat,
+    /// The pixel format of the color palette, for the palettized data formats.
+    pub palette_format: PixelFormat,
+    /// The header the texture starts with.
+    pub texture_type: TextureType,
+    /// The global index stored in the GCIX/GBIX header, if any.
+    pub global_index: Option<u32>,
+    /// Whether to generate mipmaps.
+    pub mipmaps: bool,
+    /// Whether to flip the image vertically.
+    pub flip_v: bool,
+}
+
+/// The error returned when parsing [`EncodeOptions`] fails, describing what's wrong with them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptionsError {
+    message: String,
+}
+
+impl Error for ParseOptionsError {}
+
+impl fmt::Display for ParseOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for ParseOptionsError {
+    fn from(message: String) -> Self {
+        Self { message }
+    }
+}
+
+/// A value of a JSON object, as far as [`EncodeOptions`] needs them.
+enum JsonValue {
+    String(String),
+    Number(u32),
+    Bool(bool),
+}
+
+impl FromStr for EncodeOptions {
+    type Err = ParseOptionsError;
+
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        Self::parse(json).map_err(ParseOptionsError::from)
+    }
+}
+
+impl EncodeOptions {
+    /// Parses the options from a flat JSON object, returning a description of the error if it
+    /// fails.
+    fn parse(json: &str) -> Result<Self, String> {
+        let mut options = Self::default();
+        if json.trim().is_empty() {
+            return Ok(options);
+        }
+
+        let mut chars = json.chars().peekable();
+        expect(&mut chars, '{')?;
+        if skip_whitespace(&mut chars) == Some('}') {
+            chars.next();
+        } else {
+            loop {
+                skip_whitespace(&mut chars);
+                let key = parse_string(&mut chars)?;
+                expect(&mut chars, ':')?;
+                let value = parse_value(&mut chars)?;
+                options.set(&key, value)?;
+
+                skip_whitespace(&mut chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some('}') => break,
+                    _ => return Err("expected ',' or '}' in the options".to_owned()),
+                }
+            }
+        }
+
+        if skip_whitespace(&mut chars).is_some() {
+            return Err("unexpected data after the options".to_owned());
+        }
+        Ok(options)
+    }
+
+    fn set(&mut self, key: &str, value: JsonValue) -> Result<(), String> {
+        match (key, value) {
+            ("format", JsonValue::String(name)) => {
+                self.format = name.parse().map_err(|e: ParseFormatError| e.to_string())?;
+            }
+            ("palette_format", JsonValue::String(name)) => {
+                self.palette_format = name.parse().map_err(|e: ParseFormatError| e.to_string())?;
+            }
+            ("texture_type", JsonValue::String(name)) => {
+                self.texture_type = match name.to_ascii_lowercase().as_str() {
+                    "gcix" => TextureType::Gcix,
+                    "gbix" => TextureType::Gbix,
+                    "none" => TextureType::None,
+                    _ => return Err(format!("unknown texture type \"{name}\"")),
+                };
+            }
+            ("global_index", JsonValue::Number(index)) => self.global_index = Some(index),
+            ("mipmaps", JsonValue::Bool(mipmaps)) => self.mipmaps = mipmaps,
+            ("flip_v", JsonValue::Bool(flip_v)) => self.flip_v = flip_v,
+            (
+                "format" | "palette_format" | "texture_type" | "global_index" | "mipmaps"
+                | "flip_v",
+                _,
+            ) => return Err(format!("option \"{key}\" has the wrong type")),
+            _ => return Err(format!("unknown option \"{key}\"")),
+        }
+        Ok(())
+    }
+}
+
+/// Skips whitespace, returning the character after it without consuming it.
+fn skip_whitespace(chars: &mut Peekable<Chars>) -> Option<char> {
+    while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+    chars.peek().copied()
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    skip_whitespace(chars);
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        _ => Err(format!("expected '{expected}' in the options")),
+    }
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    expect(chars, '"')?;
+    let mut string = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some(c @ ('"' | '\\' | '/')) => string.push(c),
+                _ => return Err("unsupported escape in the options".to_owned()),
+            },
+            Some(c) => string.push(c),
+            None => return Err("unterminated string in the options".to_owned()),
+        }
+    }
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+    match skip_whitespace(chars) {
+        Some('"') => parse_string(chars).map(JsonValue::String),
+        Some(c) if c.is_ascii_digit() => {
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            digits
+                .parse()
+                .map(JsonValue::Number)
+                .map_err(|_| format!("number {digits} is out of range in the options"))
+        }
+        Some(c) if c.is_ascii_alphabetic() => {
+            let mut word = String::new();
+            while let Some(letter) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                word.push(letter);
+            }
+            match word.as_str() {
+                "true" => Ok(JsonValue::Bool(true)),
+                "false" => Ok(JsonValue::Bool(false)),
+                _ => Err(format!("unexpected value {word} in the options")),
+            }
+        }
+        _ => Err("expected a string, number or boolean in the options".to_owned()),
+    }
+}
@@ -0,0 +1,32 @@
+//! Round trips textures through the JavaScript bindings, run in Node with
+//! `wasm-pack test --node --features wasm` from the crate directory.
+#![cfg(target_arch = "wasm32")]
+
+use gvrtex::wasm::{decode_gvr, encode_gvr};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn pixels() -> Vec<u8> {
+    (0..8 * 8)
+        .flat_map(|i| [i as u8 * 4, 0x80, 0xFF - i as u8, 0xFF])
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn round_trip() {
+    let rgba = pixels();
+    let options = r#"{"format": "argb8888", "texture_type": "gbix", "global_index": 7}"#;
+    let gvr = encode_gvr(&rgba, 8, 8, options).unwrap();
+    assert_eq!(&gvr[..4], b"GBIX");
+
+    let texture = decode_gvr(&gvr).unwrap();
+    assert_eq!((texture.width(), texture.height()), (8, 8));
+    assert_eq!(texture.pixels(), rgba);
+}
+
+#[wasm_bindgen_test]
+fn errors_are_thrown() {
+    let rgba = pixels();
+    assert!(encode_gvr(&rgba, 8, 8, r#"{"format": "rgb666"}"#).is_err());
+    assert!(encode_gvr(&rgba[4..], 8, 8, "").is_err());
+    assert!(decode_gvr(&rgba).is_err());
+}
@@ -33,6 +33,13 @@ pub fn gvr_encoder_base(attr: TokenStream, input: TokenStream) -> TokenStream {
     let expanded = quote! {
         #input
 
+        impl #name {
+            /// The size of the blocks the data format stores its pixels in, for the block
+            /// iterators.
+            const BLOCK_SIZE: crate::iter::ConstBlockSize<#x_block, #y_block> =
+                crate::iter::ConstBlockSize;
+        }
+
         impl GvrBase for #name {
             fn get_block_size(&self) -> (u32, u32) {
                 (#x_block, #y_block)
@@ -59,6 +66,13 @@ pub fn gvr_decoder_base(attr: TokenStream, input: TokenStream) -> TokenStream {
     let expanded = quote! {
         #input
 
+        impl #name {
+            /// The size of the blocks the data format stores its pixels in, for the block
+            /// iterators.
+            const BLOCK_SIZE: crate::iter::ConstBlockSize<#x_block, #y_block> =
+                crate::iter::ConstBlockSize;
+        }
+
         impl GvrBase for #name {
             fn get_block_size(&self) -> (u32, u32) {
                 (#x_block, #y_block)